@@ -1,12 +1,14 @@
 use engine_3d::{
-    assets::{image_importer::ImageImporter, model_importer::Modelmporter}, graphics::{
+    assets::{AssetServer, Vfs}, graphics::{
         self as graphics,
         objects::{
             buffers::FramebufferAttachment,
+            model::Model,
             texture::{Filter, Texture2D, Texture2DBuilder},
+            vertex::ModelVertex,
             viewport::Viewport,
         },
-    }, math::Vec3, post_processing::{DistanceFog, FogSystem, PostProcessing, PostProcessingContainer, PostProcessingSystem, SimpleColorProcessing, Tonemapping}, rendering::{camera::{projection::{Perspective, Projection}, Camera, CameraTransform, MainCamera, OnResizeEvent}, composition::CompositionSystem, light::{Light, LightProperties, Sun}, lit_shading::DeferredPath, material::Material, mesh_renderer::MeshRenderer, render_system::RenderSystem}
+    }, image::DynamicImage, math::Vec3, post_processing::{DistanceFog, FogSystem, PostProcessing, PostProcessingContainer, PostProcessingSystem, SimpleColorProcessing, Tonemapping}, rendering::{camera::{projection::{Perspective, Projection}, Camera, CameraTransform, MainCamera, OnResizeEvent}, composition::CompositionSystem, light::{Light, LightProperties, Sun}, lit_shading::DeferredPath, material::Material, mesh_renderer::MeshRenderer, render_system::RenderSystem}
 };
 pub mod white;
 pub mod free_cam;
@@ -14,7 +16,6 @@ pub extern crate self as s;
 pub mod physics;
 pub mod fps_system;
 
-use engine_3d::assets::Assets;
 use engine_3d::math::vec3;
 use engine_3d::specs::*;
 use engine_3d::transform::Transform;
@@ -44,20 +45,22 @@ fn main() {
     let mut win_conf = WINDOW_CONFIG;
     win_conf.icon = None;
     let app = Application::new(win_conf);
-    let mut assets = Assets::from_data_file("assets.data").unwrap();
+    let mut vfs = Vfs::new();
+    vfs.mount_data_file("assets.data").unwrap();
+    let assets = AssetServer::new(vfs);
     let mut world = World::new();
     engine_3d::init(&mut world);
     world.register::<Rigidbody>();
     world.register::<Collision3D>();
     world.register::<Static>();
     let snow_texture = Texture2DBuilder::new()
-        .image(assets.import_image("snow.jpg").unwrap())
+        .image(assets.import::<DynamicImage, _>("snow.jpg").unwrap())
         .filter(Filter::NearestLinearMipMap)
         .build()
         .unwrap();
     snow_texture.gen_mipmaps();
     //bridge
-    let model = assets.import_model("bridge.obj").unwrap();
+    let model = assets.import::<Model<ModelVertex>, _>("bridge.obj").unwrap();
     let transform = Transform::new(
         vec3(0.0, 0.0, 0.0),
         Vec3::new(0.0, 0.0, 0.0),
@@ -79,7 +82,7 @@ fn main() {
         })
         .build();
     //bridge fence
-    let model = assets.import_model("bridge_fence.obj").unwrap();
+    let model = assets.import::<Model<ModelVertex>, _>("bridge_fence.obj").unwrap();
     let transform = Transform::new(
         vec3(0.0, 0.0, 0.0),
         Vec3::new(0.0, 0.0, 0.0),
@@ -101,7 +104,7 @@ fn main() {
         })
         .build();
     //lake terrain
-    let model = assets.import_model("lake_terrain.obj").unwrap();
+    let model = assets.import::<Model<ModelVertex>, _>("lake_terrain.obj").unwrap();
     let transform = Transform::new(
         vec3(0.0, 0.0, 0.0),
         Vec3::new(0.0, 0.0, 0.0),
@@ -123,7 +126,7 @@ fn main() {
         })
         .build();
     //Lake
-    let model = assets.import_model("lake_plane.obj").unwrap();
+    let model = assets.import::<Model<ModelVertex>, _>("lake_plane.obj").unwrap();
     let transform = Transform::new(
         vec3(0.0, 0.0, 0.0),
         Vec3::new(0.0, 0.0, 0.0),
@@ -145,11 +148,11 @@ fn main() {
         })
         .build();
     let miku_texture = Texture2DBuilder::new()
-        .image(assets.import_image("models/Miku_body.png").unwrap())
+        .image(assets.import::<DynamicImage, _>("models/Miku_body.png").unwrap())
         .build()
         .unwrap();
     miku_texture.gen_mipmaps();
-    let miku = assets.import_model("models/Miku.obj").unwrap();
+    let miku = assets.import::<Model<ModelVertex>, _>("models/Miku.obj").unwrap();
     let transform =Transform::new(
         vec3(0.0, 10.0, 0.0),
         Vec3::new(0.0, 0.0, 0.0),
@@ -187,7 +190,7 @@ fn main() {
         let fog = p.get_mut::<DistanceFog>().unwrap();
         fog.apply_to(f,t.clone());
     });
-    let cylinder = assets.import_model("models/cylinder.obj").unwrap();
+    let cylinder = assets.import::<Model<ModelVertex>, _>("models/cylinder.obj").unwrap();
     let main_camera = world
         .create_entity()
         .with({
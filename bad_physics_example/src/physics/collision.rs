@@ -294,18 +294,19 @@ impl MeshShape {
     pub fn new(mesh: Model<ModelVertex>) -> Self {
         let debug = mesh.instantiate();
         let mut triangles = Vec::new();
-        if let Some(indicies) = mesh.indicies {
+        let (verticies, indicies) = mesh.into_parts();
+        if let Some(indicies) = indicies {
             for mut triangle in &indicies.into_iter().chunks(3) {
                 let p1 = triangle.next().unwrap() as usize;
                 let p2 = triangle.next().unwrap() as usize;
                 let p3 = triangle.next().unwrap() as usize;
-                let p1 = mesh.verticies[p1].position.into();
-                let p2 = mesh.verticies[p2].position.into();
-                let p3 = mesh.verticies[p3].position.into();
+                let p1 = verticies[p1].position.into();
+                let p2 = verticies[p2].position.into();
+                let p3 = verticies[p3].position.into();
                 triangles.push(Triangle::new([p1, p2, p3], (p2 - p1).cross(p3 - p1)));
             }
         } else {
-            for mut triangle in &mesh.verticies.into_iter().chunks(3) {
+            for mut triangle in &verticies.into_iter().chunks(3) {
                 let p1 = triangle.next().unwrap().position.into();
                 let p2 = triangle.next().unwrap().position.into();
                 let p3 = triangle.next().unwrap().position.into();
@@ -7,9 +7,11 @@ use engine_3d::{
             face_culling::{self, CullFace},
         },
         objects::{
-            model::{InstancedModel, Model},
+            model::{
+                primitives::{cube, icosphere},
+                InstancedModel,
+            },
             shader::{Shader, ShaderType, SubShader},
-            vertex::ModelVertex,
         },
     },
     math::{vec3, Vec3},
@@ -33,12 +35,8 @@ pub struct DebugCollisions {
 }
 impl DebugCollisions {
     pub fn new(line_width: f32, color: Vec3, non_normal_color: Vec3) -> Self {
-        let _box = Model::<ModelVertex>::from_str(include_str!("box.obj"))
-            .unwrap()
-            .instantiate();
-        let sphere = Model::<ModelVertex>::from_str(include_str!("icosahedron.obj"))
-            .unwrap()
-            .instantiate();
+        let _box = cube(1.0).instantiate();
+        let sphere = icosphere(1.0, 2).instantiate();
         let mut shader = Shader::new([
             SubShader::new(include_str!("collider_vs.glsl"), ShaderType::Vertex),
             SubShader::new(include_str!("collider_fs.glsl"), ShaderType::Fragment),
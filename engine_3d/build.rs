@@ -1,7 +1,7 @@
 use assets::saver::*;
 use std::path::*;
 pub fn main() {
-    println!("cargo:rerun-if-changed=NULL");
+    println!("cargo:rerun-if-changed=build.rs");
     let pre_assets_dir = std::env::var("OUT_DIR").unwrap();
     let mut out_path = Path::new(&pre_assets_dir);
     for _ in 0..5 {
@@ -9,17 +9,48 @@ pub fn main() {
     }
     let assets_dir = out_path.join("./assets");
     println!("{}", assets_dir.display());
-    if cfg!(debug_assertions) {
-        let out_path = Path::new(&out_path).join("./target/debug/assets.data");
-        create_data_file_to(assets_dir.clone(),out_path.clone()).unwrap();
+    let data_file_path = if cfg!(debug_assertions) {
+        Path::new(&out_path).join("./target/debug/assets.data")
     } else {
-        let out_path = Path::new(&out_path).join("./target/release/assets.data");
-
-        create_data_file_to(
-            assets_dir.clone(),
-            out_path,
-        )
-        .unwrap();
+        Path::new(&out_path).join("./target/release/assets.data")
+    };
+    std::fs::create_dir_all(data_file_path.parent().unwrap()).unwrap();
+    //debug builds favour packing speed, release builds favour size
+    let options = if cfg!(debug_assertions) {
+        PackOptions::dev()
+    } else {
+        PackOptions::release()
+    };
+    //only changed files are recompressed, the rest is copied from previous data file
+    let summary = create_data_file_with_progress(
+        assets_dir.clone(),
+        data_file_path.clone(),
+        &options,
+        |progress| match progress {
+            PackProgress::Packed {
+                path,
+                index,
+                files,
+                reused: false,
+                elapsed,
+                ..
+            } => println!(
+                "[{}/{}] {} compressed in {} secs",
+                index + 1,
+                files,
+                path,
+                elapsed.as_secs_f32()
+            ),
+            PackProgress::Finished { elapsed } => {
+                println!("packing taken {} secs", elapsed.as_secs_f32())
+            }
+            _ => (),
+        },
+    )
+    .unwrap();
+    print!("{}", summary);
+    for source in summary.sources.iter() {
+        println!("cargo:rerun-if-changed={}", source.display());
     }
-    create_data_file(assets_dir).unwrap();
+    copy_data_file(data_file_path, assets_dir.parent().unwrap().join("assets.data")).unwrap();
 }
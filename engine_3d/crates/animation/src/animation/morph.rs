@@ -45,11 +45,11 @@ impl<'a> System<'a> for MorphingSystem {
         };
         for (renderer,morph) in (&mut renderer,&morph).join() {
             let mut new_model = morph.base_model.clone();
-            for (vert_i,vert) in new_model.verticies.iter_mut().enumerate() {
+            for (vert_i,vert) in new_model.verticies_mut().iter_mut().enumerate() {
                 let mut new_pos = Vec3::from_array(vert.position);
                 let mut new_normal = Vec3::from_array(vert.normal);
                 for (i,model) in morph.key_frames.iter().enumerate() {
-                    if let Some(frame_vert) = model.verticies.get(vert_i){
+                    if let Some(frame_vert) = model.verticies().get(vert_i){
                         let frame_pos = Vec3::from_array(frame_vert.position);
                         let frame_normal = Vec3::from_array(frame_vert.normal);
                         new_pos = interpolation(new_pos, frame_pos, morph.get_weight(i));
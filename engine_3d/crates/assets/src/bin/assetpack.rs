@@ -0,0 +1,227 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use assets::{
+    saver::{create_data_file_with_progress, PackOptions, PackProgress},
+    normalize_path, AssetError, Assets, Codec,
+};
+
+const USAGE: &str = "usage: assetpack <command>
+    pack <dir> <out> [--dev | --release] [--threads <count>]   create data file from directory, sources of its files
+                                                               are kept in <out>.cache so next pack reuses unchanged ones
+    unpack <pack> <dir>                                        extract every file into directory
+    list <pack>                                                print files with sizes and compression ratios
+    verify <pack>                                              check checksums of data file
+    diff <a> <b>                                               print files added, removed or changed in b
+    cat <pack> <path>                                          write single file to stdout";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let result = match args.as_slice() {
+        ["pack", dir, out, options @ ..] => pack(dir, out, options),
+        ["unpack", pack, dir] => unpack(pack, dir),
+        ["list", pack] => list(pack, &mut io::stdout().lock()),
+        ["verify", pack] => verify(pack),
+        ["diff", a, b] => diff(a, b, &mut io::stdout().lock()),
+        ["cat", pack, path] => cat(pack, path),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    //1 means that packs differ, like with diff
+    match result {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(2)
+        }
+    }
+}
+fn pack(dir: &str, out: &str, args: &[&str]) -> Result<ExitCode, AssetError> {
+    let mut options = PackOptions::new();
+    let mut args = args.iter();
+    while let Some(&arg) = args.next() {
+        options = match arg {
+            "--dev" => PackOptions::dev().threads(options.thread_count()),
+            "--release" => PackOptions::release().threads(options.thread_count()),
+            "--threads" => match args.next().and_then(|count| count.parse().ok()) {
+                Some(count) => options.threads(count),
+                None => return usage_error("--threads expects a number"),
+            },
+            arg => return usage_error(&format!("unknown option {}", arg)),
+        };
+    }
+    let summary = create_data_file_with_progress(dir, out, &options, |progress| match progress {
+        PackProgress::Packed {
+            path,
+            index,
+            files,
+            reused,
+            ..
+        } => eprintln!(
+            "[{}/{}] {} {}",
+            index + 1,
+            files,
+            if reused { "reused" } else { "packed" },
+            path
+        ),
+        PackProgress::Finished { elapsed } => {
+            eprintln!("done in {:.2} secs", elapsed.as_secs_f32())
+        }
+        PackProgress::Started { .. } => (),
+    })?;
+    print!("{}", summary);
+    Ok(ExitCode::SUCCESS)
+}
+fn unpack(pack: &str, dir: &str) -> Result<ExitCode, AssetError> {
+    let assets = Assets::from_data_file(pack)?;
+    for file in assets.files() {
+        //paths going outside of dir are never written by saver, but data file could be crafted
+        let path = normalize_path(file.path()).ok_or(AssetError::FileFindingError)?;
+        let destination = Path::new(dir).join(path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut reader = assets.open_reader(file.path())?;
+        io::copy(&mut reader, &mut fs::File::create(&destination)?)?;
+    }
+    println!("{} files unpacked", assets.files().len());
+    Ok(ExitCode::SUCCESS)
+}
+fn list(pack: &str, stdout: &mut impl Write) -> Result<ExitCode, AssetError> {
+    let assets = Assets::from_data_file(pack)?;
+    let (mut size, mut compressed_size) = (0, 0);
+    for file in assets.files() {
+        writeln!(
+            stdout,
+            "{:>12} {:>12} {:>6.1}% {:<16} {}",
+            file.size(),
+            file.compressed_size(),
+            ratio(file.compressed_size(), file.size()),
+            codec_name(file.codec()),
+            file.path()
+        )?;
+        size += file.size();
+        compressed_size += file.compressed_size();
+    }
+    writeln!(
+        stdout,
+        "{:>12} {:>12} {:>6.1}% {} files",
+        size,
+        compressed_size,
+        ratio(compressed_size, size),
+        assets.files().len()
+    )?;
+    Ok(ExitCode::SUCCESS)
+}
+fn verify(pack: &str) -> Result<ExitCode, AssetError> {
+    let assets = Assets::from_data_file(pack)?;
+    assets.verify()?;
+    println!("{}: {} files, ok", pack, assets.files().len());
+    Ok(ExitCode::SUCCESS)
+}
+///Exits with 1 if packs differ, like diff does
+fn diff(a: &str, b: &str, stdout: &mut impl Write) -> Result<ExitCode, AssetError> {
+    let a = Assets::from_data_file(a)?;
+    let b = Assets::from_data_file(b)?;
+    let mut paths = BTreeMap::new();
+    for file in a.files() {
+        paths.insert(file.path(), (true, false));
+    }
+    for file in b.files() {
+        paths.entry(file.path()).or_insert((false, false)).1 = true;
+    }
+    let mut differs = false;
+    for (path, in_packs) in paths {
+        let status = match in_packs {
+            (true, false) => "removed",
+            (false, true) => "added",
+            _ => {
+                let (a_file, b_file) = (a.find_file(path)?, b.find_file(path)?);
+                if a_file.size() == b_file.size() && a.open_file(path)? == b.open_file(path)? {
+                    continue;
+                }
+                "changed"
+            }
+        };
+        differs = true;
+        writeln!(stdout, "{:<8} {}", status, path)?;
+    }
+    Ok(if differs {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+fn cat(pack: &str, path: &str) -> Result<ExitCode, AssetError> {
+    let assets = Assets::from_data_file(pack)?;
+    let mut reader = assets.open_reader(path)?;
+    io::copy(&mut reader, &mut io::stdout().lock())?;
+    Ok(ExitCode::SUCCESS)
+}
+fn usage_error(message: &str) -> Result<ExitCode, AssetError> {
+    eprintln!("{}\n{}", message, USAGE);
+    Ok(ExitCode::from(2))
+}
+///compressed size in percents of original size
+fn ratio(compressed_size: u64, size: u64) -> f64 {
+    match size {
+        0 => 100.0,
+        size => compressed_size as f64 * 100.0 / size as f64,
+    }
+}
+fn codec_name(codec: Codec) -> &'static str {
+    match codec {
+        Codec::Zstd => "zstd",
+        Codec::ZstdDictionary => "zstd+dictionary",
+        Codec::Stored => "stored",
+        Codec::ZstdLong => "zstd-long",
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn output(command: impl FnOnce(&mut Vec<u8>) -> Result<ExitCode, AssetError>) -> (ExitCode, String) {
+        let mut stdout = Vec::new();
+        let code = command(&mut stdout).unwrap();
+        (code, String::from_utf8(stdout).unwrap())
+    }
+    #[test]
+    fn pack_list_and_diff() {
+        let folder = std::env::temp_dir().join("assetpack_commands");
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(folder.join("assets/text")).unwrap();
+        fs::write(folder.join("assets/text/a.txt"), "a".repeat(1000)).unwrap();
+        fs::write(folder.join("assets/b.bin"), [1, 2, 3]).unwrap();
+        let [dir, a, b] = ["assets", "a.data", "b.data"].map(|name| folder.join(name).to_str().unwrap().to_owned());
+        assert_eq!(pack(&dir, &a, &["--dev", "--threads", "2"]).unwrap(), ExitCode::SUCCESS);
+        assert!(folder.join("a.data.cache").exists());
+        assert_eq!(pack(&dir, &a, &["--threads", "many"]).unwrap(), ExitCode::from(2));
+        assert!(pack(&folder.join("missing").to_string_lossy(), &a, &[]).is_err());
+
+        let (code, listed) = output(|stdout| list(&a, stdout));
+        assert_eq!(code, ExitCode::SUCCESS);
+        let lines = listed.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(" b.bin") && lines[0].trim_start().starts_with("3 "));
+        assert!(lines[1].ends_with(" text/a.txt") && lines[1].trim_start().starts_with("1000 "));
+        assert!(lines[2].ends_with(" 2 files"));
+
+        fs::write(folder.join("assets/text/a.txt"), "b".repeat(1000)).unwrap();
+        fs::write(folder.join("assets/c.txt"), "c").unwrap();
+        fs::remove_file(folder.join("assets/b.bin")).unwrap();
+        pack(&dir, &b, &[]).unwrap();
+        let (code, differences) = output(|stdout| diff(&a, &b, stdout));
+        assert_eq!(code, ExitCode::FAILURE);
+        assert_eq!(differences, "removed  b.bin\nadded    c.txt\nchanged  text/a.txt\n");
+        assert_eq!(output(|stdout| diff(&b, &b, stdout)), (ExitCode::SUCCESS, String::new()));
+        assert!(diff(&a, &folder.join("missing.data").to_string_lossy(), &mut Vec::new()).is_err());
+    }
+}
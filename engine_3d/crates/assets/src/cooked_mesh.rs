@@ -0,0 +1,221 @@
+use std::mem::{offset_of, size_of};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use graphics::objects::{
+    model::{
+        bounds::{Aabb, BoundingSphere, Bounds},
+        obj::ObjError,
+        Model,
+    },
+    vertex::ModelVertex,
+};
+use math::Vec3;
+
+use crate::{model_importer::parse_model, AsyncImporter, ImportContext, ImportError};
+
+///first bytes of every cooked mesh
+pub const MESH_MAGIC: [u8; 8] = *b"E3DMESH\0";
+///version of cooked mesh layout, cooked meshes of other versions are ignored and obj is parsed instead
+pub const MESH_VERSION: u32 = 2;
+///cooked version of "models/bunny.obj" is stored as "models/bunny.obj.mesh"
+pub const COOKED_EXTENSION: &str = "mesh";
+const NO_INDICES: u64 = u64::MAX;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeKind {
+    Position = 0,
+    Normal = 1,
+    TextureCoords = 2,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentType {
+    F32 = 0,
+}
+///Single attribute of vertex blob, offsets follow from the order of attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attribute {
+    pub kind: AttributeKind,
+    pub component_type: ComponentType,
+    pub count: u8,
+}
+///Layout of ModelVertex, cooked meshes with different layout aren't loaded
+pub const MODEL_VERTEX_LAYOUT: [Attribute; 3] = [
+    Attribute { kind: AttributeKind::Position, component_type: ComponentType::F32, count: 3 },
+    Attribute { kind: AttributeKind::Normal, component_type: ComponentType::F32, count: 3 },
+    Attribute { kind: AttributeKind::TextureCoords, component_type: ComponentType::F32, count: 2 },
+];
+//vertex blob is copied into Vec<ModelVertex> as is, so layout above must match the struct
+const _: () = assert!(
+    size_of::<ModelVertex>() == 32
+        && offset_of!(ModelVertex, position) == 0
+        && offset_of!(ModelVertex, normal) == 12
+        && offset_of!(ModelVertex, texture_coords) == 24
+);
+///Axis aligned box around every vertex of cooked mesh and radius of sphere around them centered at center of box,
+///same as Model::bounds
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MeshBounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+    pub radius: f32,
+}
+impl MeshBounds {
+    pub fn of(vertices: &[ModelVertex]) -> Self {
+        Bounds::from_points(vertices.iter().map(|vertex| Vec3::from(vertex.position))).map_or_else(
+            Self::default,
+            |bounds| Self {
+                min: bounds.aabb.min.into(),
+                max: bounds.aabb.max.into(),
+                radius: bounds.sphere.radius,
+            },
+        )
+    }
+    pub fn bounds(&self) -> Bounds {
+        let aabb = Aabb { min: self.min.into(), max: self.max.into() };
+        Bounds { aabb, sphere: BoundingSphere { center: aabb.center(), radius: self.radius } }
+    }
+}
+/// Cooked mesh structure is next (all numbers are little endian, so blobs can be copied as is):
+/// Header {
+///     magic (8 bytes)
+///     version (u32)
+///     count of attributes (u32)
+///     Array of attributes {kind (u8), component type (u8), component count (u8), padding (u8)}
+///     vertex stride (u32)
+///     count of vertices (u64)
+///     count of indices, u64::MAX if model has no indices (u64)
+///     bounds min and max ([f32;3] each), radius of bounding sphere (f32)
+/// }
+/// vertex blob
+/// index blob (u32 each)
+pub fn cook_mesh(model: &Model<ModelVertex>) -> Vec<u8> {
+    let bounds = MeshBounds::of(model.verticies());
+    let mut data = Vec::with_capacity(
+        68 + model.verticies().len() * size_of::<ModelVertex>()
+            + model.indicies().map_or(0, |indices| indices.len() * 4),
+    );
+    //writing into Vec can't fail
+    data.extend_from_slice(&MESH_MAGIC);
+    data.write_u32::<LE>(MESH_VERSION).unwrap();
+    data.write_u32::<LE>(MODEL_VERTEX_LAYOUT.len() as u32).unwrap();
+    for attribute in MODEL_VERTEX_LAYOUT {
+        data.extend_from_slice(&[attribute.kind as u8, attribute.component_type as u8, attribute.count, 0]);
+    }
+    data.write_u32::<LE>(size_of::<ModelVertex>() as u32).unwrap();
+    data.write_u64::<LE>(model.verticies().len() as u64).unwrap();
+    data.write_u64::<LE>(model.indicies().map_or(NO_INDICES, |indices| indices.len() as u64))
+        .unwrap();
+    for value in bounds.min.iter().chain(bounds.max.iter()).chain([&bounds.radius]) {
+        data.write_f32::<LE>(*value).unwrap();
+    }
+    for vertex in model.verticies() {
+        for value in vertex.position.iter().chain(&vertex.normal).chain(&vertex.texture_coords) {
+            data.write_f32::<LE>(*value).unwrap();
+        }
+    }
+    for index in model.indicies().into_iter().flatten() {
+        data.write_u32::<LE>(*index).unwrap();
+    }
+    data
+}
+///Parses obj source and cooks it, used by saver
+pub fn cook_obj(source: &str) -> Result<Vec<u8>, ObjError> {
+    Ok(cook_mesh(&parse_model(source)?))
+}
+///Reads cooked mesh, vertex and index blobs are copied with a single memcpy each.
+///Bounds of model are taken from header
+pub fn read_mesh(data: &[u8]) -> Result<(Model<ModelVertex>, MeshBounds), ImportError> {
+    let mut header = data;
+    if header.get(..8) != Some(&MESH_MAGIC[..]) {
+        return Err("not a cooked mesh".into());
+    }
+    header = &header[8..];
+    let version = header.read_u32::<LE>()?;
+    if version != MESH_VERSION {
+        return Err(format!("cooked mesh version {} isn't supported, expected {}", version, MESH_VERSION).into());
+    }
+    let attribute_count = header.read_u32::<LE>()? as usize;
+    let mut layout = Vec::with_capacity(attribute_count.min(16));
+    for _ in 0..attribute_count {
+        let mut attribute = [0u8; 4];
+        std::io::Read::read_exact(&mut header, &mut attribute)?;
+        layout.push(attribute);
+    }
+    let expected = MODEL_VERTEX_LAYOUT.map(|a| [a.kind as u8, a.component_type as u8, a.count, 0]);
+    let stride = header.read_u32::<LE>()? as usize;
+    if layout != expected || stride != size_of::<ModelVertex>() {
+        return Err("vertex layout of cooked mesh doesn't match ModelVertex".into());
+    }
+    let vertex_count = header.read_u64::<LE>()?;
+    let index_count = header.read_u64::<LE>()?;
+    let mut bounds = MeshBounds::default();
+    for value in bounds.min.iter_mut().chain(bounds.max.iter_mut()).chain([&mut bounds.radius]) {
+        *value = header.read_f32::<LE>()?;
+    }
+    let vertex_size = usize::try_from(vertex_count).ok().and_then(|count| count.checked_mul(stride));
+    let index_size = match index_count {
+        NO_INDICES => Some(0),
+        count => usize::try_from(count).ok().and_then(|count| count.checked_mul(4)),
+    };
+    let (Some(vertex_size), Some(index_size)) = (vertex_size, index_size) else {
+        return Err("cooked mesh is too large".into());
+    };
+    if vertex_size.checked_add(index_size) != Some(header.len()) {
+        return Err("size of cooked mesh doesn't match its header".into());
+    }
+    let (vertex_blob, index_blob) = header.split_at(vertex_size);
+    let mut verticies = Vec::<ModelVertex>::with_capacity(vertex_count as usize);
+    let mut indicies = Vec::<u32>::with_capacity(index_size / 4);
+    //SAFETY: ModelVertex is repr(C) made only of f32 (checked above), every bit pattern is valid
+    //and capacity of both vectors is exactly the size of their blob
+    unsafe {
+        std::ptr::copy_nonoverlapping(vertex_blob.as_ptr(), verticies.as_mut_ptr() as *mut u8, vertex_size);
+        verticies.set_len(vertex_count as usize);
+        std::ptr::copy_nonoverlapping(index_blob.as_ptr(), indicies.as_mut_ptr() as *mut u8, index_size);
+        indicies.set_len(index_size / 4);
+    }
+    if cfg!(target_endian = "big") {
+        for vertex in &mut verticies {
+            for value in vertex.position.iter_mut().chain(&mut vertex.normal).chain(&mut vertex.texture_coords) {
+                *value = f32::from_bits(u32::from_le(value.to_bits()));
+            }
+        }
+        for index in &mut indicies {
+            *index = u32::from_le(*index);
+        }
+    }
+    let indicies = (index_count != NO_INDICES).then_some(indicies);
+    //stored bounds save a pass over vertices when bounds of model are needed
+    let model_bounds = (vertex_count > 0).then(|| bounds.bounds());
+    Ok((Model::new(verticies, indicies).with_bounds(model_bounds), bounds))
+}
+///Returns cooked version of obj model if there is one and it isn't overridden by newer obj,
+///obj in a loose directory mounted over data file is newer than cooked mesh in that data file
+pub(crate) fn find_cooked(context: &ImportContext) -> Option<Model<ModelVertex>> {
+    let path = format!("{}.{}", context.path(), COOKED_EXTENSION);
+    let (cooked_layer, _) = context.source().find_file_in(&path).ok()?;
+    let (layer, _) = context.source().find_file_in(context.path()).ok()?;
+    if cooked_layer < layer {
+        return None;
+    }
+    //broken or outdated cooked mesh isn't an error, obj is parsed instead
+    let data = context.source().open_file(&path).ok()?;
+    read_mesh(&data).ok().map(|(model, _)| model)
+}
+///Cooked meshes produced by saver
+pub struct MeshImporter;
+impl AsyncImporter for MeshImporter {
+    type Decoded = Model<ModelVertex>;
+    type Output = Model<ModelVertex>;
+    fn name(&self) -> &'static str {
+        "mesh"
+    }
+    fn extensions(&self) -> &[&str] {
+        &[COOKED_EXTENSION]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<Model<ModelVertex>, ImportError> {
+        Ok(read_mesh(&context.read()?)?.0)
+    }
+    fn finish(&self, decoded: Model<ModelVertex>) -> Result<Model<ModelVertex>, ImportError> {
+        Ok(decoded)
+    }
+}
@@ -1,10 +1,12 @@
-use std::path::Path;
+use std::path::{Component, Path};
 
 //use crate::{AssetError, Assets};
 
 pub trait PathEXT {
     fn get_name(&self) -> Option<String>;
     fn to_string(&self) -> Option<String>;
+    ///path in the form it's stored in data file: relative, separated with '/'
+    fn to_asset_path(&self) -> Option<String>;
 }
 impl PathEXT for Path {
     fn get_name(&self) -> Option<String> {
@@ -13,10 +15,56 @@ impl PathEXT for Path {
     fn to_string(&self) -> Option<String> {
         Some(self.as_os_str().to_str().unwrap().to_owned())
     }
+    fn to_asset_path(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        for component in self.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_str()?),
+                Component::CurDir => (),
+                _ => return None,
+            }
+        }
+        normalize_path(&parts.join("/"))
+    }
+}
+///Brings path to the form it's stored in data file: "./models\\cube.obj/" becomes "models/cube.obj".
+///Returns None for absolute paths and paths going outside with ".."
+pub fn normalize_path(path: &str) -> Option<String> {
+    if path.starts_with(['/', '\\']) {
+        return None;
+    }
+    let mut parts = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => (),
+            ".." => return None,
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
 }
-/* TODO:For future 
-pub trait Importer{
-    type T;
-    fn load<P:AsRef<Path>>(assets:&mut Assets,path:P) ->Result<Self::T,AssetError>;
+///Matches path separated with '/' against glob pattern.
+///'*' and '?' match within single path component, "**" matches any amount of components.
+///Both pattern and path may use backslashes and "./" like in normalize_path
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+        match pattern.split_first() {
+            None => path.is_empty(),
+            Some((&"**", rest)) => (0..=path.len()).any(|i| match_components(rest, &path[i..])),
+            Some((component, rest)) => path.split_first().is_some_and(|(first, path)| {
+                match_component(component.as_bytes(), first.as_bytes()) && match_components(rest, path)
+            }),
+        }
+    }
+    fn match_component(pattern: &[u8], name: &[u8]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some((b'*', rest)) => (0..=name.len()).any(|i| match_component(rest, &name[i..])),
+            Some((b'?', rest)) => !name.is_empty() && match_component(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && match_component(rest, &name[1..]),
+        }
+    }
+    let pattern = pattern.split(['/', '\\']).filter(|c| !matches!(*c, "" | ".")).collect::<Vec<_>>();
+    let path = path.split(['/', '\\']).filter(|c| !matches!(*c, "" | ".")).collect::<Vec<_>>();
+    match_components(&pattern, &path)
 }
-*/
\ No newline at end of file
@@ -0,0 +1,366 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use graphics::objects::{model::Model, texture::Texture2D, vertex::ModelVertex};
+use gltf::{
+    animation::{util::ReadOutputs, Interpolation, Property},
+    buffer, image, material::AlphaMode,
+    mesh::Mode,
+    Document, Gltf,
+};
+use math::{Mat4, Quat, Vec3};
+use rendering::material::Material;
+
+use crate::{image_importer::upload_texture, AsyncImporter, ImportContext, ImportError, TextureSettings};
+
+///glTF 2.0 scene, indices in it are indices of arrays in the same scene
+pub struct GltfScene {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<Material>,
+    pub nodes: Vec<GltfNode>,
+    ///nodes of default scene
+    pub roots: Vec<usize>,
+    pub skins: Vec<GltfSkin>,
+    pub animations: Vec<GltfAnimation>,
+}
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+///Part of mesh with single material, only triangles are imported
+pub struct GltfPrimitive {
+    pub model: Model<ModelVertex>,
+    pub material: Option<usize>,
+    ///joints of skin influencing every vertex
+    pub joints: Option<Vec<[u16; 4]>>,
+    pub weights: Option<Vec<[f32; 4]>>,
+}
+#[derive(Debug, Clone)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub children: Vec<usize>,
+    pub mesh: Option<usize>,
+    pub skin: Option<usize>,
+}
+impl GltfNode {
+    ///transform relative to parent
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+#[derive(Debug, Clone)]
+pub struct GltfSkin {
+    pub name: Option<String>,
+    ///nodes used as joints
+    pub joints: Vec<usize>,
+    ///identity matrices if file doesn't have them
+    pub inverse_bind_matrices: Vec<Mat4>,
+    pub skeleton: Option<usize>,
+}
+#[derive(Debug, Clone)]
+pub struct GltfAnimation {
+    pub name: Option<String>,
+    pub channels: Vec<GltfChannel>,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfProperty {
+    Translation,
+    Rotation,
+    Scale,
+    MorphWeights,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GltfInterpolation {
+    Linear,
+    Step,
+    CubicSpline,
+}
+///Keyframes of one property of one node
+#[derive(Debug, Clone)]
+pub struct GltfChannel {
+    pub node: usize,
+    pub property: GltfProperty,
+    pub interpolation: GltfInterpolation,
+    ///seconds
+    pub times: Vec<f32>,
+    ///Flattened values, 3 per key for translation and scale, 4 for rotation (xyzw quaternion),
+    ///count of morph targets for weights. Cubic spline keys have in tangent, value and out tangent
+    pub values: Vec<f32>,
+}
+///Material description, textures are indices of images in DecodedGltf
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color: [f32; 4],
+    pub base_color_texture: Option<usize>,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub normal_texture: Option<usize>,
+    pub blend: bool,
+}
+impl GltfMaterial {
+    ///Approximation with Blinn-Phong of the renderer
+    pub fn material(&self, textures: &[Texture2D]) -> Material {
+        let texture = |index: Option<usize>| index.and_then(|index| textures.get(index)).cloned();
+        let [r, g, b, a] = self.base_color;
+        let roughness = self.roughness.max(0.01);
+        Material {
+            color: Vec3::new(r, g, b),
+            main_texture: texture(self.base_color_texture).unwrap_or_else(Texture2D::white),
+            specular_map: Texture2D::white(),
+            specular: 1.0 - roughness,
+            //geometry pass can't store more
+            shininess: (2.0 / roughness.powi(4) - 2.0).clamp(1.0, 256.0),
+            transparent: self.blend || a < 1.0,
+        }
+    }
+}
+///glTF decoded on worker thread, images are uploaded in finish
+pub struct DecodedGltf {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub images: Vec<::image::DynamicImage>,
+    pub nodes: Vec<GltfNode>,
+    pub roots: Vec<usize>,
+    pub skins: Vec<GltfSkin>,
+    pub animations: Vec<GltfAnimation>,
+}
+///.gltf with .bin files or data uris and .glb,
+///finish uploads textures so it must be called on the thread with GL context
+pub struct GltfImporter;
+impl AsyncImporter for GltfImporter {
+    type Decoded = DecodedGltf;
+    type Output = GltfScene;
+    fn name(&self) -> &'static str {
+        "gltf"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<DecodedGltf, ImportError> {
+        let Gltf { document, blob } = Gltf::from_slice(&context.read()?)?;
+        let mut buffers = Vec::new();
+        for buffer in document.buffers() {
+            let mut data = match buffer.source() {
+                buffer::Source::Bin => blob.clone().ok_or("glb has no binary chunk")?,
+                buffer::Source::Uri(uri) => read_uri(context, uri)?,
+            };
+            if data.len() < buffer.length() {
+                return Err(format!("buffer {} is shorter than declared", buffer.index()).into());
+            }
+            //padding of glb chunk
+            data.truncate(buffer.length());
+            buffers.push(data);
+        }
+        let mut images = Vec::new();
+        for image in document.images() {
+            let data = match image.source() {
+                image::Source::Uri { uri, .. } => read_uri(context, uri)?,
+                image::Source::View { view, .. } => buffers[view.buffer().index()]
+                    .get(view.offset()..view.offset() + view.length())
+                    .ok_or("image is outside of buffer")?
+                    .to_vec(),
+            };
+            images.push(::image::load_from_memory(&data)?.flipv());
+        }
+        Ok(DecodedGltf {
+            meshes: read_meshes(&document, &buffers)?,
+            materials: read_materials(&document),
+            images,
+            nodes: read_nodes(&document),
+            roots: read_roots(&document),
+            skins: read_skins(&document, &buffers),
+            animations: read_animations(&document, &buffers)?,
+        })
+    }
+    fn finish(&self, decoded: DecodedGltf) -> Result<GltfScene, ImportError> {
+        let mut textures = Vec::new();
+        for (i, image) in decoded.images.into_iter().enumerate() {
+            textures.push(upload_texture(image, &TextureSettings::default(), &format!("image {}", i))?);
+        }
+        Ok(GltfScene {
+            meshes: decoded.meshes,
+            materials: decoded.materials.iter().map(|material| material.material(&textures)).collect(),
+            nodes: decoded.nodes,
+            roots: decoded.roots,
+            skins: decoded.skins,
+            animations: decoded.animations,
+        })
+    }
+}
+///Reads embedded base64 data or file relative to glTF
+fn read_uri(context: &ImportContext, uri: &str) -> Result<Vec<u8>, ImportError> {
+    match uri.strip_prefix("data:") {
+        Some(data) => {
+            let (_, data) = data.split_once(";base64,").ok_or("only base64 data uris are supported")?;
+            Ok(STANDARD.decode(data)?)
+        }
+        None => Ok(context.read_relative(&uri.replace("%20", " "))?),
+    }
+}
+fn read_meshes(document: &Document, buffers: &[Vec<u8>]) -> Result<Vec<GltfMesh>, ImportError> {
+    let mut meshes = Vec::new();
+    for mesh in document.meshes() {
+        let mut primitives = Vec::new();
+        for primitive in mesh.primitives().filter(|primitive| primitive.mode() == Mode::Triangles) {
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+            let positions = reader
+                .read_positions()
+                .ok_or_else(|| format!("primitive of mesh {} has no positions", mesh.index()))?
+                .collect::<Vec<_>>();
+            let count = positions.len();
+            let normals = reader.read_normals().map(Iterator::collect::<Vec<_>>);
+            //glTF uv starts at top left corner, images are flipped when decoded
+            let uvs = reader
+                .read_tex_coords(0)
+                .map(|uvs| uvs.into_f32().map(|[u, v]| [u, 1.0 - v]).collect::<Vec<_>>())
+                .unwrap_or_else(|| vec![[0.0; 2]; count]);
+            let mut joints = reader.read_joints(0).map(|joints| joints.into_u16().collect::<Vec<_>>());
+            let mut weights = reader.read_weights(0).map(|weights| weights.into_f32().collect::<Vec<_>>());
+            let mut indices = reader
+                .read_indices()
+                .map(|indices| indices.into_u32().collect::<Vec<_>>())
+                .unwrap_or_else(|| (0..count as u32).collect());
+            let lengths = [Some(uvs.len()), normals.as_ref().map(Vec::len), joints.as_ref().map(Vec::len), weights.as_ref().map(Vec::len)];
+            if indices.iter().any(|&index| index as usize >= count) || lengths.into_iter().flatten().any(|len| len != count) {
+                return Err(format!("primitive of mesh {} has invalid accessors", mesh.index()).into());
+            }
+            let vertices = match normals {
+                Some(normals) => (0..count)
+                    .map(|i| ModelVertex::new(positions[i], normals[i], uvs[i]))
+                    .collect(),
+                //flat normals, every triangle gets own vertices
+                None => {
+                    let mut vertices = Vec::with_capacity(indices.len());
+                    for triangle in indices.chunks_exact(3) {
+                        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[triangle[i] as usize]));
+                        let normal = (b - a).cross(c - a).normalize_or_zero().to_array();
+                        for &index in triangle {
+                            vertices.push(ModelVertex::new(positions[index as usize], normal, uvs[index as usize]));
+                        }
+                    }
+                    joints = joints.map(|joints| unindex(&joints, &indices));
+                    weights = weights.map(|weights| unindex(&weights, &indices));
+                    indices = (0..vertices.len() as u32).collect();
+                    vertices
+                }
+            };
+            primitives.push(GltfPrimitive {
+                model: Model::new(vertices, Some(indices)),
+                material: primitive.material().index(),
+                joints,
+                weights,
+            });
+        }
+        meshes.push(GltfMesh {
+            name: mesh.name().map(str::to_owned),
+            primitives,
+        });
+    }
+    Ok(meshes)
+}
+fn unindex<T: Copy>(values: &[T], indices: &[u32]) -> Vec<T> {
+    indices.iter().map(|&index| values[index as usize]).collect()
+}
+fn read_materials(document: &Document) -> Vec<GltfMaterial> {
+    document
+        .materials()
+        .map(|material| {
+            let pbr = material.pbr_metallic_roughness();
+            GltfMaterial {
+                name: material.name().map(str::to_owned),
+                base_color: pbr.base_color_factor(),
+                base_color_texture: pbr.base_color_texture().map(|info| info.texture().source().index()),
+                metallic: pbr.metallic_factor(),
+                roughness: pbr.roughness_factor(),
+                normal_texture: material.normal_texture().map(|info| info.texture().source().index()),
+                blend: material.alpha_mode() == AlphaMode::Blend,
+            }
+        })
+        .collect()
+}
+fn read_nodes(document: &Document) -> Vec<GltfNode> {
+    document
+        .nodes()
+        .map(|node| {
+            let (translation, rotation, scale) = node.transform().decomposed();
+            GltfNode {
+                name: node.name().map(str::to_owned),
+                translation: translation.into(),
+                rotation: Quat::from_array(rotation),
+                scale: scale.into(),
+                children: node.children().map(|child| child.index()).collect(),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                skin: node.skin().map(|skin| skin.index()),
+            }
+        })
+        .collect()
+}
+///nodes of default scene, or nodes without parents if file has no scenes
+fn read_roots(document: &Document) -> Vec<usize> {
+    match document.default_scene().or_else(|| document.scenes().next()) {
+        Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+        None => {
+            let children = document.nodes().flat_map(|node| node.children()).map(|node| node.index()).collect::<Vec<_>>();
+            (0..document.nodes().len()).filter(|node| !children.contains(node)).collect()
+        }
+    }
+}
+fn read_skins(document: &Document, buffers: &[Vec<u8>]) -> Vec<GltfSkin> {
+    document
+        .skins()
+        .map(|skin| {
+            let joints = skin.joints().map(|joint| joint.index()).collect::<Vec<_>>();
+            let reader = skin.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+            let inverse_bind_matrices = match reader.read_inverse_bind_matrices() {
+                Some(matrices) => matrices.map(|matrix| Mat4::from_cols_array_2d(&matrix)).collect(),
+                None => vec![Mat4::IDENTITY; joints.len()],
+            };
+            GltfSkin {
+                name: skin.name().map(str::to_owned),
+                joints,
+                inverse_bind_matrices,
+                skeleton: skin.skeleton().map(|node| node.index()),
+            }
+        })
+        .collect()
+}
+fn read_animations(document: &Document, buffers: &[Vec<u8>]) -> Result<Vec<GltfAnimation>, ImportError> {
+    let mut animations = Vec::new();
+    for animation in document.animations() {
+        let mut channels = Vec::new();
+        for channel in animation.channels() {
+            let reader = channel.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+            let missing = || format!("channel {} of animation {} has no keys", channel.index(), animation.index());
+            let times = reader.read_inputs().ok_or_else(missing)?.collect();
+            let values = match reader.read_outputs().ok_or_else(missing)? {
+                ReadOutputs::Translations(values) => values.flatten().collect(),
+                ReadOutputs::Rotations(values) => values.into_f32().flatten().collect(),
+                ReadOutputs::Scales(values) => values.flatten().collect(),
+                ReadOutputs::MorphTargetWeights(values) => values.into_f32().collect(),
+            };
+            channels.push(GltfChannel {
+                node: channel.target().node().index(),
+                property: match channel.target().property() {
+                    Property::Translation => GltfProperty::Translation,
+                    Property::Rotation => GltfProperty::Rotation,
+                    Property::Scale => GltfProperty::Scale,
+                    Property::MorphTargetWeights => GltfProperty::MorphWeights,
+                },
+                interpolation: match channel.sampler().interpolation() {
+                    Interpolation::Linear => GltfInterpolation::Linear,
+                    Interpolation::Step => GltfInterpolation::Step,
+                    Interpolation::CubicSpline => GltfInterpolation::CubicSpline,
+                },
+                times,
+                values,
+            });
+        }
+        animations.push(GltfAnimation {
+            name: animation.name().map(str::to_owned),
+            channels,
+        });
+    }
+    Ok(animations)
+}
@@ -1,17 +1,70 @@
 use std::path::Path;
 
+use graphics::objects::texture::{Texture2D, Texture2DBuilder};
 use image::DynamicImage;
 
-use crate::{AssetError, Assets};
+use crate::{AssetError, AsyncImporter, ImportContext, ImportError, TextureSettings};
 
-pub trait ImageImporter {
-    fn import_image<P: AsRef<Path>>(&mut self, path: P) -> Result<DynamicImage, AssetError>;
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "bmp", "tga", "gif", "tiff", "webp", "ico", "hdr", "pnm",
+];
+///Decodes image, format is taken from extension. Image is flipped and downscaled as its meta file says
+pub struct ImageImporter;
+impl AsyncImporter for ImageImporter {
+    type Decoded = DynamicImage;
+    type Output = DynamicImage;
+    fn name(&self) -> &'static str {
+        "image"
+    }
+    fn extensions(&self) -> &[&str] {
+        IMAGE_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<DynamicImage, ImportError> {
+        let settings = context.settings()?.texture;
+        let image = decode_image(&context.read()?, Path::new(context.path()))?;
+        Ok(settings.apply_to_image(image))
+    }
+    fn finish(&self, decoded: DynamicImage) -> Result<DynamicImage, ImportError> {
+        Ok(decoded)
+    }
+}
+///Uploads image with settings from its meta file, finish must be called on the thread with GL context
+pub struct TextureImporter;
+impl AsyncImporter for TextureImporter {
+    type Decoded = (DynamicImage, TextureSettings, String);
+    type Output = Texture2D;
+    fn name(&self) -> &'static str {
+        "texture"
+    }
+    fn extensions(&self) -> &[&str] {
+        IMAGE_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<(DynamicImage, TextureSettings, String), ImportError> {
+        Ok((ImageImporter.decode(context)?, context.settings()?.texture, context.path().to_owned()))
+    }
+    fn finish(&self, (image, settings, path): (DynamicImage, TextureSettings, String)) -> Result<Texture2D, ImportError> {
+        Ok(upload_texture(image, &settings, &path)?)
+    }
 }
-impl ImageImporter for Assets {
-    fn import_image<P: AsRef<Path>>(&mut self, path: P) -> Result<DynamicImage, AssetError> {
-        Ok(image::load_from_memory_with_format(
-            &self.open_file(&path)?,
-            image::ImageFormat::from_extension(path.as_ref().extension().ok_or(AssetError::AssetImportingError)?.to_str().ok_or(AssetError::AssetImportingError)?).ok_or(AssetError::AssetImportingError)?,
-        )?.flipv())
+///image should already be flipped and downscaled with TextureSettings::apply_to_image,
+///path is only used in error
+pub(crate) fn upload_texture(image: DynamicImage, settings: &TextureSettings, path: &str) -> Result<Texture2D, AssetError> {
+    let texture = settings
+        .apply(Texture2DBuilder::new().image(image))
+        .build()
+        .map_err(|error| AssetError::TextureUploadFailed { path: path.to_owned(), error })?;
+    if settings.mipmaps {
+        texture.gen_mipmaps();
     }
+    Ok(texture)
+}
+///format is taken from extension of path, image isn't flipped
+pub(crate) fn decode_image(data: &[u8], path: &Path) -> Result<DynamicImage, AssetError> {
+    let path_string = || path.to_string_lossy().into_owned();
+    let format = path
+        .extension()
+        .and_then(image::ImageFormat::from_extension)
+        .ok_or_else(|| AssetError::UnknownImageFormat { path: path_string() })?;
+    image::load_from_memory_with_format(data, format)
+        .map_err(|error| AssetError::ImageDecodingFailed { path: path_string(), error })
 }
@@ -0,0 +1,274 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    error::Error,
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+};
+
+use crate::{ext::normalize_path, AssetError, ImportSettings, Vfs};
+
+pub type ImportError = Box<dyn Error + Send + Sync>;
+///Converts file into asset of type Output.
+///Importers are registered in Importers and chosen by extension of file and requested type
+pub trait Importer: 'static {
+    type Output: 'static;
+    ///used in error messages
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+    ///extensions without dot in lower case, "*" matches any file
+    fn extensions(&self) -> &[&str];
+    fn import(&self, context: &ImportContext) -> Result<Self::Output, ImportError>;
+}
+///Importer that decodes on worker threads and finishes on main thread, see AssetServer::load_async.
+///It's also used by blocking loads, decode and finish are just called one after another
+pub trait AsyncImporter: Send + Sync + 'static {
+    ///what is sent from worker thread to main thread
+    type Decoded: Send + 'static;
+    type Output: 'static;
+    ///used in error messages
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+    ///extensions without dot in lower case, "*" matches any file
+    fn extensions(&self) -> &[&str];
+    ///Reading, decompressing and decoding, runs on worker thread
+    fn decode(&self, context: &ImportContext) -> Result<Self::Decoded, ImportError>;
+    ///Runs on main thread with GL context, should be fast
+    fn finish(&self, decoded: Self::Decoded) -> Result<Self::Output, ImportError>;
+}
+///What importer gets to know about imported file
+pub struct ImportContext<'a> {
+    path: &'a str,
+    source: &'a Vfs,
+}
+impl<'a> ImportContext<'a> {
+    pub fn new(path: &'a str, source: &'a Vfs) -> Self {
+        Self { path, source }
+    }
+    ///path of imported file
+    pub fn path(&self) -> &str {
+        self.path
+    }
+    pub fn source(&self) -> &Vfs {
+        self.source
+    }
+    ///lower case extension of imported file
+    pub fn extension(&self) -> Option<String> {
+        extension(self.path)
+    }
+    ///Settings from meta file of imported file, defaults if it has none
+    pub fn settings(&self) -> Result<ImportSettings, ImportError> {
+        ImportSettings::load(self.source, self.path)
+    }
+    ///content of imported file
+    pub fn read(&self) -> Result<Vec<u8>, AssetError> {
+        self.source.open_file(self.path)
+    }
+    pub fn read_string(&self) -> Result<String, AssetError> {
+        self.source.open_file_string(self.path)
+    }
+    ///Path of file referenced by imported file, relative paths start in directory of imported file.
+    ///".." can't go above root of assets
+    pub fn resolve(&self, path: &str) -> Result<String, AssetError> {
+        let directory = self.path.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let mut components = Vec::new();
+        for component in directory.split('/').chain(path.split(['/', '\\'])) {
+            match component {
+                "" | "." => (),
+                ".." => {
+                    components.pop().ok_or(AssetError::FileFindingError)?;
+                }
+                component => components.push(component),
+            }
+        }
+        normalize_path(&components.join("/")).ok_or(AssetError::FileFindingError)
+    }
+    ///Reads file referenced by imported file, see resolve
+    pub fn read_relative(&self, path: &str) -> Result<Vec<u8>, AssetError> {
+        self.source.open_file(self.resolve(path)?)
+    }
+}
+fn extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+}
+trait ErasedImporter {
+    fn name(&self) -> &'static str;
+    fn import(&self, context: &ImportContext) -> Result<Box<dyn Any>, ImportError>;
+}
+pub(crate) trait ErasedAsyncImporter: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn decode(&self, context: &ImportContext) -> Result<Box<dyn Any + Send>, ImportError>;
+    fn finish(&self, decoded: Box<dyn Any + Send>) -> Result<Box<dyn Any>, ImportError>;
+}
+impl<I: AsyncImporter> ErasedAsyncImporter for I {
+    fn name(&self) -> &'static str {
+        AsyncImporter::name(self)
+    }
+    fn decode(&self, context: &ImportContext) -> Result<Box<dyn Any + Send>, ImportError> {
+        Ok(Box::new(AsyncImporter::decode(self, context)?))
+    }
+    fn finish(&self, decoded: Box<dyn Any + Send>) -> Result<Box<dyn Any>, ImportError> {
+        let decoded = decoded.downcast::<I::Decoded>().expect("decoded by another importer");
+        Ok(Box::new(AsyncImporter::finish(self, *decoded)?))
+    }
+}
+///Importers by extension and type they produce, importer registered later replaces previous one
+#[derive(Default)]
+pub struct Importers {
+    importers: HashMap<(TypeId, String), Box<dyn ErasedImporter>>,
+    async_importers: HashMap<(TypeId, String), Arc<dyn ErasedAsyncImporter>>,
+}
+impl Importers {
+    ///registry without any importers
+    pub fn new() -> Self {
+        Self::default()
+    }
+    ///registry with importers for bytes, text, images, textures, obj models, mtl materials, glTF scenes and shaders
+    pub fn with_defaults() -> Self {
+        let mut importers = Self::new();
+        importers.register_async(BytesImporter);
+        importers.register_async(TextImporter);
+        importers.register_async(crate::image_importer::ImageImporter);
+        importers.register_async(crate::image_importer::TextureImporter);
+        importers.register_async(crate::texture_container::TextureContainerImporter);
+        importers.register_async(crate::texture_container::ContainerTextureImporter);
+        importers.register_async(crate::texture_container::CubeMapImporter);
+        importers.register_async(crate::texture_container::TextureArrayImporter);
+        importers.register_async(crate::model_importer::ObjImporter);
+        importers.register_async(crate::model_importer::InstancedObjImporter);
+        importers.register_async(crate::model_importer::TangentObjImporter);
+        importers.register_async(crate::cooked_mesh::MeshImporter);
+        importers.register_async(crate::material_importer::MtlImporter);
+        importers.register_async(crate::material_importer::ObjModelImporter);
+        importers.register_async(crate::gltf_importer::GltfImporter);
+        importers.register_async(crate::shader_importer::ShaderSourceImporter);
+        importers.register_async(crate::shader_importer::SubShaderImporter);
+        importers
+    }
+    ///Registers importer for blocking loads only, load_async fails for its files
+    pub fn register<I: Importer>(&mut self, importer: I) -> &mut Self {
+        let importer = Rc::new(importer);
+        for extension in importer.extensions() {
+            let key = (TypeId::of::<I::Output>(), extension.to_lowercase());
+            self.async_importers.remove(&key);
+            self.importers.insert(key, Box::new(Shared(importer.clone())));
+        }
+        self
+    }
+    ///Registers importer for both blocking and background loads
+    pub fn register_async<I: AsyncImporter>(&mut self, importer: I) -> &mut Self {
+        let importer = Arc::new(importer);
+        for extension in importer.extensions() {
+            let key = (TypeId::of::<I::Output>(), extension.to_lowercase());
+            self.async_importers.insert(key.clone(), importer.clone());
+            self.importers.insert(key, Box::new(Blocking(importer.clone())));
+        }
+        self
+    }
+    ///true if file at path can be imported as T
+    pub fn supports<T: 'static>(&self, path: &str) -> bool {
+        find(&self.importers, TypeId::of::<T>(), path).is_some()
+    }
+    ///true if file at path can be imported as T on worker threads
+    pub fn supports_async<T: 'static>(&self, path: &str) -> bool {
+        find(&self.async_importers, TypeId::of::<T>(), path).is_some()
+    }
+    pub(crate) fn find_async<T: 'static>(
+        &self,
+        path: &str,
+    ) -> Result<Arc<dyn ErasedAsyncImporter>, AssetError> {
+        find(&self.async_importers, TypeId::of::<T>(), path)
+            .cloned()
+            .ok_or_else(|| no_importer::<T>(path))
+    }
+    ///Imports file with importer registered for its extension and T
+    pub fn import<T: 'static>(&self, source: &Vfs, path: &str) -> Result<T, AssetError> {
+        let importer =
+            find(&self.importers, TypeId::of::<T>(), path).ok_or_else(|| no_importer::<T>(path))?;
+        let asset = importer
+            .import(&ImportContext::new(path, source))
+            .map_err(|error| import_failed(path, importer.name(), error))?;
+        Ok(*asset.downcast::<T>().expect("importer returned asset of another type"))
+    }
+}
+fn find<'a, I>(importers: &'a HashMap<(TypeId, String), I>, type_id: TypeId, path: &str) -> Option<&'a I> {
+    extension(path)
+        .and_then(|extension| importers.get(&(type_id, extension)))
+        .or_else(|| importers.get(&(type_id, "*".to_owned())))
+}
+fn no_importer<T>(path: &str) -> AssetError {
+    AssetError::NoImporter {
+        path: path.to_owned(),
+        asset_type: type_name::<T>(),
+    }
+}
+pub(crate) fn import_failed(path: &str, importer: &'static str, error: ImportError) -> AssetError {
+    AssetError::ImportFailed {
+        path: path.to_owned(),
+        importer,
+        error,
+    }
+}
+///Same importer is stored once for every extension it supports
+struct Shared<I>(Rc<I>);
+impl<I: Importer> ErasedImporter for Shared<I> {
+    fn name(&self) -> &'static str {
+        Importer::name(self.0.as_ref())
+    }
+    fn import(&self, context: &ImportContext) -> Result<Box<dyn Any>, ImportError> {
+        Ok(Box::new(Importer::import(self.0.as_ref(), context)?))
+    }
+}
+///Async importer used by blocking loads
+struct Blocking<I>(Arc<I>);
+impl<I: AsyncImporter> ErasedImporter for Blocking<I> {
+    fn name(&self) -> &'static str {
+        AsyncImporter::name(self.0.as_ref())
+    }
+    fn import(&self, context: &ImportContext) -> Result<Box<dyn Any>, ImportError> {
+        let decoded = self.0.decode(context)?;
+        Ok(Box::new(self.0.finish(decoded)?))
+    }
+}
+///Imports any file as is
+pub struct BytesImporter;
+impl AsyncImporter for BytesImporter {
+    type Decoded = Vec<u8>;
+    type Output = Vec<u8>;
+    fn name(&self) -> &'static str {
+        "bytes"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["*"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<Vec<u8>, ImportError> {
+        Ok(context.read()?)
+    }
+    fn finish(&self, decoded: Vec<u8>) -> Result<Vec<u8>, ImportError> {
+        Ok(decoded)
+    }
+}
+///Imports any file as UTF-8 text
+pub struct TextImporter;
+impl AsyncImporter for TextImporter {
+    type Decoded = String;
+    type Output = String;
+    fn name(&self) -> &'static str {
+        "text"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["*"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<String, ImportError> {
+        Ok(context.read_string()?)
+    }
+    fn finish(&self, decoded: String) -> Result<String, ImportError> {
+        Ok(decoded)
+    }
+}
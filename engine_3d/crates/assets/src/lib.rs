@@ -1,25 +1,63 @@
 mod ext;
+mod importer;
+mod listing;
 mod loader;
+mod loading;
+mod meta;
+mod pack_cache;
+mod pack_options;
+mod server;
+mod vfs;
+mod watcher;
 #[allow(dead_code)]
 pub mod saver;
+pub mod cooked_mesh;
 pub mod model_importer;
+pub mod material_importer;
+pub mod gltf_importer;
 pub mod image_importer;
-//pub mod ranged_reader;
+pub mod shader_importer;
+pub mod texture_container;
+pub mod ranged_reader;
+pub use ext::{glob_match, normalize_path};
+pub use importer::*;
+pub use listing::DirEntry;
 pub use loader::*;
+pub use loading::{AsyncHandle, LoadState};
+pub use meta::*;
+pub use server::*;
+pub use vfs::*;
+pub use watcher::*;
 const COMPRESS_LEVEL: i32 = 12;
-/// Data file structure is next:
-/// count of directories
-/// Directory {
-///     Path(size+data)
-///     Count of files
-///     Array of files
+///first bytes of every data file
+pub const MAGIC: [u8; 8] = *b"E3DASSET";
+///version of data file layout, bumped on every incompatible change
+pub const FORMAT_VERSION: u32 = 5;
+///magic + version + dictionary size + table of contents size + two checksums
+pub(crate) const HEADER_SIZE: u64 = 8 + 4 + 8 + 8 + 4 + 4;
+/// Data file structure is next (all numbers are big endian):
+/// Header {
+///     magic (8 bytes)
+///     format version (u32)
+///     size of dictionary in bytes, 0 if there is none (u64)
+///     size of table of contents in bytes (u64)
+///     CRC32 of dictionary and table of contents (u32)
+///     CRC32 of everything after header (u32)
 /// }
-/// File {
-///     Name(size+data)
+/// zstd dictionary shared by small files
+/// Table of contents {
+///     count of files
+///     Array of entries
+/// }
+/// Entry {
+///     Path(size+data), relative to assets folder and separated with '/'
 ///     start byte
 ///     end byte
-///     file data
+///     size of uncompressed data
+///     codec (u8)
+///     CRC32 of compressed data (u32)
 /// }
+/// compressed file data
 #[cfg(test)]
 mod test {
     use crate::*;
@@ -68,4 +106,941 @@ mod test {
             timer.elapsed().as_secs_f32()
         );
     }
+    #[test]
+    pub fn old_data_file() {
+        //layout used before the header was introduced: directory count goes first
+        let path = std::env::temp_dir().join("old_assets.data");
+        let mut data = 1u64.to_be_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_be_bytes());
+        data.extend_from_slice(&0u64.to_be_bytes());
+        write(&path, data).unwrap();
+        assert!(matches!(
+            loader::Assets::from_data_file(&path),
+            Err(AssetError::InvalidDataFile)
+        ));
+    }
+    #[test]
+    pub fn corrupted_data_file() {
+        let path = std::env::temp_dir().join("corrupted_assets.data");
+        saver::create_data_file_to("testmaterials", &path).unwrap();
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        assets.verify().unwrap();
+        let range = assets.find_file("stanford-bunny.obj").unwrap().range();
+        drop(assets);
+
+        let mut data = read(&path).unwrap();
+        data[((range.start + range.end) / 2) as usize] ^= 0xff;
+        write(&path, &data).unwrap();
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        assert!(matches!(
+            assets.open_file("stanford-bunny.obj"),
+            Err(AssetError::Corrupted { path }) if path == "stanford-bunny.obj"
+        ));
+        assert!(assets.open_file("Pushkin's poetry/Curious.txt").is_ok());
+        assert!(assets.verify().is_err());
+        drop(assets);
+
+        write(&path, &data[..range.end as usize - 1]).unwrap();
+        assert!(loader::Assets::from_data_file(&path).is_err());
+    }
+    #[test]
+    pub fn streaming() {
+        use std::io::{Read, Seek, SeekFrom};
+        let path = std::env::temp_dir().join("streaming_assets.data");
+        saver::create_data_file_to("testmaterials", &path).unwrap();
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        let bunny = read_file("testmaterials/stanford-bunny.obj");
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let mut reader = assets.open_reader("stanford-bunny.obj").unwrap();
+                    let mut text = String::new();
+                    reader.read_to_string(&mut text).unwrap();
+                    assert_eq!(bunny, text);
+                });
+            }
+        });
+        let mut reader = assets.open_reader("stanford-bunny.obj").unwrap();
+        let mut buffer = [0u8; 16];
+        reader.seek(SeekFrom::Start(1000)).unwrap();
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(&bunny.as_bytes()[1000..1016], &buffer);
+        reader.seek(SeekFrom::Current(-500)).unwrap();
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(&bunny.as_bytes()[516..532], &buffer);
+        reader.seek(SeekFrom::End(-16)).unwrap();
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!(&bunny.as_bytes()[bunny.len() - 16..], &buffer);
+    }
+    #[test]
+    pub fn incremental() {
+        let folder = std::env::temp_dir().join("incremental_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("poetry")).unwrap();
+        copy("testmaterials/vert.glsl", folder.join("vert.glsl")).unwrap();
+        copy(
+            "testmaterials/Pushkin's poetry/Curious.txt",
+            folder.join("poetry/Curious.txt"),
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join("incremental_assets.data");
+        let _ = remove_file(&path);
+
+        let summary = saver::create_data_file_to(&folder, &path).unwrap();
+        assert_eq!((summary.compressed, summary.reused), (2, 0));
+        assert!(summary.sources.contains(&folder.join("poetry")));
+        let summary = saver::create_data_file_to(&folder, &path).unwrap();
+        assert_eq!((summary.compressed, summary.reused), (0, 2));
+
+        write(folder.join("vert.glsl"), "void main(){}").unwrap();
+        let summary = saver::create_data_file_to(&folder, &path).unwrap();
+        assert_eq!((summary.compressed, summary.reused), (1, 1));
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        assets.verify().unwrap();
+        assert_eq!(assets.open_file_string("vert.glsl").unwrap(), "void main(){}");
+        assert_eq!(
+            assets.open_file_string("poetry/Curious.txt").unwrap(),
+            read_file("testmaterials/Pushkin's poetry/Curious.txt")
+        );
+    }
+    #[test]
+    pub fn dictionary() {
+        use std::io::Read;
+        let folder = std::env::temp_dir().join("dictionary_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("materials")).unwrap();
+        for i in 0..64 {
+            write(
+                folder.join(format!("materials/material{}.mtl", i)),
+                format!(
+                    "newmtl Material{}\nNs {}.0\nKa 1.000000 1.000000 1.000000\nKd 0.{} 0.800000 0.{}\nKs 0.500000 0.500000 0.500000\nNi 1.450000\nd 1.000000\nillum 2\nmap_Kd texture{}.png\n",
+                    i, i * 7, i, 64 - i, i
+                ),
+            )
+            .unwrap();
+        }
+        copy("testmaterials/stanford-bunny.obj", folder.join("stanford-bunny.obj")).unwrap();
+        let path = std::env::temp_dir().join("dictionary_assets.data");
+        let _ = remove_file(&path);
+
+        let options = saver::PackOptions::new().dictionary(saver::DictionaryOptions::default());
+        let summary = saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+        assert!(summary.dictionary_size > 0);
+        let materials = summary.directories.iter().find(|d| d.path == "materials").unwrap();
+        assert_eq!(materials.files, 64);
+        assert!(materials.saved() > 0);
+        assert_eq!(materials.plain_size, materials.compressed_size + materials.saved() as u64);
+        let bunny = summary.directories.iter().find(|d| d.path.is_empty()).unwrap();
+        assert_eq!((bunny.saved(), bunny.plain_size), (0, bunny.compressed_size));
+        assert!(summary.to_string().contains(&format!("{} bytes saved by dictionary", materials.saved())));
+
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        assets.verify().unwrap();
+        let material = assets.find_file("materials/material5.mtl").unwrap();
+        assert_eq!(material.codec(), Codec::ZstdDictionary);
+        assert_eq!(
+            assets.find_file("stanford-bunny.obj").unwrap().codec(),
+            Codec::Zstd
+        );
+        let expected = read_to_string(folder.join("materials/material5.mtl")).unwrap();
+        assert_eq!(assets.open_file_string("materials/material5.mtl").unwrap(), expected);
+        let mut streamed = String::new();
+        assets
+            .open_reader("materials/material5.mtl")
+            .unwrap()
+            .read_to_string(&mut streamed)
+            .unwrap();
+        assert_eq!(streamed, expected);
+        drop(assets);
+
+        let reused = saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+        assert_eq!((reused.compressed, reused.reused), (0, 65));
+        assert_eq!(reused.directories[1].saved(), materials.saved());
+    }
+    #[test]
+    pub fn compression_rules() {
+        use saver::{Compression, PackOptions};
+        use std::io::{Read, Seek, SeekFrom};
+        assert!(ext::glob_match("**/*.png", "textures/ui/button.png"));
+        assert!(ext::glob_match("textures/*.pn?", "textures/wall.png"));
+        assert!(!ext::glob_match("textures/*.png", "textures/ui/button.png"));
+
+        let folder = std::env::temp_dir().join("compression_rules_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("textures")).unwrap();
+        copy("testmaterials/stanford-bunny.obj", folder.join("bunny.obj")).unwrap();
+        copy("testmaterials/stanford-bunny.obj", folder.join("textures/wall.png")).unwrap();
+        let path = std::env::temp_dir().join("compression_rules_assets.data");
+        let _ = remove_file(&path);
+
+        let options = PackOptions::new()
+            .level(3)
+            .rule("textures/**", Compression::Stored)
+            .extension("obj", Compression::ZstdLong(3));
+        assert_eq!(options.compression_for("textures/wall.png"), Compression::Stored);
+        assert_eq!(options.compression_for("shaders/a.vert"), Compression::Zstd(3));
+        //profiles store the same formats
+        for profile in [PackOptions::dev(), PackOptions::release()] {
+            for extension in saver::COMPRESSED_EXTENSIONS {
+                assert_eq!(profile.compression_for(&format!("sounds/a.{}", extension)), Compression::Stored);
+            }
+        }
+        saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        let expected = read(folder.join("bunny.obj")).unwrap();
+        let texture = assets.find_file("textures/wall.png").unwrap();
+        assert_eq!(texture.codec(), Codec::Stored);
+        assert_eq!(texture.compressed_size(), texture.size());
+        assert_eq!(assets.find_file("bunny.obj").unwrap().codec(), Codec::ZstdLong);
+        assert_eq!(assets.open_file("bunny.obj").unwrap(), expected);
+        assert_eq!(assets.open_file("textures/wall.png").unwrap(), expected);
+        let mut reader = assets.open_reader("textures/wall.png").unwrap();
+        reader.seek(SeekFrom::Start(100)).unwrap();
+        let mut chunk = [0u8; 16];
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(chunk, expected[100..116]);
+        drop(reader);
+        drop(assets);
+
+        //changing compression of a file makes it recompressed
+        let options = options.rule("textures/**", Compression::Zstd(3));
+        let summary = saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+        assert_eq!((summary.compressed, summary.reused), (1, 1));
+    }
+    #[test]
+    pub fn panicking_entry() {
+        let mut written = Vec::new();
+        let process = |index| match index {
+            5 => panic!("broken entry"),
+            index => Ok(index),
+        };
+        let result = saver::process_in_order(40, 4, process, |index, _, _| {
+            written.push(index);
+            Ok(())
+        });
+        assert_eq!(result.unwrap_err().to_string(), "entry 5 panicked: broken entry");
+        assert_eq!(written, [0, 1, 2, 3, 4]);
+    }
+    #[test]
+    pub fn layers() {
+        use std::io::Read;
+        let base = std::env::temp_dir().join("layers_base.data");
+        saver::create_data_file_to("testmaterials", &base).unwrap();
+        let patch_folder = std::env::temp_dir().join("layers_patch");
+        let _ = remove_dir_all(&patch_folder);
+        create_dir_all(patch_folder.join("Pushkin's poetry")).unwrap();
+        write(patch_folder.join("Pushkin's poetry/Curious.txt"), "patched").unwrap();
+        let patch = std::env::temp_dir().join("layers_patch.data");
+        saver::create_data_file_to(&patch_folder, &patch).unwrap();
+        let loose = std::env::temp_dir().join("layers_loose");
+        let _ = remove_dir_all(&loose);
+        create_dir_all(&loose).unwrap();
+        write(loose.join("stanford-bunny.obj"), "loose").unwrap();
+        create_dir_all(loose.join("Pushkin's poetry/Friendship.txt")).unwrap();
+        create_dir_all(loose.join("textures")).unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.mount_data_file(&base)
+            .unwrap()
+            .mount_data_file(&patch)
+            .unwrap()
+            .mount_directory(&loose)
+            .unwrap();
+        assert_eq!(vfs.open_file_string("Pushkin's poetry/Curious.txt").unwrap(), "patched");
+        //loose directory with the same name doesn't hide packed file
+        assert_eq!(
+            vfs.open_file_string("Pushkin's poetry/Friendship.txt").unwrap(),
+            read_file("testmaterials/Pushkin's poetry/Friendship.txt")
+        );
+        assert!(matches!(vfs.find_file("textures"), Err(AssetError::NotAFile)));
+        assert_eq!(vfs.open_file_string("./stanford-bunny.obj").unwrap(), "loose");
+        assert!(matches!(vfs.find_file("stanford-bunny.obj").unwrap(), VfsFile::Loose { size: 5, .. }));
+        let mut streamed = String::new();
+        vfs.open_reader("stanford-bunny.obj").unwrap().read_to_string(&mut streamed).unwrap();
+        assert_eq!(streamed, "loose");
+        //loose directory is never escaped
+        assert!(vfs.find_file("../layers_base.data").is_err());
+        assert!(matches!(vfs.find_file("missing.txt"), Err(AssetError::FileFindingError)));
+        assert!(vfs.list_dir("").unwrap().iter().any(|entry| entry.path == "stanford-bunny.obj"));
+        assert_eq!(vfs.glob("*.obj").unwrap(), ["stanford-bunny.obj"]);
+        assert_eq!(vfs.walk("Pushkin's poetry").unwrap().len(), 3);
+        vfs.verify().unwrap();
+    }
+    #[test]
+    pub fn listing() {
+        let path = std::env::temp_dir().join("listing_assets.data");
+        saver::create_data_file_to("testmaterials", &path).unwrap();
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        let root = assets.list_dir("").unwrap();
+        assert!(root.contains(&crate::DirEntry { path: "Pushkin's poetry".into(), is_dir: true }));
+        assert!(root.contains(&crate::DirEntry { path: "stanford-bunny.obj".into(), is_dir: false }));
+        let poetry = assets.list_dir(".\\Pushkin's poetry/").unwrap();
+        assert!(poetry.iter().all(|entry| !entry.is_dir));
+        assert!(poetry.iter().any(|entry| entry.name() == "Curious.txt"));
+        assert_eq!(assets.walk("Pushkin's poetry").unwrap().len(), poetry.len());
+        assert_eq!(assets.walk("").unwrap().len(), assets.files().len());
+        assert!(matches!(assets.list_dir("missing"), Err(AssetError::FileFindingError)));
+        assert!(matches!(assets.list_dir("stanford-bunny.obj"), Err(AssetError::NotAFile)));
+        assert!(assets.list_dir("../").is_err());
+        //both listings report missing directory and file the same way
+        assert!(matches!(assets.walk("missing"), Err(AssetError::FileFindingError)));
+        assert!(matches!(assets.walk("stanford-bunny.obj"), Err(AssetError::NotAFile)));
+        assert!(matches!(assets.walk("Pushkin's"), Err(AssetError::FileFindingError)));
+        let texts = assets.glob("**/*.txt");
+        assert_eq!(texts.len(), poetry.len());
+        assert!(texts.windows(2).all(|pair| pair[0].path() < pair[1].path()));
+        assert_eq!(assets.glob("*.obj").len(), 1);
+        assert_eq!(assets.find_file("Pushkin's poetry\\Curious.txt").unwrap().name(), "Curious.txt");
+        assert_eq!(normalize_path("./models\\cube.obj/").as_deref(), Some("models/cube.obj"));
+        assert_eq!(normalize_path("models/../cube.obj"), None);
+    }
+    #[test]
+    pub fn asset_server() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static LOADS: AtomicUsize = AtomicUsize::new(0);
+        struct Poem(String);
+        struct PoemImporter;
+        impl Importer for PoemImporter {
+            type Output = Poem;
+            fn extensions(&self) -> &[&str] {
+                &["txt"]
+            }
+            fn import(&self, context: &ImportContext) -> Result<Poem, ImportError> {
+                LOADS.fetch_add(1, Ordering::Relaxed);
+                Ok(Poem(context.read_string()?))
+            }
+        }
+        let mut vfs = Vfs::new();
+        vfs.mount_directory("testmaterials").unwrap();
+        let mut server = AssetServer::new(vfs);
+        server.register(PoemImporter);
+        let handles = (0..10)
+            .map(|_| server.load::<Poem, _>("./Pushkin's poetry/Curious.txt").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(LOADS.load(Ordering::Relaxed), 1);
+        assert!(handles.iter().all(|handle| handle.ptr_eq(&handles[0])));
+        assert_eq!(handles[0].count(), 10);
+        assert_eq!(handles[0].path(), "Pushkin's poetry/Curious.txt");
+        assert_eq!(handles[0].0, read_file("testmaterials/Pushkin's poetry/Curious.txt"));
+        //the same path loaded as another type is another asset
+        let text = server.load::<String, _>("Pushkin's poetry/Curious.txt").unwrap();
+        assert_eq!(*text, handles[0].0);
+        assert_eq!(server.loaded(), 2);
+
+        drop(handles);
+        assert!(server.get::<Poem, _>("Pushkin's poetry/Curious.txt").is_none());
+        assert_eq!(server.loaded(), 1);
+        server.load::<Poem, _>("Pushkin's poetry/Curious.txt").unwrap();
+        assert_eq!(LOADS.load(Ordering::Relaxed), 2);
+        assert!(matches!(
+            server.load::<Poem, _>("missing.txt"),
+            Err(AssetError::ImportFailed { .. })
+        ));
+    }
+    #[test]
+    pub fn importers() {
+        struct Level(Vec<(String, u32)>);
+        struct LevelImporter;
+        impl Importer for LevelImporter {
+            type Output = Level;
+            fn name(&self) -> &'static str {
+                "level"
+            }
+            fn extensions(&self) -> &[&str] {
+                &["level"]
+            }
+            fn import(&self, context: &ImportContext) -> Result<Level, ImportError> {
+                let mut entities = Vec::new();
+                for line in context.read_string()?.lines() {
+                    let (model, count) = line.split_once(' ').ok_or("expected model and count")?;
+                    entities.push((context.resolve(model)?, count.parse()?));
+                }
+                Ok(Level(entities))
+            }
+        }
+        let folder = std::env::temp_dir().join("importers_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("levels")).unwrap();
+        write(folder.join("levels/first.LEVEL"), "../models/tree.obj 3\nrock.obj 1").unwrap();
+        write(folder.join("levels/broken.level"), "tree.obj many").unwrap();
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let mut server = AssetServer::new(vfs);
+        assert!(!server.importers().supports::<Level>("levels/first.level"));
+        assert!(matches!(
+            server.load::<Level, _>("levels/first.level"),
+            Err(AssetError::NoImporter { .. })
+        ));
+        server.register(LevelImporter);
+        let level = server.load::<Level, _>("levels/first.LEVEL").unwrap();
+        assert_eq!(level.0, [("models/tree.obj".to_owned(), 3), ("levels/rock.obj".to_owned(), 1)]);
+        //text importer accepts any extension
+        assert!(server.import::<String, _>("levels/first.LEVEL").unwrap().starts_with("../"));
+        match server.load::<Level, _>("levels/broken.level") {
+            Err(error @ AssetError::ImportFailed { .. }) => {
+                let message = error.to_string();
+                assert!(message.starts_with("level failed to import levels/broken.level"), "{}", message);
+            }
+            _ => panic!("broken level was imported"),
+        }
+        remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn load_async() {
+        use graphics::objects::{model::Model, vertex::ModelVertex};
+        use std::time::{Duration, Instant};
+        struct Slow;
+        impl AsyncImporter for Slow {
+            type Decoded = usize;
+            type Output = usize;
+            fn extensions(&self) -> &[&str] {
+                &["txt"]
+            }
+            fn decode(&self, context: &ImportContext) -> Result<usize, ImportError> {
+                std::thread::sleep(Duration::from_millis(50));
+                Ok(context.read()?.len())
+            }
+            fn finish(&self, decoded: usize) -> Result<usize, ImportError> {
+                Ok(decoded)
+            }
+        }
+        fn finish_all(server: &mut AssetServer) {
+            let start = Instant::now();
+            while server.pending_loads() > 0 {
+                assert!(start.elapsed() < Duration::from_secs(30), "loads never finished");
+                server.finish_loads(Duration::ZERO);
+            }
+        }
+        let mut vfs = Vfs::new();
+        vfs.mount_directory("testmaterials").unwrap();
+        let mut server = AssetServer::new(vfs).worker_threads(2);
+        server.register_async(Slow);
+        let bunny = server.load_async::<Model<ModelVertex>, _>("stanford-bunny.obj");
+        let poems = server
+            .source()
+            .walk("")
+            .unwrap()
+            .into_iter()
+            .filter(|path| path.ends_with(".txt"))
+            .map(|path| server.load_async::<usize, _>(path))
+            .collect::<Vec<_>>();
+        let again = server.load_async::<usize, _>(poems[0].path());
+        let missing = server.load_async::<String, _>("missing.txt");
+        let unsupported = server.load_async::<f32, _>("frag.glsl");
+        assert!(bunny.is_loading() && again.is_loading());
+        assert!(matches!(*unsupported.error().unwrap(), AssetError::NoImporter { .. }));
+        //at least one load is finished even without budget, once it's decoded
+        let start = Instant::now();
+        while server.finish_loads(Duration::ZERO) == 0 {
+            assert!(start.elapsed() < Duration::from_secs(30));
+        }
+        finish_all(&mut server);
+        assert!(bunny.get().unwrap().verticies().len() > 1000);
+        for poem in &poems {
+            let expected = read_file(format!("testmaterials/{}", poem.path())).len();
+            assert_eq!(*poem.get().unwrap(), expected);
+        }
+        assert!(again.get().unwrap().ptr_eq(&poems[0].get().unwrap()));
+        assert!(matches!(*missing.error().unwrap(), AssetError::ImportFailed { .. }));
+        //finished loads are cached
+        assert!(server.load::<usize, _>(poems[0].path()).unwrap().ptr_eq(&again.get().unwrap()));
+        assert!(server.load_async::<usize, _>(poems[0].path()).is_ready());
+    }
+    #[test]
+    pub fn materials() {
+        use material_importer::*;
+        let folder = std::env::temp_dir().join("material_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("models")).unwrap();
+        image::RgbImage::new(2, 2).save(folder.join("models/wood.png")).unwrap();
+        write(
+            folder.join("models/crate.mtl"),
+            "# exported\nnewmtl wood\nKd 0.5 0.25 1\nKs 0.2\nNs 500\nd 0.5\n\
+             map_Kd -bm 0.5 -o 1 1 C:\\Users\\artist\\wood.png\nmap_Ks spec.png\nmap_Bump normal.png\n\
+             newmtl metal\nmap_Ks wood.png\n",
+        )
+        .unwrap();
+        write(
+            folder.join("models/crate.obj"),
+            "mtllib crate.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\n\
+             usemtl wood\nf 1/1/1 2/1/1 3/1/1\nusemtl metal\nf 1/1/1 3/1/1 2/1/1\n\
+             usemtl wood\nf 3/1/1 2/1/1 1/1/1\nusemtl unknown\n",
+        )
+        .unwrap();
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let server = AssetServer::new(vfs);
+        let materials = server.import::<Vec<MtlMaterial>, _>("models/crate.mtl").unwrap();
+        let wood = &materials[0];
+        assert_eq!((wood.name.as_str(), wood.diffuse, wood.specular), ("wood", [0.5, 0.25, 1.0], [0.2; 3]));
+        assert_eq!((wood.shininess, wood.dissolve), (500.0, 0.5));
+        assert_eq!(wood.diffuse_map.as_deref(), Some("C:\\Users\\artist\\wood.png"));
+        assert_eq!(materials[1].specular_map.as_deref(), Some("wood.png"));
+        let error = parse_mtl("newmtl a\nKd 1 x 1").unwrap_err().to_string();
+        assert!(error.starts_with("line 2"), "{}", error);
+
+        let decoded = AsyncImporter::decode(&ObjModelImporter, &ImportContext::new("models/crate.obj", server.source())).unwrap();
+        let parts = decoded.parts.iter().map(|(name, model)| (name.as_str(), model.verticies().len())).collect::<Vec<_>>();
+        assert_eq!(parts, [("wood", 6), ("metal", 3)]);
+        assert_eq!(decoded.parts[0].1.indicies().unwrap().len(), 6);
+        //absolute path of artist's machine falls back to file next to model
+        assert_eq!(decoded.materials["wood"].diffuse_map.as_deref(), Some("models/wood.png"));
+        assert_eq!(decoded.materials["metal"].specular_map.as_deref(), Some("models/wood.png"));
+        assert_eq!(decoded.images.len(), 1);
+        assert_eq!(decoded.missing, ["spec.png"]);
+        //renderer has no normal mapping, so bump map isn't loaded
+        assert_eq!(decoded.materials["wood"].bump_map.as_deref(), Some("normal.png"));
+        remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn gltf() {
+        use base64::Engine;
+        use gltf_importer::*;
+        let mut bin = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bin.extend(value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2, 0] {
+            bin.extend(index.to_le_bytes());
+        }
+        for value in [0.0f32, 1.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0] {
+            bin.extend(value.to_le_bytes());
+        }
+        for value in math::Mat4::from_translation(math::vec3(0.0, -1.0, 0.0)).to_cols_array() {
+            bin.extend(value.to_le_bytes());
+        }
+        let json = |buffer: &str| {
+            format!(
+                r#"{{"asset":{{"version":"2.0"}},"scene":0,"scenes":[{{"nodes":[0]}}],
+                "nodes":[{{"name":"root","translation":[1,0,0],"children":[1]}},
+                    {{"name":"child","mesh":0,"skin":0,"scale":[2,2,2]}},{{"name":"unused"}}],
+                "meshes":[{{"primitives":[{{"attributes":{{"POSITION":0}},"indices":1,"material":0}},
+                    {{"attributes":{{"POSITION":0}},"mode":1}}]}}],
+                "materials":[{{"pbrMetallicRoughness":{{"baseColorFactor":[1,0.5,0,1],"roughnessFactor":0.5}},"alphaMode":"BLEND"}}],
+                "skins":[{{"joints":[0],"inverseBindMatrices":4}}],
+                "animations":[{{"channels":[{{"sampler":0,"target":{{"node":1,"path":"translation"}}}}],
+                    "samplers":[{{"input":2,"output":3,"interpolation":"STEP"}}]}}],
+                "buffers":[{{"byteLength":{}{}}}],
+                "bufferViews":[{{"buffer":0,"byteLength":36}},{{"buffer":0,"byteOffset":36,"byteLength":6}},
+                    {{"buffer":0,"byteOffset":44,"byteLength":8}},{{"buffer":0,"byteOffset":52,"byteLength":24}},
+                    {{"buffer":0,"byteOffset":76,"byteLength":64}}],
+                "accessors":[{{"bufferView":0,"componentType":5126,"count":3,"type":"VEC3","min":[0,0,0],"max":[1,1,0]}},
+                    {{"bufferView":1,"componentType":5123,"count":3,"type":"SCALAR"}},
+                    {{"bufferView":2,"componentType":5126,"count":2,"type":"SCALAR","min":[0],"max":[1]}},
+                    {{"bufferView":3,"componentType":5126,"count":2,"type":"VEC3"}},
+                    {{"bufferView":4,"componentType":5126,"count":1,"type":"MAT4"}}]}}"#,
+                bin.len(),
+                buffer
+            )
+        };
+        let folder = std::env::temp_dir().join("gltf_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(&folder).unwrap();
+        let uri = format!(r#","uri":"data:application/octet-stream;base64,{}""#, base64::engine::general_purpose::STANDARD.encode(&bin));
+        write(folder.join("scene.gltf"), json(&uri)).unwrap();
+        //glb is header, json chunk and binary chunk, chunks are padded to 4 bytes
+        let mut json = json("").into_bytes();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut glb = Vec::new();
+        glb.extend(b"glTF");
+        glb.extend(2u32.to_le_bytes());
+        glb.extend((12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(json);
+        glb.extend((bin.len() as u32).to_le_bytes());
+        glb.extend(b"BIN\0");
+        glb.extend(&bin);
+        write(folder.join("scene.glb"), glb).unwrap();
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        for path in ["scene.gltf", "scene.glb"] {
+            let scene = AsyncImporter::decode(&GltfImporter, &ImportContext::new(path, &vfs)).unwrap();
+            assert_eq!(scene.roots, [0]);
+            assert_eq!(scene.nodes[0].children, [1]);
+            assert_eq!(scene.nodes[0].translation, math::vec3(1.0, 0.0, 0.0));
+            assert_eq!(scene.nodes[1].scale, math::Vec3::splat(2.0));
+            assert_eq!((scene.nodes[1].mesh, scene.nodes[1].skin), (Some(0), Some(0)));
+            //lines are skipped, triangle without normals gets flat ones
+            let primitives = &scene.meshes[0].primitives;
+            assert_eq!(primitives.len(), 1);
+            assert_eq!(primitives[0].material, Some(0));
+            assert_eq!(primitives[0].model.verticies().len(), 3);
+            assert_eq!(primitives[0].model.verticies()[0].normal, [0.0, 0.0, 1.0]);
+            let material = &scene.materials[0];
+            assert_eq!((material.base_color, material.roughness, material.blend), ([1.0, 0.5, 0.0, 1.0], 0.5, true));
+            assert_eq!(scene.skins[0].joints, [0]);
+            assert_eq!(scene.skins[0].inverse_bind_matrices[0].w_axis.y, -1.0);
+            let channel = &scene.animations[0].channels[0];
+            assert_eq!((channel.node, channel.property, channel.interpolation), (1, GltfProperty::Translation, GltfInterpolation::Step));
+            assert_eq!(channel.times, [0.0, 1.0]);
+            assert_eq!(channel.values, [0.0, 0.0, 0.0, 0.0, 2.0, 0.0]);
+        }
+        remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn weld_and_optimize_settings() {
+        use graphics::objects::{model::Model, vertex::ModelVertex};
+        //two quads sharing an edge, every triangle has its own vertices
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 2 0 0\nv 2 1 0\nf 1 2 3 4\nf 2 5 6 3\n";
+        let folder = std::env::temp_dir().join("weld_settings_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(&folder).unwrap();
+        write(folder.join("quads.obj"), obj).unwrap();
+        write(folder.join("quads.obj.meta"), "[model]\nweld = 0.0\noptimize = true\n").unwrap();
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let server = AssetServer::new(vfs);
+        let imported = server.import::<Model<ModelVertex>, _>("quads.obj").unwrap();
+        assert_eq!((imported.verticies().len(), imported.indicies().unwrap().len()), (6, 12));
+        remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn normal_and_tangent_settings() {
+        use graphics::objects::{
+            model::Model,
+            vertex::{ModelVertex, TangentVertex},
+        };
+        let folder = std::env::temp_dir().join("normal_settings_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(&folder).unwrap();
+        //cube without normals and texture coordinates
+        let cube = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\n\
+            f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+        write(folder.join("cube.obj"), cube).unwrap();
+        write(folder.join("cube.obj.meta"), "[model]\nnormals = \"smooth\"\ncrease_angle = 100\nweld = 0.0\n").unwrap();
+        let quad = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nvn 0 0 1\nf 1/1/1 2/2/1 3/3/1 4/4/1\n";
+        write(folder.join("quad.obj"), quad).unwrap();
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let server = AssetServer::new(vfs);
+        let cube = server.import::<Model<ModelVertex>, _>("cube.obj").unwrap();
+        //corners are shared by faces, so normals point along diagonals
+        assert_eq!(cube.verticies().len(), 8);
+        for vertex in cube.verticies() {
+            let diagonal = vertex.position.map(|value| (value * 2.0 - 1.0) / 3f32.sqrt());
+            assert!(vertex.normal.iter().zip(diagonal).all(|(a, b)| (a - b).abs() < 1e-5), "{:?}", vertex);
+        }
+        let quad = server.import::<Model<TangentVertex>, _>("quad.obj").unwrap();
+        assert!(quad.verticies().iter().all(|vertex| vertex.tangent == [1.0, 0.0, 0.0, 1.0]));
+        remove_dir_all(&folder).unwrap();
+    }
+    #[test]
+    pub fn cooked_mesh() {
+        use graphics::objects::{model::Model, vertex::ModelVertex};
+        fn vertices(model: &Model<ModelVertex>) -> Vec<[f32; 8]> {
+            model
+                .verticies()
+                .iter()
+                .map(|v| [v.position, v.normal].concat().into_iter().chain(v.texture_coords).collect::<Vec<_>>().try_into().unwrap())
+                .collect()
+        }
+        let folder = std::env::temp_dir().join("cooked_mesh_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("models")).unwrap();
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 2 0\nv 0 2 -1\nvt 0 0\nvt 1 1\nf 1/1 2/2 3/1\nf 1/1 3/1 4/2\n";
+        write(folder.join("models/quad.obj"), obj).unwrap();
+        let path = std::env::temp_dir().join("cooked_mesh_assets.data");
+        let _ = remove_file(&path);
+        let options = saver::PackOptions::new().cook_meshes(true);
+        let summary = saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+        assert_eq!(summary.compressed, 2);
+        assert_eq!(summary.sources.iter().filter(|source| source.ends_with("quad.obj")).count(), 1);
+
+        let parsed = model_importer::parse_model(obj).unwrap();
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        assets.verify().unwrap();
+        let (cooked, bounds) = cooked_mesh::read_mesh(&assets.open_file("models/quad.obj.mesh").unwrap()).unwrap();
+        assert_eq!(vertices(&cooked), vertices(&parsed));
+        assert_eq!(cooked.indicies(), parsed.indicies());
+        assert_eq!(bounds, cooked_mesh::MeshBounds { min: [0.0, 0.0, -1.0], max: [1.0, 2.0, 0.0], radius: 1.5f32.sqrt() });
+        //model gets bounds from header
+        assert_eq!(cooked.bounds(), parsed.bounds());
+        let mut moved = cooked.clone();
+        moved.verticies_mut()[0].position = [-1.0, 0.0, 0.0];
+        assert_eq!(moved.aabb().unwrap().min, math::vec3(-1.0, 0.0, -1.0));
+        assert!(cooked_mesh::read_mesh(&assets.open_file("models/quad.obj").unwrap()).is_err());
+        assert!(summary.warnings.is_empty());
+        drop(assets);
+        //cooked entry is reused while obj doesn't change
+        let summary = saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+        assert_eq!((summary.compressed, summary.reused), (0, 2));
+        //mesh cooked by older version is cooked again
+        let checksum = loader::Assets::from_data_file(&path).unwrap().checksum();
+        let mut cache = pack_cache::PackCache::load(&path, checksum).unwrap();
+        let info = *cache.get("models/quad.obj.mesh").unwrap();
+        assert_eq!(info.cook_version, cooked_mesh::MESH_VERSION);
+        cache.insert("models/quad.obj.mesh".to_owned(), pack_cache::SourceInfo { cook_version: 0, ..info });
+        cache.save(&path).unwrap();
+        let summary = saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+        assert_eq!((summary.compressed, summary.reused), (1, 1));
+        //obj that can't be cooked is packed as is
+        write(folder.join("models/broken.obj"), "v 0 0 0\nf 1 2 3\n").unwrap();
+        let summary = saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+        assert_eq!(summary.warnings.len(), 1);
+        assert!(summary.warnings[0].contains("models/broken.obj.mesh") && summary.to_string().contains("warning: "));
+        let assets = loader::Assets::from_data_file(&path).unwrap();
+        assert_eq!(assets.open_file("models/broken.obj.mesh").unwrap(), b"v 0 0 0\nf 1 2 3\n");
+        drop(assets);
+        remove_file(folder.join("models/broken.obj")).unwrap();
+        saver::create_data_file_with_options(&folder, &path, &options).unwrap();
+
+        let loose = std::env::temp_dir().join("cooked_mesh_loose");
+        let _ = remove_dir_all(&loose);
+        create_dir_all(loose.join("models")).unwrap();
+        let mut vfs = Vfs::new();
+        vfs.mount_data_file(&path).unwrap().mount_directory(&loose).unwrap();
+        let server = AssetServer::new(vfs);
+        let model = server.import::<Model<ModelVertex>, _>("models/quad.obj").unwrap();
+        assert_eq!(vertices(&model), vertices(&parsed));
+        //broken cooked mesh falls back to obj
+        write(loose.join("models/quad.obj.mesh"), "broken").unwrap();
+        let model = server.import::<Model<ModelVertex>, _>("models/quad.obj").unwrap();
+        assert_eq!(vertices(&model), vertices(&parsed));
+        assert!(server.import::<Model<ModelVertex>, _>("models/quad.obj.mesh").is_err());
+        //obj in loose directory is newer than cooked mesh in data file
+        remove_file(loose.join("models/quad.obj.mesh")).unwrap();
+        write(loose.join("models/quad.obj"), "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        let model = server.import::<Model<ModelVertex>, _>("models/quad.obj").unwrap();
+        assert_eq!(model.verticies().len(), 3);
+    }
+    #[test]
+    pub fn import_settings() {
+        use graphics::objects::{model::Model, vertex::ModelVertex};
+        use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+        let folder = std::env::temp_dir().join("import_settings_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("textures")).unwrap();
+        let mut image = RgbaImage::new(8, 4);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        image.save(folder.join("textures/flipped.png")).unwrap();
+        image.save(folder.join("textures/small.png")).unwrap();
+        write(folder.join("textures/small.png.meta"), "[texture]\nflip = false\nmax_size = 4\nsrgb = true\n").unwrap();
+        write(folder.join("quad.obj"), "v 0 0 0\nv 1 0 0\nv 0 1 1\nvn 1 0 0\nf 1//1 2//1 3//1\n").unwrap();
+        write(folder.join("quad.obj.meta"), "[model]\nscale = 2.0\nup_axis = \"z\"\nnormals = \"flat\"\n").unwrap();
+        write(folder.join("broken.obj"), "v 0 0 0\n").unwrap();
+        write(folder.join("broken.obj.meta"), "[model]\nscael = 2.0\n").unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let settings = ImportSettings::load(&vfs, "textures/small.png").unwrap();
+        assert!(!settings.texture.flip && settings.texture.srgb && settings.texture.mipmaps);
+        assert_eq!(settings.texture.max_size, Some(4));
+        assert_eq!(settings.model, ModelSettings::default());
+        assert_eq!(ImportSettings::load(&vfs, "textures/flipped.png").unwrap(), ImportSettings::default());
+
+        let server = AssetServer::new(vfs);
+        let flipped = server.import::<DynamicImage, _>("textures/flipped.png").unwrap();
+        assert_eq!(flipped.dimensions(), (8, 4));
+        assert_eq!(flipped.get_pixel(0, 3), Rgba([255, 0, 0, 255]));
+        let small = server.import::<DynamicImage, _>("textures/small.png").unwrap();
+        assert_eq!(small.dimensions(), (4, 2));
+        assert_ne!(small.get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+
+        let model = server.import::<Model<ModelVertex>, _>("quad.obj").unwrap();
+        let positions = model.verticies().iter().map(|v| v.position).collect::<Vec<_>>();
+        assert_eq!(positions, [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, -2.0]]);
+        //normal from file is replaced with face normal of converted triangle
+        assert!(model.verticies().iter().all(|v| v.normal == [0.0, 1.0f32 / 2f32.sqrt(), 1.0 / 2f32.sqrt()]));
+        let error = server.import::<Model<ModelVertex>, _>("broken.obj").err().unwrap().to_string();
+        assert!(error.contains("broken.obj.meta") && error.contains("scael"), "{}", error);
+        let error = image_importer::decode_image(b"not an image", Path::new("textures/fake.png")).err().unwrap();
+        assert!(matches!(&error, AssetError::ImageDecodingFailed { path, .. } if path == "textures/fake.png"));
+        let error = image_importer::decode_image(b"", Path::new("textures/image.xyz")).err().unwrap();
+        assert!(matches!(&error, AssetError::UnknownImageFormat { path } if path == "textures/image.xyz"));
+    }
+    #[test]
+    pub fn texture_containers() {
+        use graphics::objects::texture::{max_mip_level, MipLevel, TextureFormat};
+        use texture_container::*;
+        fn ktx2(vk_format: u32, (width, height): (u32, u32), layers: u32, faces: u32, supercompression: u32, levels: &[&[u8]]) -> Vec<u8> {
+            let mut header = vec![vk_format, 1, width, height, 0, layers, faces, levels.len() as u32, supercompression];
+            //data format descriptor and key/value data aren't read
+            header.extend([0; 4]);
+            let mut data = KTX2_MAGIC.to_vec();
+            data.extend(header.iter().flat_map(|value| value.to_le_bytes()));
+            data.extend([0; 16]);
+            let mut offset = data.len() + levels.len() * 24;
+            for level in levels {
+                for value in [offset, level.len(), level.len()] {
+                    data.extend((value as u64).to_le_bytes());
+                }
+                offset += level.len();
+            }
+            data.extend(levels.concat());
+            data
+        }
+        fn dds(size: u32, mip_count: u32, pixel_format: [u32; 8], dx10: Option<[u32; 5]>, images: &[u8]) -> Vec<u8> {
+            let mut header = [0u32; 31];
+            header[0] = 124;
+            header[1] = 0x1007 | 0x20000;
+            (header[2], header[3], header[6]) = (size, size, mip_count);
+            header[18..26].copy_from_slice(&pixel_format);
+            let mut data = DDS_MAGIC.to_vec();
+            data.extend(header.iter().chain(dx10.iter().flatten()).flat_map(|value| value.to_le_bytes()));
+            data.extend(images);
+            data
+        }
+        let base = (0..16).collect::<Vec<u8>>();
+        let rgba = parse_ktx2(&ktx2(37, (2, 2), 0, 1, 0, &[&base, &[16, 17, 18, 19]])).unwrap();
+        assert_eq!((rgba.format, rgba.layers, rgba.faces, rgba.array), (TextureFormat::RGBA8, 1, 1, false));
+        assert_eq!(rgba.levels[1], MipLevel { width: 1, height: 1, data: vec![16, 17, 18, 19] });
+        let compressed = zstd::bulk::compress(&base, 3).unwrap();
+        let mut zstd = ktx2(37, (2, 2), 0, 1, 2, &[&compressed]);
+        //uncompressed length of level
+        zstd[80 + 16..80 + 24].copy_from_slice(&16u64.to_le_bytes());
+        assert_eq!(TextureContainer::parse(&zstd).unwrap().levels[0].data, base);
+        //BGRA is swizzled, array of two layers
+        let array = parse_ktx2(&ktx2(44, (1, 1), 2, 1, 0, &[&[1, 2, 3, 4, 5, 6, 7, 8]])).unwrap();
+        assert!(array.array);
+        assert_eq!((array.image(0, 0, 0), array.image(0, 1, 0)), (&[3, 2, 1, 4][..], &[7, 6, 5, 8][..]));
+
+        //single level isn't a whole mip chain, so generated levels aren't cut off
+        let single = parse_ktx2(&ktx2(37, (2, 2), 0, 1, 0, &[&base])).unwrap();
+        assert!(single.generates_mipmaps(&TextureSettings::default()));
+        assert_eq!((max_mip_level(&single.levels), max_mip_level(&rgba.levels)), (None, Some(1)));
+        assert!(!rgba.generates_mipmaps(&TextureSettings::default()));
+
+        let bgra = dds(2, 1, [32, 0x41, 0, 32, 0xff0000, 0xff00, 0xff, 0xff000000], None, &base);
+        let bgra = TextureContainer::parse(&bgra).unwrap();
+        assert_eq!((bgra.format, bgra.width(), bgra.levels.len()), (TextureFormat::RGBA8, 2, 1));
+        assert_eq!(&bgra.levels[0].data[..4], [2, 1, 0, 3]);
+        //DX10 cube map of BC1 with 8x8 and 4x4 levels, images are stored face by face with their mip chains
+        let images = (0..6u8).flat_map(|face| [vec![face; 32], vec![face + 10; 8]].concat()).collect::<Vec<_>>();
+        let cube = dds(8, 2, [32, 0x4, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0], Some([71, 3, 0x4, 1, 0]), &images);
+        let mut cube = parse_dds(&cube).unwrap();
+        assert!(cube.is_cube_map() && !cube.array);
+        assert_eq!((cube.format, cube.levels[0].data.len(), cube.levels[1].data.len()), (TextureFormat::Bc1, 6 * 32, 6 * 8));
+        assert_eq!(cube.image(0, 0, 5), [5; 32]);
+        assert_eq!(cube.image(1, 0, 2), [12; 8]);
+        cube.limit_size(4);
+        assert_eq!((cube.width(), cube.levels.len()), (4, 1));
+        cube.limit_size(1);
+        assert_eq!(cube.levels.len(), 1);
+
+        let error = |result: Result<TextureContainer, ImportError>| result.err().unwrap().to_string();
+        assert!(error(parse_ktx2(&ktx2(37, (2, 2), 0, 1, 0, &[&base[..12]]))).contains("12 bytes"));
+        assert!(error(parse_ktx2(&ktx2(1000, (2, 2), 0, 1, 0, &[&base]))).contains("VkFormat 1000"));
+        assert!(error(parse_ktx2(&ktx2(37, (2, 2), 0, 1, 0, &[&base, &[0; 4], &[0; 4]]))).contains("too many"));
+        let truncated = ktx2(37, (2, 2), 0, 1, 0, &[&base]);
+        assert!(error(parse_ktx2(&truncated[..truncated.len() - 1])).contains("outside"));
+        assert!(error(TextureContainer::parse(b"PNG file")).contains("not a KTX2 or DDS"));
+        //sizes from corrupt headers are checked before anything is allocated
+        assert!(error(parse_ktx2(&ktx2(37, (2, 2), u32::MAX, 6, 0, &[&base]))).contains("too many"));
+        assert!(error(parse_ktx2(&ktx2(37, (u32::MAX, u32::MAX), 0, 1, 0, &[&base]))).contains("too large"));
+        zstd[80 + 16..80 + 24].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(error(parse_ktx2(&zstd)).contains("expected 16"));
+        let dx10 = [32, 0x4, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0];
+        assert!(error(parse_dds(&dds(8, 1, dx10, Some([71, 3, 0x4, u32::MAX, 0]), &images))).contains("too many"));
+        assert!(error(parse_dds(&dds(8, 1, dx10, Some([71, 3, 0, 1 << 30, 0]), &images))).contains("outside"));
+        assert!(error(parse_dds(&dds(u32::MAX, 1, dx10, Some([71, 3, 0, 1, 0]), &images))).contains("outside"));
+
+        let folder = std::env::temp_dir().join("texture_container_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(&folder).unwrap();
+        write(folder.join("sky.dds"), dds(8, 2, [32, 0x4, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0], Some([71, 3, 0x4, 1, 0]), &images)).unwrap();
+        write(folder.join("sky.dds.meta"), "[texture]\nmax_size = 4\n").unwrap();
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let server = AssetServer::new(vfs);
+        let sky = server.import::<TextureContainer, _>("sky.dds").unwrap();
+        assert_eq!((sky.width(), sky.levels.len(), sky.image(0, 0, 3)), (4, 1, &[13; 8][..]));
+    }
+    #[test]
+    pub fn shader_includes() {
+        use graphics::objects::shader::{preprocessor::ShaderSource, ShaderType};
+        let folder = std::env::temp_dir().join("shader_include_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("shaders/lib")).unwrap();
+        write(folder.join("shaders/light_frag.glsl"), "#version 430 core\n#include \"lib/light.glsl\"\n#include \"common/gbuffer.glsl\"\nvoid main(){}\n").unwrap();
+        write(folder.join("shaders/light_frag.glsl.meta"), "[shader]\ndefines = { SHADOWS = true, KERNEL = 5 }\n").unwrap();
+        write(folder.join("shaders/lib/light.glsl"), "#include \"../lib/math.glsl\"\nfloat light(){ return PI; }\n").unwrap();
+        write(folder.join("shaders/lib/math.glsl"), "#include \"/shaders/lib/light.glsl\"\nconst float PI = 3.14;\n").unwrap();
+        write(folder.join("shaders/broken.vert"), "#version 430 core\n\n#include \"missing.glsl\"\n").unwrap();
+        write(folder.join("shaders/copy.glsl"), "").unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let server = AssetServer::new(vfs);
+        let source = server.import::<ShaderSource, _>("shaders/light_frag.glsl").unwrap();
+        let lines = source.code.lines().collect::<Vec<_>>();
+        assert_eq!(lines[..5], ["#version 430 core", "#define KERNEL 5", "#define SHADOWS 1", "const float PI = 3.14;", "float light(){ return PI; }"]);
+        assert_eq!(source.files, ["shaders/light_frag.glsl", "shaders/lib/light.glsl", "shaders/lib/math.glsl", "shaders/common/gbuffer.glsl"]);
+        //common/gbuffer.glsl isn't in assets, so it is taken from rendering crate
+        assert!(source.code.contains("vec3 depth_to_world_pos("));
+        assert_eq!(lines.last(), Some(&"void main(){}"));
+        assert_eq!(source.origin(2), None);
+        assert_eq!(source.origin(4), Some(("shaders/lib/math.glsl", 2)));
+        assert_eq!(source.origin(lines.len() as u32), Some(("shaders/light_frag.glsl", 4)));
+        let log = "0:5(10): error: `PI' undeclared\nERROR: 0:4: bad\n0(1) : error C0000: syntax error\nwarning: 0 errors";
+        assert_eq!(
+            source.map_log(log),
+            "shaders/lib/light.glsl:2(10): error: `PI' undeclared\nERROR: shaders/lib/math.glsl:2: bad\nshaders/light_frag.glsl:1 : error C0000: syntax error\nwarning: 0 errors"
+        );
+
+        let error = server.import::<ShaderSource, _>("shaders/broken.vert").err().unwrap().to_string();
+        assert!(error.contains("shaders/broken.vert:3: can't include \"missing.glsl\""), "{}", error);
+        //file in assets replaces embedded one
+        assert_eq!(server.import::<ShaderSource, _>("shaders/copy.glsl").unwrap().code, "");
+        //hot reload preprocesses shaders without importing them
+        assert_eq!(shader_importer::preprocess_shader(server.source(), "shaders/light_frag.glsl").unwrap(), source);
+        assert!(shader_importer::preprocess_shader(server.source(), "shaders/forward_frag.glsl").is_ok());
+        assert!(matches!(shader_importer::shader_stage("shaders/light_frag.glsl"), Some(ShaderType::Fragment)));
+        assert!(matches!(shader_importer::shader_stage("sky.VERT"), Some(ShaderType::Vertex)));
+        assert!(shader_importer::shader_stage("shaders/copy.glsl").is_none());
+    }
+    #[test]
+    pub fn watcher() {
+        use std::time::Duration;
+        let folder = std::env::temp_dir().join("watched_assets");
+        let _ = remove_dir_all(&folder);
+        create_dir_all(folder.join("textures")).unwrap();
+        write(folder.join("textures/wall.png"), "old").unwrap();
+        let mut watcher = AssetWatcher::new(&folder).unwrap().interval(Duration::from_secs(3600));
+        assert!(watcher.poll_now().unwrap().is_empty());
+        write(folder.join("textures/wall.png"), "new content").unwrap();
+        write(folder.join("shader.frag"), "void main(){}").unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+        assert_eq!(watcher.poll_now().unwrap(), ["shader.frag", "textures/wall.png"]);
+        assert!(watcher.poll_now().unwrap().is_empty());
+
+        let mut vfs = Vfs::new();
+        vfs.mount_directory(&folder).unwrap();
+        let mut server = AssetServer::new(vfs);
+        let old = server.load::<String, _>("shader.frag").unwrap();
+        write(folder.join("shader.frag"), "void main(){ }").unwrap();
+        let new = server.reload::<String, _>("shader.frag").unwrap();
+        assert_eq!(*old, "void main(){}");
+        assert!(server.load::<String, _>("shader.frag").unwrap().ptr_eq(&new));
+        remove_file(folder.join("shader.frag")).unwrap();
+        assert!(server.reload::<String, _>("shader.frag").is_err());
+        assert!(server.load::<String, _>("shader.frag").unwrap().ptr_eq(&new));
+    }
+    #[test]
+    pub fn deterministic() {
+        let options = saver::PackOptions::new().level(3);
+        let path = std::env::temp_dir().join("deterministic_assets.data");
+        let mut packs = Vec::new();
+        for threads in [1, 4] {
+            //without previous data file everything is compressed again
+            let _ = remove_file(&path);
+            let mut packed = Vec::new();
+            saver::create_data_file_with_progress(
+                "testmaterials",
+                &path,
+                &options.clone().threads(threads),
+                |progress| {
+                    if let saver::PackProgress::Packed { path, index, .. } = progress {
+                        packed.push((index, path.to_owned()));
+                    }
+                },
+            )
+            .unwrap();
+            assert!(packed.iter().enumerate().all(|(i, (index, _))| i == *index));
+            assert!(packed.windows(2).all(|pair| pair[0].1 < pair[1].1));
+            packs.push(read(&path).unwrap());
+        }
+        assert!(packs[0] == packs[1]);
+    }
 }
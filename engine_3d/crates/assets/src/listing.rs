@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    ext::{glob_match, normalize_path},
+    AssetError,
+};
+
+///File or directory directly inside listed directory
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DirEntry {
+    ///full path, relative to assets root and separated with '/'
+    pub path: String,
+    pub is_dir: bool,
+}
+impl DirEntry {
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+///"" is the root directory
+fn normalize_dir(dir: &str) -> Result<String, AssetError> {
+    normalize_path(dir).ok_or(AssetError::FileFindingError)
+}
+///Lists children of directory, directories are derived from file paths.
+///Result is sorted by path
+pub(crate) fn list_dir<'a>(
+    paths: impl Iterator<Item = &'a str>,
+    dir: &str,
+) -> Result<Vec<DirEntry>, AssetError> {
+    let dir = normalize_dir(dir)?;
+    let mut entries = BTreeMap::new();
+    let mut found = dir.is_empty();
+    for path in paths {
+        if path == dir {
+            return Err(AssetError::NotAFile);
+        }
+        let Some(rest) = strip_dir(path, &dir) else {
+            continue;
+        };
+        found = true;
+        let (name, is_dir) = match rest.split_once('/') {
+            Some((name, _)) => (name, true),
+            None => (rest, false),
+        };
+        let path = match dir.is_empty() {
+            true => name.to_owned(),
+            false => format!("{}/{}", dir, name),
+        };
+        entries.insert(path, is_dir);
+    }
+    if !found {
+        return Err(AssetError::FileFindingError);
+    }
+    Ok(entries
+        .into_iter()
+        .map(|(path, is_dir)| DirEntry { path, is_dir })
+        .collect())
+}
+///Paths of every file inside directory and its subdirectories, sorted
+pub(crate) fn walk<'a>(
+    paths: impl Iterator<Item = &'a str>,
+    dir: &str,
+) -> Result<Vec<&'a str>, AssetError> {
+    let dir = normalize_dir(dir)?;
+    let mut files = Vec::new();
+    for path in paths {
+        if path == dir {
+            return Err(AssetError::NotAFile);
+        }
+        if strip_dir(path, &dir).is_some() {
+            files.push(path);
+        }
+    }
+    //root is found even if there are no files, as in list_dir
+    if files.is_empty() && !dir.is_empty() {
+        return Err(AssetError::FileFindingError);
+    }
+    files.sort_unstable();
+    Ok(files)
+}
+///Paths of every file matching pattern, sorted. See glob_match for syntax
+pub(crate) fn glob<'a>(paths: impl Iterator<Item = &'a str>, pattern: &str) -> Vec<&'a str> {
+    let mut files = paths
+        .filter(|path| glob_match(pattern, path))
+        .collect::<Vec<_>>();
+    files.sort_unstable();
+    files
+}
+///Path relative to dir if path is inside of it
+fn strip_dir<'a>(path: &'a str, dir: &str) -> Option<&'a str> {
+    if dir.is_empty() {
+        return Some(path);
+    }
+    path.strip_prefix(dir)?.strip_prefix('/')
+}
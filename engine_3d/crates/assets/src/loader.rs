@@ -1,30 +1,82 @@
 use crate::ext::PathEXT;
-//use crate::ranged_reader::{self, RangedReader};
+use crate::listing::{self, DirEntry};
+use crate::ranged_reader::AssetReader;
+use crate::{saver::LONG_WINDOW_LOG, FORMAT_VERSION, HEADER_SIZE, MAGIC};
 use byteorder::{ReadBytesExt, BE};
+use crc32fast::Hasher;
+use graphics::objects::texture::BuildError;
 use image::ImageError;
 use positioned_io::*;
-use std::io::Read;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read};
 use std::path::Path;
 use thiserror::Error;
+///How file data is stored in data file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Codec {
+    Zstd = 0,
+    ///zstd with dictionary stored in data file header
+    ZstdDictionary = 1,
+    ///data is stored as is, used for files that are compressed already
+    Stored = 2,
+    ///zstd with long distance matching, needs bigger window when decompressing
+    ZstdLong = 3,
+}
+impl Codec {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Codec::Zstd),
+            1 => Some(Codec::ZstdDictionary),
+            2 => Some(Codec::Stored),
+            3 => Some(Codec::ZstdLong),
+            _ => None,
+        }
+    }
+}
 #[derive(Debug, Clone)]
 pub struct File {
-    name: String,
+    path: String,
     start: u64,
     end: u64,
+    size: u64,
+    codec: Codec,
+    checksum: u32,
 }
 impl File {
-    pub fn new(name: String, start: u64, end: u64) -> Self {
-        Self { name, start, end }
+    pub fn new(path: String, start: u64, end: u64, size: u64, codec: Codec, checksum: u32) -> Self {
+        Self {
+            path,
+            start,
+            end,
+            size,
+            codec,
+            checksum,
+        }
     }
-}
-#[derive(Debug, Clone)]
-pub struct Directory {
-    path: String,
-    files: Vec<File>,
-}
-impl Directory {
-    pub fn new(path: String, files: Vec<File>) -> Self {
-        Self { path, files }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+    ///position of compressed data in data file
+    pub fn range(&self) -> std::ops::Range<u64> {
+        self.start..self.end
+    }
+    ///size of compressed data
+    pub fn compressed_size(&self) -> u64 {
+        self.end - self.start
+    }
+    ///size of file after decompression
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+    pub(crate) fn checksum(&self) -> u32 {
+        self.checksum
     }
 }
 #[derive(Error, Debug)]
@@ -35,6 +87,28 @@ pub enum AssetError {
     NotAFile,
     StringCastingError(#[from] std::string::FromUtf8Error),
     ImageError(#[from] ImageError),
+    InvalidDataFile,
+    UnsupportedVersion(u32),
+    Corrupted { path: String },
+    CorruptedDataFile,
+    NoImporter { path: String, asset_type: &'static str },
+    ImportFailed {
+        path: String,
+        importer: &'static str,
+        #[source]
+        error: crate::ImportError,
+    },
+    UnknownImageFormat { path: String },
+    ImageDecodingFailed {
+        path: String,
+        #[source]
+        error: ImageError,
+    },
+    TextureUploadFailed {
+        path: String,
+        #[source]
+        error: BuildError,
+    },
 }
 impl std::fmt::Display for AssetError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -48,78 +122,275 @@ impl std::fmt::Display for AssetError {
             AssetError::StringCastingError(error) => write!(f, "{}", error),
             AssetError::ImageError(error) => write!(f, "{}", error),
             AssetError::AssetImportingError => write!(f,"had error while loading asset"),
+            AssetError::InvalidDataFile => write!(
+                f,
+                "data file has no valid header (it's not a data file or it was created by an older saver)"
+            ),
+            AssetError::UnsupportedVersion(version) => write!(
+                f,
+                "data file version {} is not supported (expected {})",
+                version, FORMAT_VERSION
+            ),
+            AssetError::Corrupted { path } => write!(f, "data of {} is corrupted", path),
+            AssetError::CorruptedDataFile => write!(
+                f,
+                "data file is corrupted or was not completely written"
+            ),
+            AssetError::NoImporter { path, asset_type } => {
+                write!(f, "no importer of {} for {}", asset_type, path)
+            }
+            AssetError::ImportFailed {
+                path,
+                importer,
+                error,
+            } => write!(f, "{} failed to import {}: {}", importer, path, error),
+            AssetError::UnknownImageFormat { path } => write!(f, "image format of {} is unknown", path),
+            AssetError::ImageDecodingFailed { path, error } => write!(f, "can't decode image {}: {}", path, error),
+            AssetError::TextureUploadFailed { path, error } => write!(f, "can't upload texture {}: {}", path, error),
         }
     }
 }
-use zstd::*;
+pub(crate) fn path_str(path: &Path) -> Result<&str, AssetError> {
+    path.to_str().ok_or(AssetError::FileFindingError)
+}
+use zstd::{dict::DecoderDictionary, *};
+///Dictionary shared by small files
+pub(crate) struct Dictionary {
+    data: Vec<u8>,
+    decoder: DecoderDictionary<'static>,
+}
+impl Dictionary {
+    fn new(data: Vec<u8>) -> Self {
+        Self {
+            decoder: DecoderDictionary::copy(&data),
+            data,
+        }
+    }
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+impl std::fmt::Debug for Dictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Dictionary({} bytes)", self.data.len())
+    }
+}
 #[derive(Debug)]
 pub struct Assets {
-    directories: Vec<Directory>,
+    files: Vec<File>,
+    index: HashMap<String, usize>,
     source: RandomAccessFile,
+    dictionary: Option<Dictionary>,
+    checksum: u32,
 }
 impl Assets {
-    pub fn new<P: AsRef<Path>>(directories: Vec<Directory>, source: RandomAccessFile) -> Self {
+    pub fn new(
+        files: Vec<File>,
+        source: RandomAccessFile,
+        dictionary: Option<Vec<u8>>,
+        checksum: u32,
+    ) -> Self {
+        let index = files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (file.path.clone(), i))
+            .collect();
         Self {
-            directories,
+            files,
+            index,
             source,
+            dictionary: dictionary.map(Dictionary::new),
+            checksum,
         }
     }
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+    ///CRC32 of everything after header
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+    pub(crate) fn dictionary(&self) -> Option<&Dictionary> {
+        self.dictionary.as_ref()
+    }
     pub fn find_file<P: AsRef<Path>>(&self, path: P) -> Result<&File, AssetError> {
-        let directory_path = path.as_ref().parent().ok_or(AssetError::FileFindingError)?;
-        let file_name = path.as_ref().get_name().ok_or(AssetError::NotAFile)?;
-        let directory = self
-            .directories
-            .iter()
-            .find(|x| x.path == directory_path.to_str().unwrap())
-            .ok_or(AssetError::FileFindingError)?;
-        let file = directory
-            .files
-            .iter()
-            .find(|x| x.name == file_name)
-            .ok_or(AssetError::FileFindingError)?;
-        Ok(file)
+        let path = path.as_ref().to_asset_path().ok_or(AssetError::FileFindingError)?;
+        let &i = self.index.get(&path).ok_or(AssetError::FileFindingError)?;
+        Ok(&self.files[i])
+    }
+    ///Files and directories directly inside dir, "" lists the root
+    pub fn list_dir<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<DirEntry>, AssetError> {
+        listing::list_dir(self.paths(), path_str(dir.as_ref())?)
+    }
+    ///Every file inside dir and its subdirectories, sorted by path
+    pub fn walk<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<&File>, AssetError> {
+        let paths = listing::walk(self.paths(), path_str(dir.as_ref())?)?;
+        Ok(paths.into_iter().map(|path| &self.files[self.index[path]]).collect())
+    }
+    ///Every file matching pattern like "models/**/*.obj", sorted by path
+    pub fn glob(&self, pattern: &str) -> Vec<&File> {
+        listing::glob(self.paths(), pattern)
+            .into_iter()
+            .map(|path| &self.files[self.index[path]])
+            .collect()
+    }
+    fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|file| file.path())
     }
     pub fn open_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>, AssetError> {
         let file = self.find_file(path)?;
-        let mut file_buffer = vec![0u8; (file.end - file.start) as usize];
-        let _ = self
-            .source
+        let file_buffer = self.read_compressed(file)?;
+        let data = match file.codec {
+            Codec::Zstd => decode_all(file_buffer.as_slice())?,
+            Codec::ZstdDictionary => {
+                let mut data = Vec::with_capacity(file.size as usize);
+                Decoder::with_prepared_dictionary(file_buffer.as_slice(), self.decoder_dictionary(file)?)?
+                    .read_to_end(&mut data)?;
+                data
+            }
+            Codec::Stored => file_buffer,
+            Codec::ZstdLong => {
+                let mut data = Vec::with_capacity(file.size as usize);
+                let mut decoder = Decoder::with_buffer(file_buffer.as_slice())?;
+                decoder.window_log_max(LONG_WINDOW_LOG)?;
+                decoder.read_to_end(&mut data)?;
+                data
+            }
+        };
+        if data.len() as u64 != file.size {
+            return Err(AssetError::Corrupted {
+                path: file.path.clone(),
+            });
+        }
+        Ok(data)
+    }
+    ///Opens file for streaming, data is read and decompressed while reading.
+    ///Any amount of readers can be opened at the same time
+    pub fn open_reader<P: AsRef<Path>>(&self, path: P) -> Result<AssetReader<'_>, AssetError> {
+        let file = self.find_file(path)?;
+        let dictionary = match file.codec {
+            Codec::ZstdDictionary => Some(self.decoder_dictionary(file)?),
+            _ => None,
+        };
+        Ok(AssetReader::new(&self.source, file, dictionary)?)
+    }
+    fn decoder_dictionary(&self, file: &File) -> Result<&DecoderDictionary<'static>, AssetError> {
+        self.dictionary
+            .as_ref()
+            .map(|dictionary| &dictionary.decoder)
+            .ok_or_else(|| AssetError::Corrupted {
+                path: file.path.clone(),
+            })
+    }
+    ///Reads compressed data of file and checks it against stored checksum
+    pub(crate) fn read_compressed(&self, file: &File) -> Result<Vec<u8>, AssetError> {
+        let mut file_buffer = vec![0u8; file.compressed_size() as usize];
+        self.source
             .read_exact_at(file.start, file_buffer.as_mut_slice())?;
-        Ok(decode_all(file_buffer.as_slice())?)
+        if crc32fast::hash(&file_buffer) != file.checksum {
+            return Err(AssetError::Corrupted {
+                path: file.path.clone(),
+            });
+        }
+        Ok(file_buffer)
+    }
+    ///Checks every file and the whole data file against stored checksums.
+    ///Intended to be called once at startup, it reads the entire data file
+    pub fn verify(&self) -> Result<(), AssetError> {
+        for file in self.files.iter() {
+            self.read_compressed(file)?;
+        }
+        let mut hasher = Hasher::new();
+        let mut buffer = vec![0u8; 1 << 20];
+        let mut position = HEADER_SIZE;
+        loop {
+            let read = self.source.read_at(position, &mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            position += read as u64;
+        }
+        if hasher.finalize() != self.checksum {
+            return Err(AssetError::CorruptedDataFile);
+        }
+        Ok(())
     }
     pub fn open_file_string<P: AsRef<Path>>(&self, path: P) -> Result<String, AssetError> {
         let file = self.open_file(path)?;
         Ok(String::from_utf8(file)?)
     }
-    pub fn from_data_file<P: AsRef<Path>>(path: P) -> std::io::Result<Assets> {
-        let mut data_file = Cursor::new(RandomAccessFile::open(path)?);
-        let mut dictionaries = Vec::new();
-        let dictionaries_count = data_file.read_u64::<BE>()?;
-
-        for _ in 0..dictionaries_count {
-            //Reading dictionary path
-            let path_buffer_size = data_file.read_u64::<BE>()?;
-            let mut path_buffer = vec![0u8; path_buffer_size as usize];
-            data_file.read_exact(path_buffer.as_mut_slice())?;
-
-            let dictionary_path = String::from_utf8(path_buffer.to_vec()).unwrap();
-            let files_count = data_file.read_u64::<BE>()?; //Reading count of files in dictionary
-            let mut files = Vec::with_capacity(files_count as usize);
-
-            for _ in 0..files_count {
-                //reading file name
-                let file_name_size = data_file.read_u64::<BE>()?;
-                let mut file_name_buffer = vec![0u8; file_name_size as usize];
-                data_file.read_exact(file_name_buffer.as_mut_slice())?;
-                let file_name = String::from_utf8(file_name_buffer).unwrap();
+    ///Reads header and table of contents, file data is read lazily
+    pub fn from_data_file<P: AsRef<Path>>(path: P) -> Result<Assets, AssetError> {
+        let source = RandomAccessFile::open(path)?;
+        let mut header = [0u8; HEADER_SIZE as usize];
+        match source.read_exact_at(0, &mut header) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Err(AssetError::InvalidDataFile)
+            }
+            result => result?,
+        }
+        let mut header = header.as_slice();
+        let mut magic = [0u8; 8];
+        header.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(AssetError::InvalidDataFile);
+        }
+        let version = header.read_u32::<BE>()?;
+        if version != FORMAT_VERSION {
+            return Err(AssetError::UnsupportedVersion(version));
+        }
+        let dictionary_size = header.read_u64::<BE>()?;
+        let toc_size = header.read_u64::<BE>()?;
+        let index_checksum = header.read_u32::<BE>()?;
+        let checksum = header.read_u32::<BE>()?;
+        let data_file_size = source.size()?.unwrap_or(u64::MAX);
+        let index_size = dictionary_size
+            .checked_add(toc_size)
+            .ok_or(AssetError::InvalidDataFile)?;
+        if data_file_size < HEADER_SIZE + index_size {
+            return Err(AssetError::CorruptedDataFile);
+        }
+        //dictionary and whole table of contents are read at once
+        let mut index_buffer = vec![0u8; index_size as usize];
+        source.read_exact_at(HEADER_SIZE, &mut index_buffer)?;
+        if crc32fast::hash(&index_buffer) != index_checksum {
+            return Err(AssetError::CorruptedDataFile);
+        }
+        let (dictionary, mut toc) = index_buffer.split_at(dictionary_size as usize);
+        let dictionary = (dictionary_size > 0).then(|| dictionary.to_vec());
 
-                let file_start = data_file.read_u64::<BE>()?;
-                let file_end = data_file.read_u64::<BE>()?;
-                data_file.set_position((file_end - file_start) + data_file.position());
-                files.push(File::new(file_name, file_start, file_end));
+        let files_count = toc.read_u64::<BE>()?;
+        let mut files = Vec::with_capacity(files_count.min(toc_size) as usize);
+        for _ in 0..files_count {
+            let path_size = toc.read_u64::<BE>()?;
+            if path_size > toc.len() as u64 {
+                return Err(AssetError::InvalidDataFile);
+            }
+            let (path_buffer, rest) = toc.split_at(path_size as usize);
+            toc = rest;
+            let file_path = String::from_utf8(path_buffer.to_vec())?;
+            let file_start = toc.read_u64::<BE>()?;
+            let file_end = toc.read_u64::<BE>()?;
+            let file_size = toc.read_u64::<BE>()?;
+            let file_codec = Codec::from_u8(toc.read_u8()?).ok_or(AssetError::InvalidDataFile)?;
+            let file_checksum = toc.read_u32::<BE>()?;
+            if file_end < file_start {
+                return Err(AssetError::InvalidDataFile);
+            }
+            //truncated data file is rejected right away, other damage is found on read
+            if file_end > data_file_size {
+                return Err(AssetError::Corrupted { path: file_path });
             }
-            dictionaries.push(Directory::new(dictionary_path, files));
+            files.push(File::new(
+                file_path,
+                file_start,
+                file_end,
+                file_size,
+                file_codec,
+                file_checksum,
+            ));
         }
-        Ok(Self::new::<P>(dictionaries, data_file.into_inner()))
+        Ok(Self::new(files, source, dictionary, checksum))
     }
 }
@@ -0,0 +1,127 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    panic::{catch_unwind, AssertUnwindSafe},
+    rc::Rc,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    importer::{import_failed, ErasedAsyncImporter},
+    AssetError, Handle, ImportContext, Vfs,
+};
+
+///State of asset loaded with AssetServer::load_async
+pub enum LoadState<T> {
+    ///decoded on worker thread or waiting for upload on main thread
+    Loading,
+    Ready(Handle<T>),
+    Failed(Rc<AssetError>),
+}
+impl<T> Clone for LoadState<T> {
+    fn clone(&self) -> Self {
+        match self {
+            LoadState::Loading => LoadState::Loading,
+            LoadState::Ready(handle) => LoadState::Ready(handle.clone()),
+            LoadState::Failed(error) => LoadState::Failed(error.clone()),
+        }
+    }
+}
+///Handle to asset that is loaded in background, becomes ready after AssetServer::finish_loads uploads it
+pub struct AsyncHandle<T> {
+    state: Rc<RefCell<LoadState<T>>>,
+    path: Rc<str>,
+}
+impl<T> AsyncHandle<T> {
+    pub(crate) fn new(state: LoadState<T>, path: Rc<str>) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(state)),
+            path,
+        }
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn state(&self) -> LoadState<T> {
+        self.state.borrow().clone()
+    }
+    pub fn is_loading(&self) -> bool {
+        matches!(*self.state.borrow(), LoadState::Loading)
+    }
+    pub fn is_ready(&self) -> bool {
+        matches!(*self.state.borrow(), LoadState::Ready(_))
+    }
+    pub fn is_failed(&self) -> bool {
+        matches!(*self.state.borrow(), LoadState::Failed(_))
+    }
+    ///asset if it's ready
+    pub fn get(&self) -> Option<Handle<T>> {
+        match &*self.state.borrow() {
+            LoadState::Ready(handle) => Some(handle.clone()),
+            _ => None,
+        }
+    }
+    pub fn error(&self) -> Option<Rc<AssetError>> {
+        match &*self.state.borrow() {
+            LoadState::Failed(error) => Some(error.clone()),
+            _ => None,
+        }
+    }
+    pub(crate) fn set(&self, state: LoadState<T>) {
+        *self.state.borrow_mut() = state;
+    }
+}
+impl<T> Clone for AsyncHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            path: self.path.clone(),
+        }
+    }
+}
+pub(crate) type Decoded = Result<Box<dyn Any + Send>, AssetError>;
+///Threads decoding assets, they stop when Workers is dropped
+pub(crate) struct Workers {
+    jobs: Sender<(u64, String, Arc<dyn ErasedAsyncImporter>)>,
+    decoded: Receiver<(u64, Decoded)>,
+}
+impl Workers {
+    pub(crate) fn new(count: usize, source: Arc<Vfs>) -> Self {
+        let (jobs, receiver) = channel::<(u64, String, Arc<dyn ErasedAsyncImporter>)>();
+        let (sender, decoded) = channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..count.max(1) {
+            let (receiver, sender, source) = (receiver.clone(), sender.clone(), source.clone());
+            thread::Builder::new()
+                .name(format!("asset worker {}", i))
+                .spawn(move || loop {
+                    let job = receiver.lock().map(|receiver| receiver.recv());
+                    let Ok(Ok((id, path, importer))) = job else {
+                        return;
+                    };
+                    let result = catch_unwind(AssertUnwindSafe(|| {
+                        importer.decode(&ImportContext::new(&path, &source))
+                    }))
+                    .unwrap_or_else(|_| Err("importer panicked".into()))
+                    .map_err(|error| import_failed(&path, importer.name(), error));
+                    if sender.send((id, result)).is_err() {
+                        return;
+                    }
+                })
+                .expect("failed to spawn asset worker");
+        }
+        Self { jobs, decoded }
+    }
+    pub(crate) fn send(&self, id: u64, path: &str, importer: Arc<dyn ErasedAsyncImporter>) {
+        //workers only stop when Workers is dropped
+        let _ = self.jobs.send((id, path.to_owned(), importer));
+    }
+    ///decoded asset if some is ready
+    pub(crate) fn try_recv(&self) -> Option<(u64, Decoded)> {
+        self.decoded.try_recv().ok()
+    }
+}
@@ -0,0 +1,269 @@
+use std::{collections::HashMap, path::Path};
+
+use graphics::objects::{
+    model::{obj::parse_obj, Model},
+    texture::Texture2D,
+    vertex::ModelVertex,
+};
+use image::DynamicImage;
+use math::Vec3;
+use rendering::material::Material;
+
+use crate::{
+    image_importer::{decode_image, upload_texture},
+    AsyncImporter, ImportContext, ImportError, ImportSettings, TextureSettings,
+};
+
+///Material from Wavefront mtl file, texture paths are as written in file
+#[derive(Debug, Clone, PartialEq)]
+pub struct MtlMaterial {
+    pub name: String,
+    ///Kd
+    pub diffuse: [f32; 3],
+    ///Ks
+    pub specular: [f32; 3],
+    ///Ns
+    pub shininess: f32,
+    ///d, 1 is opaque
+    pub dissolve: f32,
+    ///map_Kd
+    pub diffuse_map: Option<String>,
+    ///map_Ks
+    pub specular_map: Option<String>,
+    ///map_Bump or bump, path as written in mtl file since it is not loaded
+    pub bump_map: Option<String>,
+}
+impl MtlMaterial {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            diffuse: [1.0; 3],
+            specular: [0.5; 3],
+            shininess: 32.0,
+            dissolve: 1.0,
+            diffuse_map: None,
+            specular_map: None,
+            bump_map: None,
+        }
+    }
+    ///Material with textures found by their paths, missing textures are white
+    pub fn material(&self, textures: &HashMap<String, Texture2D>) -> Material {
+        let texture = |path: &Option<String>| path.as_ref().and_then(|path| textures.get(path)).cloned();
+        let [r, g, b] = self.diffuse;
+        Material {
+            color: Vec3::new(r, g, b),
+            main_texture: texture(&self.diffuse_map).unwrap_or_else(Texture2D::white),
+            specular_map: texture(&self.specular_map).unwrap_or_else(Texture2D::white),
+            specular: self.specular.iter().sum::<f32>() / 3.0,
+            //geometry pass can't store more
+            shininess: self.shininess.clamp(1.0, 256.0),
+            transparent: self.dissolve < 1.0,
+        }
+    }
+    ///Textures loaded for Material, bump map isn't loaded as renderer has no normal mapping
+    fn textures_mut(&mut self) -> [&mut Option<String>; 2] {
+        [&mut self.diffuse_map, &mut self.specular_map]
+    }
+}
+///Parses mtl file, unknown statements are skipped
+pub fn parse_mtl(source: &str) -> Result<Vec<MtlMaterial>, ImportError> {
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+    for (number, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args = words.collect::<Vec<_>>();
+        let error = |message: &str| format!("line {}: {}", number + 1, message);
+        let keyword = keyword.to_lowercase();
+        if keyword == "newmtl" {
+            materials.push(MtlMaterial::new(&args.join(" ")));
+            continue;
+        }
+        let Some(material) = materials.last_mut() else {
+            if matches!(keyword.as_str(), "kd" | "ks" | "ns" | "d" | "tr" | "map_kd" | "map_ks" | "map_bump" | "bump") {
+                return Err(error(&format!("{} before newmtl", keyword)).into());
+            }
+            continue;
+        };
+        let number = |i: usize| -> Result<f32, ImportError> {
+            let arg = args.get(i).ok_or_else(|| error(&format!("{} expects a number", keyword)))?;
+            arg.parse().map_err(|_| error(&format!("{} is not a number", arg)).into())
+        };
+        let color = || -> Result<[f32; 3], ImportError> {
+            let r = number(0)?;
+            //single value is grey
+            match args.len() {
+                1 => Ok([r; 3]),
+                _ => Ok([r, number(1)?, number(2)?]),
+            }
+        };
+        let texture = || texture_path(&args).ok_or_else(|| error(&format!("{} expects a file", keyword)));
+        match keyword.as_str() {
+            "kd" => material.diffuse = color()?,
+            "ks" => material.specular = color()?,
+            "ns" => material.shininess = number(0)?,
+            "d" => material.dissolve = number(0)?,
+            "tr" => material.dissolve = 1.0 - number(0)?,
+            "map_kd" => material.diffuse_map = Some(texture()?),
+            "map_ks" => material.specular_map = Some(texture()?),
+            "map_bump" | "bump" => material.bump_map = Some(texture()?),
+            _ => (),
+        }
+    }
+    Ok(materials)
+}
+///File name of texture statement, options before it are skipped
+fn texture_path(args: &[&str]) -> Option<String> {
+    let mut args = args;
+    while let Some((option, rest)) = args.split_first() {
+        let count = match *option {
+            "-bm" | "-blendu" | "-blendv" | "-boost" | "-cc" | "-clamp" | "-imfchan" | "-texres" | "-type" => 1,
+            "-mm" => 2,
+            //up to 3 numbers
+            "-o" | "-s" | "-t" => rest.iter().take(3).take_while(|arg| arg.parse::<f32>().is_ok()).count(),
+            _ => break,
+        };
+        args = rest.get(count..)?;
+    }
+    Some(args.join(" ")).filter(|path| !path.is_empty())
+}
+///Texture paths written by exporters are often absolute paths on artist's machine,
+///so file with the same name next to imported file is used if path doesn't exist
+fn find_texture(context: &ImportContext, path: &str) -> Option<String> {
+    let exists = |path: &String| context.source().find_file(path).is_ok();
+    context.resolve(path).ok().filter(exists).or_else(|| {
+        let name = path.rsplit(['/', '\\']).next()?;
+        context.resolve(name).ok().filter(exists)
+    })
+}
+///Materials of mtl file
+pub struct MtlImporter;
+impl AsyncImporter for MtlImporter {
+    type Decoded = Vec<MtlMaterial>;
+    type Output = Vec<MtlMaterial>;
+    fn name(&self) -> &'static str {
+        "mtl"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["mtl"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<Vec<MtlMaterial>, ImportError> {
+        parse_mtl(&context.read_string()?)
+    }
+    fn finish(&self, decoded: Vec<MtlMaterial>) -> Result<Vec<MtlMaterial>, ImportError> {
+        Ok(decoded)
+    }
+}
+///Obj model split by material
+pub struct ObjModel {
+    pub parts: Vec<ObjPart>,
+    ///mtl files and textures referenced by model that weren't found
+    pub missing: Vec<String>,
+}
+pub struct ObjPart {
+    ///name from usemtl, empty for faces before first usemtl
+    pub material_name: String,
+    pub model: Model<ModelVertex>,
+    ///default material if mtl doesn't have it
+    pub material: Material,
+}
+///Obj model decoded on worker thread, texture paths of materials are resolved to asset paths
+pub struct DecodedObj {
+    pub parts: Vec<(String, Model<ModelVertex>)>,
+    pub materials: HashMap<String, MtlMaterial>,
+    ///textures by asset path with settings from their meta files
+    pub images: HashMap<String, (DynamicImage, TextureSettings)>,
+    pub missing: Vec<String>,
+}
+///Obj models with materials from their mtllib files,
+///textures are uploaded in finish so it must be called on the thread with GL context
+pub struct ObjModelImporter;
+impl AsyncImporter for ObjModelImporter {
+    type Decoded = DecodedObj;
+    type Output = ObjModel;
+    fn name(&self) -> &'static str {
+        "obj"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<DecodedObj, ImportError> {
+        let obj = parse_obj(&context.read_string()?)?;
+        let mut model = obj.model;
+        let model_settings = context.settings()?.model;
+        model_settings.transform(&mut model);
+        let mut decoded = DecodedObj {
+            parts: Vec::new(),
+            materials: HashMap::new(),
+            images: HashMap::new(),
+            missing: Vec::new(),
+        };
+        for library in obj.material_libraries {
+            match context.resolve(&library).and_then(|path| context.source().open_file_string(path)) {
+                Ok(mtl) => decoded.materials.extend(
+                    parse_mtl(&mtl)?
+                        .into_iter()
+                        .map(|material| (material.name.clone(), material)),
+                ),
+                Err(_) => decoded.missing.push(library),
+            }
+        }
+        //transform keeps order of vertices, so ranges still match
+        for range in obj.materials {
+            let vertices = &model.verticies()[range.triangles.start * 3..range.triangles.end * 3];
+            match decoded.parts.iter_mut().find(|(part, _)| *part == range.name) {
+                Some((_, model)) => model.verticies_mut().extend_from_slice(vertices),
+                None => decoded.parts.push((range.name, Model::new(vertices.to_vec(), None))),
+            }
+        }
+        for (_, model) in &mut decoded.parts {
+            *model.indicies_mut() = Some((0..model.verticies().len() as u32).collect());
+            model_settings.process(model);
+        }
+        for material in decoded.materials.values_mut() {
+            for texture in material.textures_mut() {
+                let Some(path) = texture.take() else {
+                    continue;
+                };
+                match find_texture(context, &path) {
+                    Some(found) => {
+                        if !decoded.images.contains_key(&found) {
+                            let settings = ImportSettings::load(context.source(), &found)?.texture;
+                            let image = decode_image(&context.source().open_file(&found)?, Path::new(&found))?;
+                            decoded.images.insert(found.clone(), (settings.apply_to_image(image), settings));
+                        }
+                        *texture = Some(found);
+                    }
+                    None if !decoded.missing.contains(&path) => decoded.missing.push(path),
+                    None => (),
+                }
+            }
+        }
+        Ok(decoded)
+    }
+    fn finish(&self, decoded: DecodedObj) -> Result<ObjModel, ImportError> {
+        let mut textures = HashMap::new();
+        for (path, (image, settings)) in decoded.images {
+            let texture = upload_texture(image, &settings, &path)?;
+            textures.insert(path, texture);
+        }
+        let parts = decoded
+            .parts
+            .into_iter()
+            .map(|(material_name, model)| ObjPart {
+                material: decoded
+                    .materials
+                    .get(&material_name)
+                    .map_or_else(Material::default, |material| material.material(&textures)),
+                material_name,
+                model,
+            })
+            .collect();
+        Ok(ObjModel {
+            parts,
+            missing: decoded.missing,
+        })
+    }
+}
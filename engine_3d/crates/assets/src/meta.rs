@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+
+use graphics::objects::{
+    model::{normals::NormalWeighting, processing::VERTEX_CACHE_SIZE, Model},
+    shader::ShaderType,
+    texture::{Filter, Texture2DBuilder, TextureFormat, TextureWrap},
+    vertex::ModelVertex,
+};
+use image::{imageops::FilterType, DynamicImage};
+use math::Vec3;
+use serde::Deserialize;
+
+use crate::{AssetError, ImportError, Vfs};
+
+///settings of "textures/wall.png" are stored in "textures/wall.png.meta"
+pub const META_EXTENSION: &str = "meta";
+///path of sidecar file with import settings of file at path
+pub fn meta_path(path: &str) -> String {
+    format!("{}.{}", path, META_EXTENSION)
+}
+///Import settings read from toml sidecar file next to asset, every field is optional:
+///```toml
+///[texture]
+///flip = false
+///srgb = true
+///filter = "linear"
+///wrap = "clamp"
+///mipmaps = false
+///max_size = 512
+///
+///[model]
+///scale = 0.01
+///up_axis = "z"
+///normals = "smooth"
+///crease_angle = 45
+///weld = 0.0001
+///optimize = true
+///
+///[shader]
+///stage = "fragment"
+///defines = { SHADOWS = 1, KERNEL = "5" }
+///```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImportSettings {
+    pub texture: TextureSettings,
+    pub model: ModelSettings,
+    pub shader: ShaderSettings,
+}
+impl ImportSettings {
+    ///Settings of file at path, defaults if it has no meta file
+    pub fn load(source: &Vfs, path: &str) -> Result<Self, ImportError> {
+        let meta = meta_path(path);
+        match source.open_file_string(&meta) {
+            Ok(text) => Self::parse(&text).map_err(|error| format!("{}: {}", meta, error).into()),
+            Err(AssetError::FileFindingError) => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+    pub fn parse(text: &str) -> Result<Self, ImportError> {
+        Ok(toml::from_str(text)?)
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureWrapMode {
+    Repeat,
+    Clamp,
+    Mirror,
+}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TextureSettings {
+    ///images are flipped by default, OpenGL expects first row at the bottom
+    pub flip: bool,
+    ///color textures should be srgb, data textures (normal, specular maps) linear
+    pub srgb: bool,
+    pub filter: TextureFilter,
+    pub wrap: TextureWrapMode,
+    pub mipmaps: bool,
+    ///larger images are downscaled so their longest side fits, aspect ratio is kept
+    pub max_size: Option<u32>,
+}
+impl Default for TextureSettings {
+    fn default() -> Self {
+        Self {
+            flip: true,
+            srgb: false,
+            filter: TextureFilter::Nearest,
+            wrap: TextureWrapMode::Repeat,
+            mipmaps: true,
+            max_size: None,
+        }
+    }
+}
+impl TextureSettings {
+    ///flip and max_size, done on worker thread
+    pub fn apply_to_image(&self, mut image: DynamicImage) -> DynamicImage {
+        if let Some(max_size) = self.max_size.filter(|max_size| image.width().max(image.height()) > *max_size) {
+            image = image.resize(max_size, max_size, FilterType::Triangle);
+        }
+        match self.flip {
+            true => image.flipv(),
+            false => image,
+        }
+    }
+    ///min and mag filter of texture with or without mipmaps
+    pub fn filters(&self, mipmaps: bool) -> (Filter, Filter) {
+        match (self.filter, mipmaps) {
+            (TextureFilter::Nearest, true) => (Filter::NearestLinearMipMap, Filter::Nearest),
+            (TextureFilter::Linear, true) => (Filter::LinearMipMap, Filter::Linear),
+            (TextureFilter::Nearest, false) => (Filter::Nearest, Filter::Nearest),
+            (TextureFilter::Linear, false) => (Filter::Linear, Filter::Linear),
+        }
+    }
+    ///filter, wrap and format, mipmaps are generated after texture is built
+    pub fn apply(&self, builder: Texture2DBuilder) -> Texture2DBuilder {
+        let (min_filter, mag_filter) = self.filters(self.mipmaps);
+        let wrap = match self.wrap {
+            TextureWrapMode::Repeat => TextureWrap::Repeat,
+            TextureWrapMode::Clamp => TextureWrap::ClampToEdge,
+            TextureWrapMode::Mirror => TextureWrap::MirroredRepeat,
+        };
+        let format = match self.srgb {
+            true => TextureFormat::SrgbA,
+            false => TextureFormat::RGBA,
+        };
+        builder
+            .min_filter(min_filter)
+            .mag_filter(mag_filter)
+            .wrap(wrap)
+            .internal_format(format)
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpAxis {
+    Y,
+    ///converted to y up, most CAD tools and Blender export it
+    Z,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NormalMode {
+    ///normals from file, flat if file has none
+    Import,
+    ///normals from file are replaced with face normals
+    Flat,
+    ///normals from file are replaced with angle weighted smooth normals, see crease_angle
+    Smooth,
+}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelSettings {
+    pub scale: f32,
+    pub up_axis: UpAxis,
+    pub normals: NormalMode,
+    ///in degrees, faces meeting at larger angle have sharp edge between them with smooth normals
+    pub crease_angle: f32,
+    ///vertices closer than this are merged, obj models have 3 vertices per triangle without it
+    pub weld: Option<f32>,
+    ///reorders triangles and vertices for vertex cache and fetch, see Model::optimize
+    pub optimize: bool,
+}
+impl Default for ModelSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            up_axis: UpAxis::Y,
+            normals: NormalMode::Import,
+            crease_angle: 60.0,
+            weld: None,
+            optimize: false,
+        }
+    }
+}
+impl ModelSettings {
+    pub fn apply(&self, model: &mut Model<ModelVertex>) {
+        self.transform(model);
+        self.process(model);
+    }
+    ///scale, up axis and normals, order of vertices is kept
+    pub fn transform(&self, model: &mut Model<ModelVertex>) {
+        if self.scale == 1.0 && self.up_axis == UpAxis::Y && self.normals == NormalMode::Import {
+            return;
+        }
+        for vertex in model.verticies_mut().iter_mut() {
+            if self.up_axis == UpAxis::Z {
+                let [x, y, z] = vertex.position;
+                vertex.position = [x, z, -y];
+                let [x, y, z] = vertex.normal;
+                vertex.normal = [x, z, -y];
+            }
+            vertex.position = vertex.position.map(|value| value * self.scale);
+        }
+        match self.normals {
+            NormalMode::Import => (),
+            NormalMode::Flat => flat_normals(model),
+            NormalMode::Smooth => model.smooth_normals(self.crease_angle.to_radians(), NormalWeighting::Angle),
+        }
+    }
+    ///welding and optimization, vertices are merged and reordered
+    pub fn process(&self, model: &mut Model<ModelVertex>) {
+        if let Some(epsilon) = self.weld {
+            model.weld(epsilon);
+        }
+        if self.optimize {
+            model.optimize_vertex_cache(VERTEX_CACHE_SIZE);
+            model.optimize_vertex_fetch();
+        }
+    }
+}
+///Every triangle gets its own vertices with normal of triangle
+fn flat_normals(model: &mut Model<ModelVertex>) {
+    if let Some(indicies) = model.indicies_mut().take() {
+        let verticies = indicies.iter().map(|&i| model.verticies()[i as usize]).collect::<Vec<_>>();
+        *model.indicies_mut() = Some((0..verticies.len() as u32).collect());
+        *model.verticies_mut() = verticies;
+    }
+    for triangle in model.verticies_mut().chunks_exact_mut(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(triangle[i].position));
+        let normal = (b - a).cross(c - a).normalize_or_zero().into();
+        for vertex in triangle {
+            vertex.normal = normal;
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+    Geometry,
+}
+impl From<ShaderStage> for ShaderType {
+    fn from(stage: ShaderStage) -> Self {
+        match stage {
+            ShaderStage::Vertex => ShaderType::Vertex,
+            ShaderStage::Fragment => ShaderType::Fragment,
+            ShaderStage::Compute => ShaderType::Compute,
+            ShaderStage::Geometry => ShaderType::Geometry,
+        }
+    }
+}
+///Value of define, written as is after its name
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum DefineValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+impl std::fmt::Display for DefineValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", *value as u8),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{:?}", value),
+            Self::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShaderSettings {
+    ///stage of shader if it can't be told from file name
+    pub stage: Option<ShaderStage>,
+    ///injected after #version in name order
+    pub defines: BTreeMap<String, DefineValue>,
+}
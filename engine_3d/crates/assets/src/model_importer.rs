@@ -1,14 +1,72 @@
-use std::path::Path;
-use graphics::objects::{model::{Model,from_str}, vertex::ModelVertex};
+use graphics::objects::{
+    model::{obj::ObjError, InstancedModel, Model},
+    vertex::{ModelVertex, TangentVertex},
+};
 
-use crate::{AssetError, Assets};
+use crate::{cooked_mesh::find_cooked, AsyncImporter, ImportContext, ImportError};
 
-pub trait Modelmporter {
-    fn import_model<P: AsRef<Path>>(&mut self, path: P) -> Result<Model<ModelVertex>,AssetError>;
+///Wavefront obj models, cooked version made by saver is loaded instead of parsing when data file has it.
+///Model settings from meta file are applied after loading
+pub struct ObjImporter;
+impl AsyncImporter for ObjImporter {
+    type Decoded = Model<ModelVertex>;
+    type Output = Model<ModelVertex>;
+    fn name(&self) -> &'static str {
+        "obj"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<Model<ModelVertex>, ImportError> {
+        let settings = context.settings()?.model;
+        let mut model = match find_cooked(context) {
+            Some(model) => model,
+            None => parse_model(&context.read_string()?)?,
+        };
+        settings.apply(&mut model);
+        Ok(model)
+    }
+    fn finish(&self, decoded: Model<ModelVertex>) -> Result<Model<ModelVertex>, ImportError> {
+        Ok(decoded)
+    }
 }
-impl Modelmporter for Assets {
-    fn import_model<P: AsRef<Path>>(&mut self, path: P) -> Result<Model<ModelVertex>,AssetError> {
-        let source = self.open_file_string(path)?;
-        from_str(&source).ok_or(AssetError::AssetImportingError)
+///Wavefront obj models uploaded to GPU, finish must be called on the thread with GL context
+pub struct InstancedObjImporter;
+impl AsyncImporter for InstancedObjImporter {
+    type Decoded = Model<ModelVertex>;
+    type Output = InstancedModel;
+    fn name(&self) -> &'static str {
+        "obj"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<Model<ModelVertex>, ImportError> {
+        ObjImporter.decode(context)
+    }
+    fn finish(&self, decoded: Model<ModelVertex>) -> Result<InstancedModel, ImportError> {
+        Ok(decoded.instantiate())
     }
 }
+///Wavefront obj models with MikkTSpace tangents for normal mapping, model must have texture coordinates
+pub struct TangentObjImporter;
+impl AsyncImporter for TangentObjImporter {
+    type Decoded = Model<TangentVertex>;
+    type Output = Model<TangentVertex>;
+    fn name(&self) -> &'static str {
+        "obj"
+    }
+    fn extensions(&self) -> &[&str] {
+        &["obj"]
+    }
+    fn decode(&self, context: &ImportContext) -> Result<Model<TangentVertex>, ImportError> {
+        let model = ObjImporter.decode(context)?;
+        Ok(model.with_tangents().ok_or("can't generate tangents")?)
+    }
+    fn finish(&self, decoded: Model<TangentVertex>) -> Result<Model<TangentVertex>, ImportError> {
+        Ok(decoded)
+    }
+}
+pub(crate) fn parse_model(source: &str) -> Result<Model<ModelVertex>, ObjError> {
+    source.parse()
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fs,
+    io::{BufReader, BufWriter, Read, Result, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+const CACHE_MAGIC: [u8; 8] = *b"E3DCACHE";
+const CACHE_VERSION: u32 = 4;
+
+///What was known about source of an entry when data file was written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceInfo {
+    pub size: u64,
+    ///seconds and nanoseconds since unix epoch
+    pub modified: (u64, u32),
+    ///CRC32 of uncompressed content
+    pub hash: u32,
+    ///compression the entry was written with, see Compression::id
+    pub compression: u32,
+    ///MESH_VERSION of cooked entries, so they are recooked when format changes. 0 for copied files
+    pub cook_version: u32,
+    ///size of entry compressed without dictionary, used to report what dictionary saved
+    pub plain_size: u64,
+}
+impl SourceInfo {
+    ///size and modification time of file
+    pub fn stat(path: impl AsRef<Path>) -> Result<(u64, (u64, u32))> {
+        let metadata = fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok((
+            metadata.len(),
+            (modified.as_secs(), modified.subsec_nanos()),
+        ))
+    }
+}
+///Cache is stored next to data file and describes sources of its entries.
+///It's only valid for the data file it was written with
+#[derive(Debug, Default)]
+pub struct PackCache {
+    data_file_checksum: u32,
+    entries: HashMap<String, SourceInfo>,
+}
+impl PackCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn cache_path(data_file_path: &Path) -> PathBuf {
+        let mut path = OsString::from(data_file_path.as_os_str());
+        path.push(".cache");
+        path.into()
+    }
+    pub fn get(&self, path: &str) -> Option<&SourceInfo> {
+        self.entries.get(path)
+    }
+    pub fn insert(&mut self, path: String, info: SourceInfo) {
+        self.entries.insert(path, info);
+    }
+    pub fn set_data_file_checksum(&mut self, checksum: u32) {
+        self.data_file_checksum = checksum;
+    }
+    ///Returns None if there is no cache or it doesn't match data file
+    pub fn load(data_file_path: &Path, data_file_checksum: u32) -> Option<Self> {
+        let cache = Self::read(data_file_path).ok()?;
+        (cache.data_file_checksum == data_file_checksum).then_some(cache)
+    }
+    fn read(data_file_path: &Path) -> Result<Self> {
+        let mut reader = BufReader::new(fs::File::open(Self::cache_path(data_file_path))?);
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != CACHE_MAGIC || reader.read_u32::<BE>()? != CACHE_VERSION {
+            return Err(std::io::ErrorKind::InvalidData.into());
+        }
+        let data_file_checksum = reader.read_u32::<BE>()?;
+        let count = reader.read_u64::<BE>()?;
+        let mut entries = HashMap::new();
+        for _ in 0..count {
+            let path_size = reader.read_u64::<BE>()?;
+            let mut path = Vec::new();
+            (&mut reader).take(path_size).read_to_end(&mut path)?;
+            let path = String::from_utf8(path).map_err(|_| std::io::ErrorKind::InvalidData)?;
+            let info = SourceInfo {
+                size: reader.read_u64::<BE>()?,
+                modified: (reader.read_u64::<BE>()?, reader.read_u32::<BE>()?),
+                hash: reader.read_u32::<BE>()?,
+                compression: reader.read_u32::<BE>()?,
+                cook_version: reader.read_u32::<BE>()?,
+                plain_size: reader.read_u64::<BE>()?,
+            };
+            entries.insert(path, info);
+        }
+        Ok(Self {
+            data_file_checksum,
+            entries,
+        })
+    }
+    pub fn save(&self, data_file_path: &Path) -> Result<()> {
+        let cache_path = Self::cache_path(data_file_path);
+        let temp_path = cache_path.with_extension(format!("{}.tmp", std::process::id()));
+        let mut writer = BufWriter::new(fs::File::create(&temp_path)?);
+        writer.write_all(&CACHE_MAGIC)?;
+        writer.write_u32::<BE>(CACHE_VERSION)?;
+        writer.write_u32::<BE>(self.data_file_checksum)?;
+        writer.write_u64::<BE>(self.entries.len() as u64)?;
+        for (path, info) in self.entries.iter() {
+            writer.write_u64::<BE>(path.len() as u64)?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_u64::<BE>(info.size)?;
+            writer.write_u64::<BE>(info.modified.0)?;
+            writer.write_u32::<BE>(info.modified.1)?;
+            writer.write_u32::<BE>(info.hash)?;
+            writer.write_u32::<BE>(info.compression)?;
+            writer.write_u32::<BE>(info.cook_version)?;
+            writer.write_u64::<BE>(info.plain_size)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        fs::rename(temp_path, cache_path)
+    }
+}
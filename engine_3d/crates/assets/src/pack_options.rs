@@ -0,0 +1,144 @@
+use crate::{ext::glob_match, COMPRESS_LEVEL};
+
+///How single file is stored in data file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    ///copied as is, for files that are already compressed (png, jpg, ogg...)
+    Stored,
+    ///zstd with given level (1-22)
+    Zstd(i32),
+    ///zstd with long distance matching, for large files with repeating data (meshes)
+    ZstdLong(i32),
+}
+impl Compression {
+    ///identifies compression in pack cache, so entries are recompressed when it changes
+    pub(crate) fn id(&self) -> u32 {
+        let (kind, level) = match *self {
+            Compression::Stored => (0, 0),
+            Compression::Zstd(level) => (1, level),
+            Compression::ZstdLong(level) => (2, level),
+        };
+        kind << 8 | level as i8 as u8 as u32
+    }
+}
+///Small files are compressed with a dictionary trained on them,
+///it helps when there are a lot of small similar files (shaders, .mtl, text)
+#[derive(Debug, Clone, Copy)]
+pub struct DictionaryOptions {
+    ///files larger than this are compressed without dictionary
+    pub max_file_size: u64,
+    ///maximum size of trained dictionary
+    pub max_dictionary_size: usize,
+}
+impl Default for DictionaryOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: 16 * 1024,
+            max_dictionary_size: 64 * 1024,
+        }
+    }
+}
+///Decides how every file is compressed.
+///Rules are checked in order they were added and the last matching one wins,
+///files that match no rule are compressed with default level
+#[derive(Debug, Clone)]
+pub struct PackOptions {
+    default: Compression,
+    rules: Vec<(String, Compression)>,
+    dictionary: Option<DictionaryOptions>,
+    threads: usize,
+    cook_meshes: bool,
+}
+///formats that zstd can't make smaller
+pub const COMPRESSED_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "ogg", "mp3", "ktx2", "zip"];
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl PackOptions {
+    pub fn new() -> Self {
+        Self {
+            default: Compression::Zstd(COMPRESS_LEVEL),
+            rules: Vec::new(),
+            dictionary: None,
+            threads: 0,
+            cook_meshes: false,
+        }
+    }
+    ///fast compression for debug builds, already compressed formats are stored as is
+    pub fn dev() -> Self {
+        Self::new().level(1).cook_meshes(true).store_compressed()
+    }
+    ///best compression for release builds, already compressed formats are stored as is
+    pub fn release() -> Self {
+        Self::new()
+            .level(22)
+            .dictionary(DictionaryOptions::default())
+            .cook_meshes(true)
+            .store_compressed()
+            .extension("obj", Compression::ZstdLong(22))
+            .extension("mesh", Compression::ZstdLong(22))
+    }
+    ///files of COMPRESSED_EXTENSIONS are stored without compression
+    pub fn store_compressed(self) -> Self {
+        COMPRESSED_EXTENSIONS
+            .iter()
+            .fold(self, |options, extension| options.extension(extension, Compression::Stored))
+    }
+    ///compression level of files that match no rule
+    pub fn level(mut self, level: i32) -> Self {
+        self.default = Compression::Zstd(level);
+        self
+    }
+    ///pattern without '/' is matched against file name, otherwise against whole path.
+    ///See glob_match for syntax
+    pub fn rule(mut self, pattern: &str, compression: Compression) -> Self {
+        self.rules.push((pattern.to_owned(), compression));
+        self
+    }
+    pub fn extension(self, extension: &str, compression: Compression) -> Self {
+        self.rule(&format!("*.{}", extension), compression)
+    }
+    ///enables dictionary for small files compressed with zstd
+    pub fn dictionary(mut self, options: DictionaryOptions) -> Self {
+        self.dictionary = Some(options);
+        self
+    }
+    pub fn dictionary_options(&self) -> Option<DictionaryOptions> {
+        self.dictionary
+    }
+    ///count of threads files are compressed on, 0 means one per cpu core.
+    ///Data file is the same no matter how many threads were used
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+    pub fn thread_count(&self) -> usize {
+        match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, |count| count.get()),
+            threads => threads,
+        }
+    }
+    ///stores cooked version of every .obj file next to it as "<name>.obj.mesh",
+    ///obj importers load it instead of parsing obj
+    pub fn cook_meshes(mut self, cook: bool) -> Self {
+        self.cook_meshes = cook;
+        self
+    }
+    pub fn cooks_meshes(&self) -> bool {
+        self.cook_meshes
+    }
+    ///path is relative to assets folder and separated with '/'
+    pub fn compression_for(&self, path: &str) -> Compression {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| match pattern.contains('/') {
+                true => glob_match(pattern, path),
+                false => glob_match(pattern, name),
+            })
+            .map_or(self.default, |(_, compression)| *compression)
+    }
+}
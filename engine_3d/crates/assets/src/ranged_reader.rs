@@ -1,29 +1,155 @@
-//wtf!!!!!!
-/*use std::io::BufReader;
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom};
 
-use positioned_io::Cursor;
-use zstd::Decoder;
+use crc32fast::Hasher;
+use positioned_io::{RandomAccessFile, ReadAt};
+use zstd::{dict::DecoderDictionary, Decoder};
+
+use crate::{saver::LONG_WINDOW_LOG, AssetError, Codec, File};
+
+///Reads compressed data of a single file from data file.
+///Checksum is checked when the end of the range is reached, unless reader jumped over some data
 pub struct RangedReader<'a> {
-    inner: Decoder<'a, BufReader<Cursor<std::fs::File>>>,
-    pos: usize,
-    end: u64,
-}
-impl RangedReader<'_> {
-    pub fn new(file: std::fs::File, start: u64, end: u64) -> Self {
-        let reader = Decoder::new({
-            let mut cur = Cursor::new(file);
-            cur.set_position(start);
-            cur
+    source: &'a RandomAccessFile,
+    file: &'a File,
+    position: u64,
+    hasher: Option<Hasher>,
+}
+impl<'a> RangedReader<'a> {
+    pub fn new(source: &'a RandomAccessFile, file: &'a File) -> Self {
+        Self {
+            source,
+            file,
+            position: file.range().start,
+            hasher: Some(Hasher::new()),
+        }
+    }
+    ///Moves to offset relative to the start of the range, returns new offset
+    pub fn jump(&mut self, offset: u64) -> u64 {
+        let position = (self.file.range().start + offset).min(self.file.range().end);
+        if position != self.position {
+            self.hasher = None;
+            self.position = position;
+        }
+        self.position - self.file.range().start
+    }
+}
+impl Read for RangedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes_left = self.file.range().end - self.position;
+        if bytes_left == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        let bytes_count = usize::min(bytes_left as usize, buf.len());
+        let read = self.source.read_at(self.position, &mut buf[..bytes_count])?;
+        if read == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        self.position += read as u64;
+        let Some(hasher) = self.hasher.as_mut() else {
+            return Ok(read);
+        };
+        hasher.update(&buf[..read]);
+        if self.position == self.file.range().end && hasher.clone().finalize() != self.file.checksum()
+        {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                AssetError::Corrupted {
+                    path: self.file.path().to_owned(),
+                },
+            ));
+        }
+        Ok(read)
+    }
+}
+enum Inner<'a> {
+    Stored(RangedReader<'a>),
+    Compressed(Decoder<'a, BufReader<RangedReader<'a>>>),
+}
+///Decompresses file while it's being read, so the whole file is never held in memory.
+///Seeking forward skips data, seeking backward starts decompression from the beginning.
+///Files stored without compression are seeked directly
+pub struct AssetReader<'a> {
+    inner: Inner<'a>,
+    source: &'a RandomAccessFile,
+    file: &'a File,
+    dictionary: Option<&'a DecoderDictionary<'static>>,
+    position: u64,
+}
+impl<'a> AssetReader<'a> {
+    pub fn new(
+        source: &'a RandomAccessFile,
+        file: &'a File,
+        dictionary: Option<&'a DecoderDictionary<'static>>,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            inner: Self::inner(source, file, dictionary)?,
+            source,
+            file,
+            dictionary,
+            position: 0,
         })
-        .unwrap();
-        Self { inner: reader, end,pos: start as usize }
+    }
+    fn inner(
+        source: &'a RandomAccessFile,
+        file: &'a File,
+        dictionary: Option<&'a DecoderDictionary<'static>>,
+    ) -> io::Result<Inner<'a>> {
+        let reader = RangedReader::new(source, file);
+        let decoder = match (file.codec(), dictionary) {
+            (Codec::Stored, _) => return Ok(Inner::Stored(reader)),
+            (_, Some(dictionary)) => {
+                Decoder::with_prepared_dictionary(BufReader::new(reader), dictionary)?
+            }
+            (Codec::ZstdLong, None) => {
+                let mut decoder = Decoder::with_buffer(BufReader::new(reader))?;
+                decoder.window_log_max(LONG_WINDOW_LOG)?;
+                decoder
+            }
+            _ => Decoder::with_buffer(BufReader::new(reader))?,
+        };
+        Ok(Inner::Compressed(decoder))
+    }
+    ///size of file after decompression
+    pub fn size(&self) -> u64 {
+        self.file.size()
+    }
+    fn restart(&mut self) -> io::Result<()> {
+        self.inner = Self::inner(self.source, self.file, self.dictionary)?;
+        self.position = 0;
+        Ok(())
     }
 }
-impl std::io::Read for RangedReader<'_> {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let bytes_count = usize::min(self.end as usize - buf.len() - self.pos,buf.len());
-        self.pos = self.inner.;
-        self.inner.read(&mut buf[..bytes_count])?;
-        Ok(bytes_count)
+impl Read for AssetReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = match &mut self.inner {
+            Inner::Stored(reader) => reader.read(buf)?,
+            Inner::Compressed(decoder) => decoder.read(buf)?,
+        };
+        self.position += read as u64;
+        Ok(read)
     }
-}*/
+}
+impl Seek for AssetReader<'_> {
+    ///Seeking past the end stops at the end of file
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.size().checked_add_signed(offset),
+        }
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "seek to a negative position"))?;
+        if let Inner::Stored(reader) = &mut self.inner {
+            self.position = reader.jump(target);
+            return Ok(self.position);
+        }
+        if target < self.position {
+            self.restart()?;
+        }
+        let skip = target - self.position;
+        io::copy(&mut self.by_ref().take(skip), &mut io::sink())?;
+        Ok(self.position)
+    }
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
@@ -1,81 +1,707 @@
 use std::{
+    collections::BTreeMap,
     fs,
-    io::{ copy, BufReader, BufWriter, Result, Seek, Write},
+    io::{self, copy, sink, BufReader, BufWriter, Read, Result, Seek, SeekFrom, Write},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Condvar, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
 };
-use zstd::stream::copy_encode;
+use zstd::Encoder;
 
-use crate::{ext::PathEXT, COMPRESS_LEVEL};
+pub use crate::pack_options::{Compression, DictionaryOptions, PackOptions, COMPRESSED_EXTENSIONS};
+use crate::{
+    cooked_mesh::{cook_obj, COOKED_EXTENSION, MESH_VERSION},
+    ext::PathEXT,
+    pack_cache::{PackCache, SourceInfo},
+    Assets, Codec, FORMAT_VERSION, HEADER_SIZE, MAGIC,
+};
 use byteorder::{WriteBytesExt, BE};
+use crc32fast::Hasher;
 #[derive(Debug, Clone)]
 pub struct File {
     file_source: PathBuf,
     name: String,
+    ///entry is cooked mesh made from obj at file_source
+    cooked: bool,
 }
 impl File {
     pub fn new(name: &str, path: impl AsRef<Path>) -> Self {
         Self {
             name: name.to_owned(),
             file_source: path.as_ref().into(),
+            cooked: false,
+        }
+    }
+    ///Cooked mesh of obj file, it's stored next to obj with .mesh appended to its name
+    pub fn cooked_mesh(obj_name: &str, path: impl AsRef<Path>) -> Self {
+        Self {
+            name: format!("{}.{}", obj_name, COOKED_EXTENSION),
+            file_source: path.as_ref().into(),
+            cooked: true,
         }
     }
 }
 #[derive(Debug, Clone)]
 pub struct Directory {
-    path: String, 
+    path: String,
+    source: PathBuf,
     file_count: usize,
     files: Vec<File>,
 }
+///Window of long distance mode. Readers of ZstdLong entries pass it to window_log_max,
+///so entries written with larger window can't be read
+pub(crate) const LONG_WINDOW_LOG: u32 = 27;
 fn create_file(path: impl AsRef<Path>) -> Result<fs::File> {
     fs::File::create(path)
 }
 fn open_file(path: impl AsRef<Path>) -> Result<fs::File> {
     fs::OpenOptions::new().read(true).open(path)
 }
-pub fn create_data_file_to(assets_folder: impl AsRef<Path>,data_file_folder_path:impl AsRef<Path>) -> Result<()>{
-    let mut data_file = fs::File::create(data_file_folder_path)?;
-    let mut data_file_writer = BufWriter::new(data_file.try_clone()?);
+///What was done while creating data file
+#[derive(Debug, Clone, Default)]
+pub struct PackSummary {
+    ///every file and directory data file was built from
+    pub sources: Vec<PathBuf>,
+    ///count of files that were compressed
+    pub compressed: usize,
+    ///count of unchanged files copied from previous data file
+    pub reused: usize,
+    ///size of dictionary stored in data file, 0 if there is none
+    pub dictionary_size: usize,
+    ///sizes of files grouped by directory they are placed in
+    pub directories: Vec<DirectorySummary>,
+    ///problems that didn't stop packing, like obj files that couldn't be cooked
+    pub warnings: Vec<String>,
+}
+#[derive(Debug, Clone, Default)]
+pub struct DirectorySummary {
+    pub path: String,
+    pub files: usize,
+    ///size of files before compression
+    pub size: u64,
+    pub compressed_size: u64,
+    ///compressed size if files were compressed without dictionary
+    pub plain_size: u64,
+}
+impl DirectorySummary {
+    ///bytes saved by dictionary, negative if it made files larger
+    pub fn saved(&self) -> i64 {
+        self.plain_size as i64 - self.compressed_size as i64
+    }
+}
+impl std::fmt::Display for PackSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} files compressed, {} files reused, dictionary is {} bytes",
+            self.compressed, self.reused, self.dictionary_size
+        )?;
+        for directory in self.directories.iter() {
+            write!(
+                f,
+                "{}/: {} files, {} -> {} bytes",
+                directory.path, directory.files, directory.size, directory.compressed_size,
+            )?;
+            if self.dictionary_size > 0 {
+                write!(f, ", {} bytes saved by dictionary", directory.saved())?;
+            }
+            writeln!(f)?;
+        }
+        for warning in self.warnings.iter() {
+            writeln!(f, "warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+///Reported while data file is being created, entries are reported in the order they are written
+#[derive(Debug, Clone, Copy)]
+pub enum PackProgress<'a> {
+    ///files are listed and compression is about to start
+    Started { files: usize },
+    ///file was compressed or copied from previous data file and written
+    Packed {
+        path: &'a str,
+        ///position of file in data file, starts from 0
+        index: usize,
+        files: usize,
+        size: u64,
+        compressed_size: u64,
+        reused: bool,
+        ///time spent compressing this file
+        elapsed: Duration,
+    },
+    ///everything is written, elapsed is time since start
+    Finished { elapsed: Duration },
+}
+///Compressed entry ready to be written
+struct PackedEntry {
+    compressed_data: Vec<u8>,
+    ///size of uncompressed entry, differs from size of source for cooked meshes
+    size: u64,
+    codec: Codec,
+    source: SourceInfo,
+    reused: bool,
+    warning: Option<String>,
+}
+///Dictionary that is going to be stored in data file
+struct PackDictionary {
+    data: Vec<u8>,
+    max_file_size: u64,
+    ///entries compressed with dictionary of previous data file can't be reused
+    changed: bool,
+}
+///Data file is written next to destination first and renamed when complete,
+///so readers never see partially written file.
+///Files that didn't change since previous data file at the same destination aren't recompressed
+pub fn create_data_file_to(assets_folder: impl AsRef<Path>,data_file_folder_path:impl AsRef<Path>) -> Result<PackSummary>{
+    create_data_file_with_options(assets_folder, data_file_folder_path, &PackOptions::new())
+}
+///Same as create_data_file_to, but compression of every file is chosen by options
+pub fn create_data_file_with_options(
+    assets_folder: impl AsRef<Path>,
+    data_file_path: impl AsRef<Path>,
+    options: &PackOptions,
+) -> Result<PackSummary> {
+    create_data_file_with_progress(assets_folder, data_file_path, options, |_| ())
+}
+///Same as create_data_file_with_options, progress is called on the calling thread
+pub fn create_data_file_with_progress(
+    assets_folder: impl AsRef<Path>,
+    data_file_path: impl AsRef<Path>,
+    options: &PackOptions,
+    mut progress: impl FnMut(PackProgress),
+) -> Result<PackSummary> {
+    let data_file_path = data_file_path.as_ref();
+    let temp_file_path = data_file_path.with_extension(format!("{}.tmp", std::process::id()));
+    let timer = Instant::now();
+    let result = write_data_file(
+        assets_folder.as_ref(),
+        data_file_path,
+        &temp_file_path,
+        options,
+        &mut progress,
+    )
+        .and_then(|(summary, cache)| {
+            fs::rename(&temp_file_path, data_file_path)?;
+            cache.save(data_file_path)?;
+            progress(PackProgress::Finished {
+                elapsed: timer.elapsed(),
+            });
+            Ok(summary)
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_file_path);
+    }
+    result
+}
+fn write_data_file(
+    assets_folder: &Path,
+    data_file_path: &Path,
+    temp_file_path: &Path,
+    options: &PackOptions,
+    progress: &mut dyn FnMut(PackProgress),
+) -> Result<(PackSummary, PackCache)> {
+    let previous = Assets::from_data_file(data_file_path).ok().and_then(|assets| {
+        let cache = PackCache::load(data_file_path, assets.checksum())?;
+        Some((assets, cache))
+    });
+    let mut cache = PackCache::new();
+    let mut summary = PackSummary::default();
+
+    let data_file = fs::File::create(temp_file_path)?;
+    let mut data_file_writer = BufWriter::new(data_file);
 
-    let directories = get_directories(assets_folder.as_ref(), assets_folder.as_ref())?;
-    data_file_writer.write_u64::<BE>(directories.len() as u64)?;
-    for dir in directories.iter() {
-        // TODO: maybe use hashes instead??
-        data_file_writer.write_u64::<BE>(dir.path.chars().count() as u64)?;//writing directory size len
-        data_file_writer.write_all(dir.path.as_bytes())?;//writing directory name
-        data_file_writer.write_u64::<BE>(dir.file_count as u64)?;//writing file count
-        for file in dir.files.iter() {
-            data_file_writer.write_u64::<BE>(file.name.len() as u64)?;//writing file name len
-            data_file_writer.write_all(file.name.as_bytes())?;//writing file name
-            let offset = data_file_writer.stream_position()?+16;// 16 considers writing 2 u64
-            data_file_writer.write_u64::<BE>(offset)?;
-            let mut compressed_data = Vec::new();
-            copy_file_compressed(&mut compressed_data, file)?;
-            data_file_writer.write_u64::<BE>(offset + compressed_data.len() as u64)?;
-            data_file_writer.write(compressed_data.as_slice())?;
+    let directories = get_directories(assets_folder, assets_folder)?;
+    let cooked_meshes = match options.cooks_meshes() {
+        true => cooked_meshes(&directories),
+        false => Vec::new(),
+    };
+    let mut entries = directories
+        .iter()
+        .flat_map(|dir| dir.files.iter().map(move |file| (dir, file)))
+        .chain(cooked_meshes.iter().map(|(dir, file)| (*dir, file)))
+        .map(|(dir, file)| {
+            let path = Path::new(&dir.path).join(&file.name).to_asset_path().unwrap();
+            (path, file)
+        })
+        .collect::<Vec<_>>();
+    //order of read_dir isn't specified, sorting keeps data file the same for the same files
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    progress(PackProgress::Started {
+        files: entries.len(),
+    });
+    summary.sources.extend(directories.iter().map(|dir| dir.source.clone()));
+    summary.sources.extend(
+        entries
+            .iter()
+            .filter(|(_, file)| !file.cooked)
+            .map(|(_, file)| file.file_source.clone()),
+    );
+    let dictionary = match options.dictionary_options() {
+        Some(dictionary_options) => {
+            prepare_dictionary(&entries, options, dictionary_options, previous.as_ref())?
+        }
+        None => None,
+    };
+    let dictionary_data = dictionary.as_ref().map(|d| d.data.as_slice()).unwrap_or_default();
+    summary.dictionary_size = dictionary_data.len();
+    //every entry is path(size+data) + start + end + size + codec + checksum
+    let toc_size = 8 + entries
+        .iter()
+        .map(|(path, _)| 8 + path.len() as u64 + 24 + 1 + 4)
+        .sum::<u64>();
+    let index_size = dictionary_data.len() as u64 + toc_size;
+    //header and table of contents are filled after all files are written, when offsets are known
+    data_file_writer.write_all(&vec![0u8; (HEADER_SIZE + index_size) as usize])?;
+
+    let mut offset = HEADER_SIZE + index_size;
+    let mut data_hasher = Hasher::new();
+    let mut ranges = Vec::with_capacity(entries.len());
+    let mut directory_summaries = BTreeMap::<String, DirectorySummary>::new();
+    let packed = pack_entries(&entries, options, dictionary.as_ref(), previous.as_ref(), |index, entry, elapsed| {
+        let path = &entries[index].0;
+        let compressed_data = entry.compressed_data;
+        data_file_writer.write_all(compressed_data.as_slice())?;
+        data_hasher.update(&compressed_data);
+        let checksum = crc32fast::hash(&compressed_data);
+        ranges.push((
+            offset,
+            offset + compressed_data.len() as u64,
+            entry.size,
+            entry.codec,
+            checksum,
+        ));
+        offset += compressed_data.len() as u64;
+        summary.warnings.extend(entry.warning);
+        if entry.reused {
+            summary.reused += 1;
+        } else {
+            summary.compressed += 1;
         }
+        let directory_path = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default();
+        let directory_summary = directory_summaries
+            .entry(directory_path.to_owned())
+            .or_insert_with(|| DirectorySummary {
+                path: directory_path.to_owned(),
+                ..Default::default()
+            });
+        directory_summary.files += 1;
+        directory_summary.size += entry.size;
+        directory_summary.compressed_size += compressed_data.len() as u64;
+        directory_summary.plain_size += entry.source.plain_size;
+        cache.insert(path.clone(), entry.source);
+        progress(PackProgress::Packed {
+            path,
+            index,
+            files: entries.len(),
+            size: entry.size,
+            compressed_size: compressed_data.len() as u64,
+            reused: entry.reused,
+            elapsed,
+        });
+        Ok(())
+    });
+    packed?;
+    summary.directories = directory_summaries.into_values().collect();
+
+    let mut toc = Vec::with_capacity(toc_size as usize);
+    toc.write_u64::<BE>(entries.len() as u64)?;
+    for ((path, _), (start, end, size, codec, checksum)) in entries.iter().zip(ranges) {
+        toc.write_u64::<BE>(path.len() as u64)?;//writing path len
+        toc.write_all(path.as_bytes())?;//writing path
+        toc.write_u64::<BE>(start)?;
+        toc.write_u64::<BE>(end)?;
+        toc.write_u64::<BE>(size)?;
+        toc.write_u8(codec as u8)?;
+        toc.write_u32::<BE>(checksum)?;
     }
-    data_file.flush().unwrap();
-    Ok(())
+    let mut pack_hasher = Hasher::new();
+    pack_hasher.update(dictionary_data);
+    pack_hasher.update(&toc);
+    let index_checksum = pack_hasher.clone().finalize();
+    pack_hasher.combine(&data_hasher);
+    let checksum = pack_hasher.finalize();
+    cache.set_data_file_checksum(checksum);
+
+    data_file_writer.seek(SeekFrom::Start(0))?;
+    data_file_writer.write_all(&MAGIC)?;
+    data_file_writer.write_u32::<BE>(FORMAT_VERSION)?;
+    data_file_writer.write_u64::<BE>(dictionary_data.len() as u64)?;
+    data_file_writer.write_u64::<BE>(toc_size)?;
+    data_file_writer.write_u32::<BE>(index_checksum)?;
+    data_file_writer.write_u32::<BE>(checksum)?;
+    data_file_writer.write_all(dictionary_data)?;
+    data_file_writer.write_all(&toc)?;
+    data_file_writer.flush()?;
+    data_file_writer.get_ref().sync_all()?;
+    Ok((summary, cache))
+}
+///Compresses entries on a pool of threads, write is called with entries in their original order
+fn pack_entries(
+    entries: &[(String, &File)],
+    options: &PackOptions,
+    dictionary: Option<&PackDictionary>,
+    previous: Option<&(Assets, PackCache)>,
+    write: impl FnMut(usize, PackedEntry, Duration) -> Result<()>,
+) -> Result<()> {
+    let pack = |index: usize| {
+        let (path, file) = &entries[index];
+        pack_entry(path, file, options.compression_for(path), dictionary, previous)
+    };
+    process_in_order(entries.len(), options.thread_count(), pack, write)
+}
+///Runs process for indices up to count on a pool of threads, write is called with results in order of indices.
+///Panic in process is returned as error instead of leaving writer waiting for missing result
+pub(crate) fn process_in_order<T: Send>(
+    count: usize,
+    threads: usize,
+    process: impl Fn(usize) -> Result<T> + Sync,
+    mut write: impl FnMut(usize, T, Duration) -> Result<()>,
+) -> Result<()> {
+    let threads = threads.clamp(1, count.max(1));
+    //entries aren't compressed further ahead of the first unwritten one,
+    //so finished entries don't pile up in memory behind a slow one
+    let look_ahead = threads * 2;
+    let next = AtomicUsize::new(0);
+    //count of written entries, usize::MAX when writing stopped
+    let written = (Mutex::new(0usize), Condvar::new());
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(look_ahead);
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let (next, written, process) = (&next, &written, &process);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= count {
+                    break;
+                }
+                let count = written
+                    .1
+                    .wait_while(written.0.lock().unwrap(), |count| index >= count.saturating_add(look_ahead))
+                    .unwrap();
+                //writing failed, there is no point to continue
+                if *count == usize::MAX {
+                    break;
+                }
+                drop(count);
+                let timer = Instant::now();
+                let entry = panic::catch_unwind(AssertUnwindSafe(|| process(index)))
+                    .unwrap_or_else(|payload| {
+                        let message = payload
+                            .downcast_ref::<&str>()
+                            .copied()
+                            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                            .unwrap_or("unknown panic");
+                        Err(io::Error::other(format!("entry {} panicked: {}", index, message)))
+                    })
+                    .map(|entry| (entry, timer.elapsed()));
+                if sender.send((index, entry)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        //entries finished out of order wait here until everything before them is written
+        let mut finished = BTreeMap::new();
+        let mut write_all = || -> Result<()> {
+            for index in 0..count {
+                let (entry, elapsed) = loop {
+                    if let Some(entry) = finished.remove(&index) {
+                        break entry?;
+                    }
+                    let (finished_index, entry) = receiver
+                        .recv()
+                        .map_err(|_| io::Error::other("packing thread stopped"))?;
+                    debug_assert!(finished_index < index + look_ahead);
+                    finished.insert(finished_index, entry);
+                };
+                write(index, entry, elapsed)?;
+                *written.0.lock().unwrap() = index + 1;
+                written.1.notify_all();
+            }
+            Ok(())
+        };
+        let result = write_all();
+        //wakes up threads waiting for their turn after an error
+        *written.0.lock().unwrap() = usize::MAX;
+        written.1.notify_all();
+        result
+    })
+}
+///Returns info about source if it didn't change since previous data file
+///and previous entry was written with the same compression and version of cooked mesh
+fn unchanged_source(
+    path: &str,
+    file: &File,
+    compression: Compression,
+    cache: &PackCache,
+) -> Result<Option<SourceInfo>> {
+    let cook_version = if file.cooked { MESH_VERSION } else { 0 };
+    let Some(&info) = cache
+        .get(path)
+        .filter(|info| info.compression == compression.id() && info.cook_version == cook_version)
+    else {
+        return Ok(None);
+    };
+    let (size, modified) = SourceInfo::stat(&file.file_source)?;
+    //modification time alone is enough to trust the cache, touched files are hashed
+    let unchanged = info.size == size
+        && (info.modified == modified || hash_file(&file.file_source)? == info.hash);
+    Ok(unchanged.then_some(SourceInfo { modified, ..info }))
+}
+///Dictionary of previous data file is kept if none of small files changed, otherwise new one is trained.
+///Returns None if there are too few small files to train on.
+///Only files compressed with plain zstd use dictionary
+fn prepare_dictionary(
+    entries: &[(String, &File)],
+    pack_options: &PackOptions,
+    options: DictionaryOptions,
+    previous: Option<&(Assets, PackCache)>,
+) -> Result<Option<PackDictionary>> {
+    let mut small_entries = Vec::new();
+    for (path, file) in entries.iter() {
+        let compression = pack_options.compression_for(path);
+        if matches!(compression, Compression::Zstd(_))
+            && !file.cooked
+            && fs::metadata(&file.file_source)?.len() <= options.max_file_size
+        {
+            small_entries.push((path, file, compression));
+        }
+    }
+    if let Some((assets, cache)) = previous {
+        if let Some(dictionary) = assets.dictionary() {
+            let previous_count = assets
+                .files()
+                .iter()
+                .filter(|file| file.codec() == Codec::ZstdDictionary)
+                .count();
+            let mut unchanged = previous_count == small_entries.len();
+            for (path, file, compression) in small_entries.iter() {
+                if !unchanged {
+                    break;
+                }
+                unchanged = assets
+                    .find_file(path)
+                    .is_ok_and(|old_file| old_file.codec() == Codec::ZstdDictionary)
+                    && unchanged_source(path, file, *compression, cache)?.is_some();
+            }
+            if unchanged {
+                return Ok(Some(PackDictionary {
+                    data: dictionary.data().to_vec(),
+                    max_file_size: options.max_file_size,
+                    changed: false,
+                }));
+            }
+        }
+    }
+    let samples = small_entries
+        .iter()
+        .map(|(_, file, _)| fs::read(&file.file_source))
+        .collect::<Result<Vec<_>>>()?;
+    //training fails when there are not enough samples, files are compressed without dictionary then
+    let Ok(data) = zstd::dict::from_samples(&samples, options.max_dictionary_size) else {
+        return Ok(None);
+    };
+    let changed = previous
+        .and_then(|(assets, _)| assets.dictionary())
+        .map_or(true, |dictionary| dictionary.data() != data.as_slice());
+    Ok(Some(PackDictionary {
+        data,
+        max_file_size: options.max_file_size,
+        changed,
+    }))
+}
+///Copies compressed data from previous data file if source is unchanged, otherwise compresses file
+fn pack_entry(
+    path: &str,
+    file: &File,
+    compression: Compression,
+    dictionary: Option<&PackDictionary>,
+    previous: Option<&(Assets, PackCache)>,
+) -> Result<PackedEntry> {
+    let dictionary = dictionary
+        .filter(|_| matches!(compression, Compression::Zstd(_)) && !file.cooked)
+        .filter(|dictionary| {
+            fs::metadata(&file.file_source).is_ok_and(|m| m.len() <= dictionary.max_file_size)
+        });
+    let codec = match (compression, dictionary) {
+        (Compression::Stored, _) => Codec::Stored,
+        (Compression::ZstdLong(_), _) => Codec::ZstdLong,
+        (Compression::Zstd(_), Some(_)) => Codec::ZstdDictionary,
+        (Compression::Zstd(_), None) => Codec::Zstd,
+    };
+    if let Some((assets, cache)) = previous {
+        let old_file = assets
+            .find_file(path)
+            .ok()
+            .filter(|old_file| old_file.codec() == codec)
+            .filter(|_| dictionary.map_or(true, |dictionary| !dictionary.changed));
+        if let Some(old_file) = old_file {
+            if let Some(source) = unchanged_source(path, file, compression, cache)? {
+                if let Ok(compressed_data) = assets.read_compressed(old_file) {
+                    return Ok(PackedEntry {
+                        compressed_data,
+                        size: old_file.size(),
+                        codec,
+                        source,
+                        reused: true,
+                        warning: None,
+                    });
+                }
+            }
+        }
+    }
+    let (_, modified) = SourceInfo::stat(&file.file_source)?;
+    let mut compressed_data = Vec::new();
+    let dictionary = dictionary.map(|dictionary| dictionary.data.as_slice());
+    let mut warning = None;
+    let mut cook_version = 0;
+    //cache knows cooked entry by its obj, so it's recooked only when obj or MESH_VERSION changes
+    let (size, source_size, hash) = if file.cooked {
+        let obj = fs::read(&file.file_source)?;
+        let cooked = std::str::from_utf8(&obj)
+            .map_err(|error| error.to_string())
+            .and_then(|source| cook_obj(source).map_err(|error| error.to_string()));
+        //plain obj isn't a cooked mesh, so loader falls back to parsing the obj and reports its error.
+        //Without cook version it's tried again next time
+        let cooked = match cooked {
+            Ok(cooked) => {
+                cook_version = MESH_VERSION;
+                cooked
+            }
+            Err(error) => {
+                warning = Some(format!("can't cook {}, obj is packed instead: {}", path, error));
+                obj.clone()
+            }
+        };
+        let (size, _) = copy_compressed(&mut compressed_data, cooked.as_slice(), compression, dictionary)?;
+        (size, obj.len() as u64, crc32fast::hash(&obj))
+    } else {
+        let opened_file = BufReader::new(std::fs::File::open(&file.file_source)?);
+        let (size, hash) = copy_compressed(&mut compressed_data, opened_file, compression, dictionary)?;
+        (size, size, hash)
+    };
+    //files using dictionary are small, so they are compressed again to know what dictionary saved
+    let plain_size = match dictionary {
+        Some(_) => {
+            let mut plain = Vec::new();
+            let opened_file = BufReader::new(std::fs::File::open(&file.file_source)?);
+            copy_compressed(&mut plain, opened_file, compression, None)?;
+            plain.len()
+        }
+        None => compressed_data.len(),
+    };
+    Ok(PackedEntry {
+        compressed_data,
+        size,
+        codec,
+        source: SourceInfo {
+            size: source_size,
+            modified,
+            hash,
+            compression: compression.id(),
+            cook_version,
+            plain_size: plain_size as u64,
+        },
+        reused: false,
+        warning,
+    })
+}
+///Copies data file, destination is replaced only when copy is complete
+pub fn copy_data_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> Result<()> {
+    let to = to.as_ref();
+    let temp_file_path = to.with_extension(format!("{}.tmp", std::process::id()));
+    let result = fs::copy(from, &temp_file_path).and_then(|_| fs::rename(&temp_file_path, to));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_file_path);
+    }
+    result
 }
 ///creates asset file
-pub fn create_data_file(assets_folder: impl AsRef<Path>) -> Result<()> {
+pub fn create_data_file(assets_folder: impl AsRef<Path>) -> Result<PackSummary> {
     let data_file_path = &assets_folder.as_ref().parent().unwrap().join("assets.data");
     create_data_file_to(assets_folder, data_file_path)
 }
 ///not in use
 fn copy_file_to(mut destination: impl Write, file: &File) -> Result<()> {
     let mut opened_file = BufReader::new(std::fs::File::open(&file.file_source)?);
-    let timer = std::time::Instant::now();
-    println!("{} bytes copied", copy(&mut opened_file, &mut destination)?);
-    println!("writing taken {} secs", timer.elapsed().as_secs_f32());
+    copy(&mut opened_file, &mut destination)?;
     Ok(())
 }
-fn copy_file_compressed(mut destination: &mut impl Write, file: &File) -> Result<()> {
-    let mut opened_file = BufReader::new(std::fs::File::open(&file.file_source)?);
-    let timer = std::time::Instant::now();
-    copy_encode(&mut opened_file, &mut destination, COMPRESS_LEVEL)?;
-    println!("writing taken {} secs", timer.elapsed().as_secs_f32());
-    Ok(())
+///returns size and CRC32 of uncompressed data
+fn copy_compressed(
+    destination: &mut impl Write,
+    source: impl Read,
+    compression: Compression,
+    dictionary: Option<&[u8]>,
+) -> Result<(u64, u32)> {
+    let mut opened_file = HashingReader::new(source);
+    let mut encoder = match (compression, dictionary) {
+        (Compression::Stored, _) => {
+            let size = copy(&mut opened_file, destination)?;
+            return Ok((size, opened_file.hasher.finalize()));
+        }
+        (Compression::Zstd(level), Some(dictionary)) => {
+            Encoder::with_dictionary(destination, level, dictionary)?
+        }
+        (Compression::Zstd(level), None) => Encoder::new(destination, level)?,
+        (Compression::ZstdLong(level), _) => {
+            let mut encoder = Encoder::new(destination, level)?;
+            encoder.long_distance_matching(true)?;
+            encoder.window_log(LONG_WINDOW_LOG)?;
+            encoder
+        }
+    };
+    let size = copy(&mut opened_file, &mut encoder)?;
+    encoder.finish()?;
+    Ok((size, opened_file.hasher.finalize()))
+}
+fn hash_file(path: impl AsRef<Path>) -> Result<u32> {
+    let mut opened_file = HashingReader::new(BufReader::new(std::fs::File::open(path)?));
+    copy(&mut opened_file, &mut sink())?;
+    Ok(opened_file.hasher.finalize())
+}
+///Computes CRC32 of everything read through it
+struct HashingReader<R: Read> {
+    inner: R,
+    hasher: Hasher,
+}
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Hasher::new(),
+        }
+    }
+}
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
+///Cooked mesh for every .obj file, unless there is a file with the name of cooked mesh already
+fn cooked_meshes(directories: &[Directory]) -> Vec<(&Directory, File)> {
+    directories
+        .iter()
+        .flat_map(|dir| {
+            dir.files
+                .iter()
+                .filter(|file| {
+                    let extension = file.name.rsplit_once('.').map(|(_, extension)| extension);
+                    extension.is_some_and(|extension| extension.eq_ignore_ascii_case("obj"))
+                })
+                .map(|file| File::cooked_mesh(&file.name, &file.file_source))
+                .filter(|cooked| dir.files.iter().all(|file| file.name != cooked.name))
+                .map(move |cooked| (dir, cooked))
+        })
+        .collect()
 }
 ///recursevely handle every dictionary,creates vec of directories and files
 fn get_directories(
@@ -94,6 +720,7 @@ fn get_directories(
         }
     }
     value.push(Directory {
+        source: path.as_ref().into(),
         file_count: files.len(),
         files,
         path: path
@@ -0,0 +1,260 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+    ops::Deref,
+    path::Path,
+    rc::{Rc, Weak},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ext::PathEXT,
+    importer::{import_failed, ErasedAsyncImporter},
+    loading::Workers,
+    AssetError, AsyncHandle, AsyncImporter, Importer, Importers, LoadState, Vfs,
+};
+
+///Shared reference to loaded asset, asset is freed when the last handle is dropped
+pub struct Handle<T> {
+    asset: Rc<T>,
+    path: Rc<str>,
+}
+impl<T> Handle<T> {
+    ///path asset was loaded from
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    ///count of handles to this asset
+    pub fn count(&self) -> usize {
+        Rc::strong_count(&self.asset)
+    }
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.asset, &other.asset)
+    }
+}
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            asset: self.asset.clone(),
+            path: self.path.clone(),
+        }
+    }
+}
+impl<T> Deref for Handle<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.asset
+    }
+}
+impl<T: Debug> Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handle")
+            .field("path", &self.path)
+            .field("asset", &self.asset)
+            .finish()
+    }
+}
+struct CacheEntry {
+    asset: Weak<dyn Any>,
+    path: Rc<str>,
+}
+///Load decoded by worker thread and waiting for finish on main thread
+struct Upload {
+    path: Rc<str>,
+    importer: Arc<dyn ErasedAsyncImporter>,
+    complete: Box<dyn FnOnce(&mut AssetServer, Result<Box<dyn Any>, AssetError>)>,
+}
+///Loads assets from Vfs with registered importers and caches them by path and type.
+///The same asset is loaded once while there are handles to it
+pub struct AssetServer {
+    source: Arc<Vfs>,
+    importers: Importers,
+    cache: HashMap<(TypeId, String), CacheEntry>,
+    worker_count: usize,
+    workers: Option<Workers>,
+    uploads: HashMap<u64, Upload>,
+    ///AsyncHandle of every asset that is being loaded
+    loading: HashMap<(TypeId, String), Box<dyn Any>>,
+    next_load: u64,
+}
+impl AssetServer {
+    ///server with default importers, see Importers::with_defaults
+    pub fn new(source: Vfs) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        Self {
+            source: Arc::new(source),
+            importers: Importers::with_defaults(),
+            cache: HashMap::new(),
+            worker_count: threads.saturating_sub(1).max(1),
+            workers: None,
+            uploads: HashMap::new(),
+            loading: HashMap::new(),
+            next_load: 0,
+        }
+    }
+    ///count of threads used by load_async, by default one less than count of cores.
+    ///Threads are started on first load_async
+    pub fn worker_threads(mut self, count: usize) -> Self {
+        self.worker_count = count.max(1);
+        self
+    }
+    pub fn source(&self) -> &Vfs {
+        &self.source
+    }
+    ///Adds importer, it replaces importers of the same type and extension
+    pub fn register<I: Importer>(&mut self, importer: I) -> &mut Self {
+        self.importers.register(importer);
+        self
+    }
+    ///Adds importer that can be used by load_async
+    pub fn register_async<I: AsyncImporter>(&mut self, importer: I) -> &mut Self {
+        self.importers.register_async(importer);
+        self
+    }
+    pub fn importers(&self) -> &Importers {
+        &self.importers
+    }
+    pub fn importers_mut(&mut self) -> &mut Importers {
+        &mut self.importers
+    }
+    ///Imports asset without caching it, for assets that are modified after loading
+    pub fn import<T: 'static, P: AsRef<Path>>(&self, path: P) -> Result<T, AssetError> {
+        let path = path.as_ref().to_asset_path().ok_or(AssetError::FileFindingError)?;
+        self.importers.import(&self.source, &path)
+    }
+    ///Returns cached asset or loads it
+    pub fn load<T: 'static, P: AsRef<Path>>(&mut self, path: P) -> Result<Handle<T>, AssetError> {
+        let path = path.as_ref().to_asset_path().ok_or(AssetError::FileFindingError)?;
+        if let Some(handle) = self.get(&path) {
+            return Ok(handle);
+        }
+        self.insert(path)
+    }
+    ///Loads asset again even if it's cached, following loads return new asset.
+    ///Existing handles keep the old one.
+    ///Cache is left untouched if loading fails
+    pub fn reload<T: 'static, P: AsRef<Path>>(&mut self, path: P) -> Result<Handle<T>, AssetError> {
+        let path = path.as_ref().to_asset_path().ok_or(AssetError::FileFindingError)?;
+        self.insert(path)
+    }
+    fn insert<T: 'static>(&mut self, path: String) -> Result<Handle<T>, AssetError> {
+        let asset = self.importers.import::<T>(&self.source, &path)?;
+        Ok(self.cache_asset(asset, path))
+    }
+    fn cache_asset<T: 'static>(&mut self, asset: T, path: String) -> Handle<T> {
+        self.remove_unused();
+        let asset = Rc::new(asset);
+        let path: Rc<str> = path.into();
+        let weak: Weak<dyn Any> = Rc::downgrade(&asset) as Weak<dyn Any>;
+        self.cache.insert(
+            (TypeId::of::<T>(), path.to_string()),
+            CacheEntry {
+                asset: weak,
+                path: path.clone(),
+            },
+        );
+        Handle { asset, path }
+    }
+    ///Starts loading asset on worker threads, it becomes ready after finish_loads.
+    ///Cached asset is returned as ready, asset that is already loading isn't loaded twice
+    pub fn load_async<T: 'static, P: AsRef<Path>>(&mut self, path: P) -> AsyncHandle<T> {
+        let Some(path) = path.as_ref().to_asset_path() else {
+            let path = path.as_ref().to_string_lossy();
+            return AsyncHandle::new(LoadState::Failed(Rc::new(AssetError::FileFindingError)), path.into());
+        };
+        if let Some(handle) = self.get::<T, _>(&path) {
+            let path = handle.path.clone();
+            return AsyncHandle::new(LoadState::Ready(handle), path);
+        }
+        let key = (TypeId::of::<T>(), path);
+        if let Some(handle) = self.loading.get(&key).and_then(|handle| handle.downcast_ref::<AsyncHandle<T>>()) {
+            return handle.clone();
+        }
+        let path: Rc<str> = key.1.as_str().into();
+        let importer = match self.importers.find_async::<T>(&path) {
+            Ok(importer) => importer,
+            Err(error) => return AsyncHandle::new(LoadState::Failed(Rc::new(error)), path),
+        };
+        let id = self.next_load;
+        self.next_load += 1;
+        let (worker_count, source) = (self.worker_count, &self.source);
+        self.workers
+            .get_or_insert_with(|| Workers::new(worker_count, source.clone()))
+            .send(id, &path, importer.clone());
+        let handle = AsyncHandle::new(LoadState::Loading, path.clone());
+        self.loading.insert(key.clone(), Box::new(handle.clone()));
+        let pending = handle.clone();
+        let complete = move |server: &mut AssetServer, result: Result<Box<dyn Any>, AssetError>| {
+            server.loading.remove(&key);
+            pending.set(match result {
+                Ok(asset) => {
+                    let asset = *asset.downcast::<T>().expect("importer returned asset of another type");
+                    LoadState::Ready(server.cache_asset(asset, key.1))
+                }
+                Err(error) => LoadState::Failed(Rc::new(error)),
+            });
+        };
+        self.uploads.insert(
+            id,
+            Upload {
+                path,
+                importer,
+                complete: Box::new(complete),
+            },
+        );
+        handle
+    }
+    ///Finishes loads decoded by worker threads, GL objects are created here,
+    ///so it must be called on the thread with GL context.
+    ///Stops when budget is spent, but finishes at least one load if some is decoded.
+    ///Returns count of finished loads
+    pub fn finish_loads(&mut self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut finished = 0;
+        while finished == 0 || start.elapsed() < budget {
+            let Some((id, decoded)) = self.workers.as_ref().and_then(Workers::try_recv) else {
+                break;
+            };
+            let Some(upload) = self.uploads.remove(&id) else {
+                continue;
+            };
+            let result = decoded.and_then(|decoded| {
+                upload
+                    .importer
+                    .finish(decoded)
+                    .map_err(|error| import_failed(&upload.path, upload.importer.name(), error))
+            });
+            (upload.complete)(self, result);
+            finished += 1;
+        }
+        finished
+    }
+    ///count of loads started by load_async that aren't finished yet
+    pub fn pending_loads(&self) -> usize {
+        self.uploads.len()
+    }
+    ///Returns asset if it's loaded and somebody holds a handle to it
+    pub fn get<T: 'static, P: AsRef<Path>>(&self, path: P) -> Option<Handle<T>> {
+        let path = path.as_ref().to_asset_path()?;
+        let entry = self.cache.get(&(TypeId::of::<T>(), path))?;
+        let asset = entry.asset.upgrade()?.downcast::<T>().ok()?;
+        Some(Handle {
+            asset,
+            path: entry.path.clone(),
+        })
+    }
+    ///count of assets that are still in use
+    pub fn loaded(&self) -> usize {
+        self.cache
+            .values()
+            .filter(|entry| entry.asset.strong_count() > 0)
+            .count()
+    }
+    ///Forgets assets whose handles were all dropped, it's also done on every load
+    pub fn remove_unused(&mut self) {
+        self.cache.retain(|_, entry| entry.asset.strong_count() > 0);
+    }
+}
@@ -0,0 +1,88 @@
+use graphics::objects::shader::{
+    preprocessor::{Preprocessor, ShaderSource},
+    ShaderType, SubShader,
+};
+use rendering::shader_sources::embedded_shader;
+
+use crate::{AssetError, AsyncImporter, ImportContext, ImportError, ImportSettings, ShaderSettings, Vfs};
+
+const SHADER_EXTENSIONS: &[&str] = &["glsl", "vert", "frag", "comp", "geom"];
+///Stage from extension ("light.frag") or end of name ("light_frag.glsl")
+pub fn shader_stage(path: &str) -> Option<ShaderType> {
+    let name = path.rsplit('/').next()?;
+    let (stem, extension) = name.rsplit_once('.')?;
+    let suffix = match extension.to_lowercase().as_str() {
+        "glsl" => stem.rsplit_once('_')?.1.to_lowercase(),
+        extension => extension.to_owned(),
+    };
+    Some(match suffix.as_str() {
+        "vert" => ShaderType::Vertex,
+        "frag" => ShaderType::Fragment,
+        "comp" => ShaderType::Compute,
+        "geom" => ShaderType::Geometry,
+        _ => return None,
+    })
+}
+///Included files are read from Vfs, shaders embedded into rendering crate are used if Vfs has no such file
+fn read_include(source: &Vfs, path: &str) -> Result<String, String> {
+    match source.open_file_string(path) {
+        Err(AssetError::FileFindingError) => embedded_shader(path)
+            .map(str::to_owned)
+            .ok_or_else(|| AssetError::FileFindingError.to_string()),
+        result => result.map_err(|error| error.to_string()),
+    }
+}
+///Shader with resolved includes and defines from meta file
+fn preprocess(context: &ImportContext, settings: &ShaderSettings) -> Result<ShaderSource, ImportError> {
+    let source = context.read_string()?;
+    let preprocessor = Preprocessor::new(|path| read_include(context.source(), path)).defines(&settings.defines);
+    Ok(preprocessor.process_source(context.path(), &source)?)
+}
+///Preprocesses shader at path with defines from its meta file. Shader itself is read like its includes,
+///so files in Vfs replace shaders embedded into rendering crate
+pub fn preprocess_shader(source: &Vfs, path: &str) -> Result<ShaderSource, String> {
+    let settings = ImportSettings::load(source, path).map_err(|error| error.to_string())?.shader;
+    Preprocessor::new(|path| read_include(source, path)).defines(&settings.defines).process(path)
+}
+///Preprocessed shader sources, nothing is compiled
+pub struct ShaderSourceImporter;
+impl AsyncImporter for ShaderSourceImporter {
+    type Decoded = ShaderSource;
+    type Output = ShaderSource;
+    fn name(&self) -> &'static str {
+        "shader source"
+    }
+    fn extensions(&self) -> &[&str] {
+        SHADER_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<ShaderSource, ImportError> {
+        preprocess(context, &context.settings()?.shader)
+    }
+    fn finish(&self, decoded: ShaderSource) -> Result<ShaderSource, ImportError> {
+        Ok(decoded)
+    }
+}
+///Compiled shaders, stage is taken from meta file or from path, see shader_stage.
+///Compile errors point to lines of included files
+pub struct SubShaderImporter;
+impl AsyncImporter for SubShaderImporter {
+    type Decoded = (ShaderSource, ShaderType);
+    type Output = SubShader;
+    fn name(&self) -> &'static str {
+        "shader"
+    }
+    fn extensions(&self) -> &[&str] {
+        SHADER_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<(ShaderSource, ShaderType), ImportError> {
+        let settings = context.settings()?.shader;
+        let stage = match settings.stage {
+            Some(stage) => stage.into(),
+            None => shader_stage(context.path()).ok_or("can't tell shader stage from path, set it in meta file")?,
+        };
+        Ok((preprocess(context, &settings)?, stage))
+    }
+    fn finish(&self, (source, stage): (ShaderSource, ShaderType)) -> Result<SubShader, ImportError> {
+        Ok(source.try_compile(stage)?)
+    }
+}
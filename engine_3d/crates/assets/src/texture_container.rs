@@ -0,0 +1,488 @@
+use std::{io::Read, ops::Range};
+
+use byteorder::{ByteOrder, LE};
+use graphics::objects::texture::{
+    texture::Texture,
+    texture_trait::TextureTrait,
+    texture_type::{CubeMapTexture, TextureArray2D},
+    MipLevel, Texture2D, Texture2DBuilder, TextureFormat,
+};
+
+use crate::{AsyncImporter, ImportContext, ImportError, TextureSettings};
+
+pub const KTX2_MAGIC: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+pub const DDS_MAGIC: [u8; 4] = *b"DDS ";
+///Texture from KTX2 or DDS file with its mip chain, nothing is decoded or converted
+///except BGRA pixels which are swizzled to RGBA.
+///Rows are stored as in file, so textures should be exported with bottom left origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureContainer {
+    pub format: TextureFormat,
+    ///1 if texture isn't an array
+    pub layers: u32,
+    ///6 for cube maps, 1 otherwise
+    pub faces: u32,
+    pub array: bool,
+    ///first level is the largest, every level has images of every layer and face in that order
+    pub levels: Vec<MipLevel>,
+}
+impl TextureContainer {
+    ///Parses KTX2 or DDS file, format is detected from its first bytes
+    pub fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        if data.starts_with(&KTX2_MAGIC) {
+            parse_ktx2(data)
+        } else if data.starts_with(&DDS_MAGIC) {
+            parse_dds(data)
+        } else {
+            Err("not a KTX2 or DDS file".into())
+        }
+    }
+    pub fn width(&self) -> u32 {
+        self.levels[0].width
+    }
+    pub fn height(&self) -> u32 {
+        self.levels[0].height
+    }
+    pub fn is_cube_map(&self) -> bool {
+        self.faces == 6
+    }
+    ///image of single layer and face
+    pub fn image(&self, level: usize, layer: u32, face: u32) -> &[u8] {
+        let level = &self.levels[level];
+        let size = level.data.len() / (self.layers as usize * self.faces as usize);
+        let start = (layer as usize * self.faces as usize + face as usize) * size;
+        &level.data[start..start + size]
+    }
+    ///Mipmaps are generated for uncompressed textures without mip chain
+    pub fn generates_mipmaps(&self, settings: &TextureSettings) -> bool {
+        settings.mipmaps && self.levels.len() == 1 && !self.format.is_compressed()
+    }
+    ///Drops largest levels until texture fits into max_size, at least one level is kept
+    pub fn limit_size(&mut self, max_size: u32) {
+        let larger = self
+            .levels
+            .iter()
+            .take_while(|level| level.width.max(level.height) > max_size)
+            .count();
+        self.levels.drain(..larger.min(self.levels.len() - 1));
+    }
+    ///Checks that every level has size expected from format and its dimensions
+    fn validate(self) -> Result<Self, ImportError> {
+        let base = self.levels.first().ok_or("texture has no mip levels")?;
+        if base.width == 0 || base.height == 0 {
+            return Err("texture is empty".into());
+        }
+        if self.is_cube_map() && base.width != base.height {
+            return Err("faces of cube map aren't square".into());
+        }
+        if self.levels.len() > 32 - base.width.max(base.height).leading_zeros() as usize {
+            return Err(format!("{} mip levels are too many for {}x{}", self.levels.len(), base.width, base.height).into());
+        }
+        let images = image_count(self.layers, self.faces)?;
+        for (i, level) in self.levels.iter().enumerate() {
+            if (level.width, level.height) != ((base.width >> i).max(1), (base.height >> i).max(1)) {
+                return Err(format!("mip level {} has wrong size {}x{}", i, level.width, level.height).into());
+            }
+            let expected = level_size(self.format, level.width, level.height, images)?;
+            if level.data.len() != expected {
+                return Err(format!("mip level {} is {} bytes, expected {}", i, level.data.len(), expected).into());
+            }
+        }
+        Ok(self)
+    }
+}
+fn image_size(format: TextureFormat, width: u32, height: u32) -> Result<usize, ImportError> {
+    match format.image_size(width, height) {
+        Some(size) => Ok(size),
+        None if format.image_size(1, 1).is_some() => Err(format!("{}x{} texture is too large", width, height).into()),
+        None => Err(format!("{:?} can't be stored in texture container", format).into()),
+    }
+}
+///Count of images in every mip level, layers and faces come from file header
+fn image_count(layers: u32, faces: u32) -> Result<usize, ImportError> {
+    layers
+        .checked_mul(faces)
+        .map(|count| count as usize)
+        .ok_or_else(|| format!("{} layers of {} faces are too many", layers, faces).into())
+}
+///Size of mip level with images of every layer and face
+fn level_size(format: TextureFormat, width: u32, height: u32, images: usize) -> Result<usize, ImportError> {
+    image_size(format, width, height)?
+        .checked_mul(images)
+        .ok_or_else(|| format!("{}x{} texture with {} images is too large", width, height, images).into())
+}
+fn range(data: &[u8], offset: u64, length: u64) -> Result<Range<usize>, ImportError> {
+    let end = offset.checked_add(length).filter(|end| *end <= data.len() as u64);
+    end.map(|end| offset as usize..end as usize)
+        .ok_or_else(|| "texture data is outside of file".into())
+}
+///BGRA pixels are stored as RGBA
+fn swizzle_bgra(levels: &mut [MipLevel]) {
+    for level in levels {
+        for pixel in level.data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+}
+///Format from VkFormat of KTX2, second value is true for BGRA formats
+fn vk_format(vk_format: u32) -> Option<(TextureFormat, bool)> {
+    Some(match vk_format {
+        9 => (TextureFormat::R8, false),
+        16 => (TextureFormat::RG8, false),
+        37 => (TextureFormat::RGBA8, false),
+        43 => (TextureFormat::SrgbA, false),
+        44 => (TextureFormat::RGBA8, true),
+        50 => (TextureFormat::SrgbA, true),
+        97 => (TextureFormat::RGBA16F, false),
+        109 => (TextureFormat::RGBA32F, false),
+        131 | 133 => (TextureFormat::Bc1, false),
+        132 | 134 => (TextureFormat::Bc1Srgb, false),
+        135 => (TextureFormat::Bc2, false),
+        136 => (TextureFormat::Bc2Srgb, false),
+        137 => (TextureFormat::Bc3, false),
+        138 => (TextureFormat::Bc3Srgb, false),
+        139 => (TextureFormat::Bc4, false),
+        141 => (TextureFormat::Bc5, false),
+        143 => (TextureFormat::Bc6HUFloat, false),
+        145 => (TextureFormat::Bc7, false),
+        146 => (TextureFormat::Bc7Srgb, false),
+        _ => return None,
+    })
+}
+const KTX2_HEADER_SIZE: usize = 80;
+const KTX2_SUPERCOMPRESSION_ZSTD: u32 = 2;
+///KTX2 without supercompression or with zstd, 3d textures aren't supported
+pub fn parse_ktx2(data: &[u8]) -> Result<TextureContainer, ImportError> {
+    if !data.starts_with(&KTX2_MAGIC) {
+        return Err("not a KTX2 file".into());
+    }
+    if data.len() < KTX2_HEADER_SIZE {
+        return Err("KTX2 header is truncated".into());
+    }
+    let u32_at = |offset: usize| LE::read_u32(&data[offset..]);
+    let [format, _type_size, width, height, depth, layers, faces, level_count, supercompression] =
+        std::array::from_fn(|i| u32_at(12 + i * 4));
+    let (format, bgra) = vk_format(format).ok_or_else(|| format!("VkFormat {} isn't supported", format))?;
+    if depth > 0 {
+        return Err("3d textures aren't supported".into());
+    }
+    if faces != 1 && faces != 6 {
+        return Err(format!("{} faces, expected 1 or 6", faces).into());
+    }
+    if supercompression != 0 && supercompression != KTX2_SUPERCOMPRESSION_ZSTD {
+        return Err(format!("supercompression scheme {} isn't supported", supercompression).into());
+    }
+    //0 means that mipmaps should be generated after loading
+    let level_count = level_count.max(1) as usize;
+    let index_end = KTX2_HEADER_SIZE + level_count * 24;
+    if level_count > 32 || data.len() < index_end {
+        return Err("KTX2 level index is truncated".into());
+    }
+    let images = image_count(layers.max(1), faces)?;
+    let mut levels = Vec::with_capacity(level_count);
+    for i in 0..level_count {
+        let entry = &data[KTX2_HEADER_SIZE + i * 24..];
+        let (offset, length, uncompressed_length) =
+            (LE::read_u64(entry), LE::read_u64(&entry[8..]), LE::read_u64(&entry[16..]));
+        let (width, height) = ((width >> i).max(1), (height >> i).max(1));
+        let expected = level_size(format, width, height, images)?;
+        if uncompressed_length != expected as u64 {
+            return Err(format!("mip level {} is {} bytes, expected {}", i, uncompressed_length, expected).into());
+        }
+        let compressed = &data[range(data, offset, length)?];
+        let level_data = match supercompression {
+            KTX2_SUPERCOMPRESSION_ZSTD => {
+                //buffer grows with decompressed data, so corrupt level can't make it allocate too much
+                let mut level_data = Vec::new();
+                zstd::stream::read::Decoder::with_buffer(compressed)?
+                    .take(uncompressed_length)
+                    .read_to_end(&mut level_data)?;
+                level_data
+            }
+            _ => compressed.to_vec(),
+        };
+        levels.push(MipLevel {
+            width,
+            height,
+            data: level_data,
+        });
+    }
+    if bgra {
+        swizzle_bgra(&mut levels);
+    }
+    TextureContainer {
+        format,
+        layers: layers.max(1),
+        faces,
+        array: layers > 0,
+        levels,
+    }
+    .validate()
+}
+///Format from DXGI_FORMAT of DX10 header, second value is true for BGRA formats
+fn dxgi_format(dxgi_format: u32) -> Option<(TextureFormat, bool)> {
+    Some(match dxgi_format {
+        2 => (TextureFormat::RGBA32F, false),
+        10 => (TextureFormat::RGBA16F, false),
+        28 => (TextureFormat::RGBA8, false),
+        29 => (TextureFormat::SrgbA, false),
+        49 => (TextureFormat::RG8, false),
+        61 => (TextureFormat::R8, false),
+        71 => (TextureFormat::Bc1, false),
+        72 => (TextureFormat::Bc1Srgb, false),
+        74 => (TextureFormat::Bc2, false),
+        75 => (TextureFormat::Bc2Srgb, false),
+        77 => (TextureFormat::Bc3, false),
+        78 => (TextureFormat::Bc3Srgb, false),
+        80 => (TextureFormat::Bc4, false),
+        83 => (TextureFormat::Bc5, false),
+        87 => (TextureFormat::RGBA8, true),
+        91 => (TextureFormat::SrgbA, true),
+        95 => (TextureFormat::Bc6HUFloat, false),
+        98 => (TextureFormat::Bc7, false),
+        99 => (TextureFormat::Bc7Srgb, false),
+        _ => return None,
+    })
+}
+const DDS_HEADER_SIZE: usize = 4 + 124;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+const DDS_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+///DDS with legacy or DX10 header, volume textures aren't supported
+pub fn parse_dds(data: &[u8]) -> Result<TextureContainer, ImportError> {
+    if !data.starts_with(&DDS_MAGIC) {
+        return Err("not a DDS file".into());
+    }
+    if data.len() < DDS_HEADER_SIZE {
+        return Err("DDS header is truncated".into());
+    }
+    let u32_at = |offset: usize| LE::read_u32(&data[4 + offset..]);
+    let (flags, height, width, mip_count) = (u32_at(4), u32_at(8), u32_at(12), u32_at(24));
+    //pixel format starts at 72
+    let (pixel_flags, four_cc, bit_count) = (u32_at(76), &data[4 + 80..4 + 84], u32_at(84));
+    let masks = [u32_at(88), u32_at(92), u32_at(96), u32_at(100)];
+    let caps2 = u32_at(108);
+    if caps2 & DDSCAPS2_VOLUME != 0 {
+        return Err("volume textures aren't supported".into());
+    }
+    let mut header_end = DDS_HEADER_SIZE;
+    let mut faces = if caps2 & DDSCAPS2_CUBEMAP != 0 { 6 } else { 1 };
+    let mut layers = 1;
+    let mut array = false;
+    let (format, bgra) = if pixel_flags & DDPF_FOURCC != 0 {
+        match four_cc {
+            b"DXT1" => (TextureFormat::Bc1, false),
+            b"DXT2" | b"DXT3" => (TextureFormat::Bc2, false),
+            b"DXT4" | b"DXT5" => (TextureFormat::Bc3, false),
+            b"ATI1" | b"BC4U" => (TextureFormat::Bc4, false),
+            b"ATI2" | b"BC5U" => (TextureFormat::Bc5, false),
+            b"DX10" => {
+                header_end += 20;
+                if data.len() < header_end {
+                    return Err("DX10 header is truncated".into());
+                }
+                let dx10 = |offset: usize| LE::read_u32(&data[DDS_HEADER_SIZE + offset..]);
+                let (format, misc_flags, array_size) = (dx10(0), dx10(8), dx10(12));
+                if misc_flags & DDS_RESOURCE_MISC_TEXTURECUBE != 0 {
+                    faces = 6;
+                }
+                layers = array_size.max(1);
+                array = array_size > 1;
+                dxgi_format(format).ok_or_else(|| format!("DXGI_FORMAT {} isn't supported", format))?
+            }
+            four_cc => return Err(format!("FourCC {} isn't supported", String::from_utf8_lossy(four_cc)).into()),
+        }
+    } else if pixel_flags & DDPF_RGB != 0 && bit_count == 32 {
+        match masks {
+            [0xff, 0xff00, 0xff0000, _] => (TextureFormat::RGBA8, false),
+            [0xff0000, 0xff00, 0xff, _] => (TextureFormat::RGBA8, true),
+            _ => return Err("pixel masks of DDS aren't supported".into()),
+        }
+    } else {
+        return Err("pixel format of DDS isn't supported".into());
+    };
+    let level_count = match flags & DDSD_MIPMAPCOUNT {
+        0 => 1,
+        _ => mip_count.max(1) as usize,
+    };
+    if level_count > 32 {
+        return Err(format!("{} mip levels are too many", level_count).into());
+    }
+    let images = image_count(layers, faces)?;
+    let sizes = (0..level_count)
+        .map(|i| {
+            let (width, height) = ((width >> i).max(1), (height >> i).max(1));
+            Ok((width, height, image_size(format, width, height)?))
+        })
+        .collect::<Result<Vec<_>, ImportError>>()?;
+    //checked before allocating, as sizes come from header
+    let mut total = header_end;
+    for &(width, height, _) in &sizes {
+        total = total.saturating_add(level_size(format, width, height, images)?);
+    }
+    if total > data.len() {
+        return Err("texture data is outside of file".into());
+    }
+    let mut levels = sizes
+        .iter()
+        .map(|&(width, height, size)| MipLevel {
+            width,
+            height,
+            data: Vec::with_capacity(size * images),
+        })
+        .collect::<Vec<_>>();
+    //DDS stores whole mip chain of every image one after another
+    let mut offset = header_end as u64;
+    for _ in 0..images {
+        for (level, (_, _, size)) in levels.iter_mut().zip(&sizes) {
+            let image = range(data, offset, *size as u64)?;
+            level.data.extend_from_slice(&data[image]);
+            offset += *size as u64;
+        }
+    }
+    if bgra {
+        swizzle_bgra(&mut levels);
+    }
+    TextureContainer {
+        format,
+        layers,
+        faces,
+        array,
+        levels,
+    }
+    .validate()
+}
+///Container read on worker thread, max_size of meta file is already applied
+fn decode_container(context: &ImportContext) -> Result<(TextureContainer, TextureSettings), ImportError> {
+    let settings = context.settings()?.texture;
+    let mut container = TextureContainer::parse(&context.read()?)?;
+    if let Some(max_size) = settings.max_size {
+        container.limit_size(max_size);
+    }
+    Ok((container, settings))
+}
+const CONTAINER_EXTENSIONS: &[&str] = &["ktx2", "dds"];
+///KTX2 and DDS files without uploading them
+pub struct TextureContainerImporter;
+impl AsyncImporter for TextureContainerImporter {
+    type Decoded = TextureContainer;
+    type Output = TextureContainer;
+    fn name(&self) -> &'static str {
+        "texture container"
+    }
+    fn extensions(&self) -> &[&str] {
+        CONTAINER_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<TextureContainer, ImportError> {
+        Ok(decode_container(context)?.0)
+    }
+    fn finish(&self, decoded: TextureContainer) -> Result<TextureContainer, ImportError> {
+        Ok(decoded)
+    }
+}
+///2d textures from KTX2 and DDS files, mip levels are uploaded as stored.
+///Mipmaps are generated only for uncompressed textures without them
+pub struct ContainerTextureImporter;
+impl AsyncImporter for ContainerTextureImporter {
+    type Decoded = (TextureContainer, TextureSettings);
+    type Output = Texture2D;
+    fn name(&self) -> &'static str {
+        "texture container"
+    }
+    fn extensions(&self) -> &[&str] {
+        CONTAINER_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<(TextureContainer, TextureSettings), ImportError> {
+        let (container, settings) = decode_container(context)?;
+        if container.array || container.is_cube_map() {
+            return Err("texture is a cube map or an array, it can't be loaded as Texture2D".into());
+        }
+        Ok((container, settings))
+    }
+    fn finish(&self, (container, settings): (TextureContainer, TextureSettings)) -> Result<Texture2D, ImportError> {
+        let generate = container.generates_mipmaps(&settings);
+        let levels = if generate { usize::MAX } else { container.levels.len() };
+        let (min_filter, mag_filter) = settings.filters(levels > 1);
+        //srgb is decided by format of container
+        let builder = settings
+            .apply(Texture2DBuilder::new())
+            .min_filter(min_filter)
+            .mag_filter(mag_filter)
+            .internal_format(container.format)
+            .mip_levels(container.levels);
+        let texture = builder.build()?;
+        if generate {
+            texture.gen_mipmaps();
+        }
+        Ok(texture)
+    }
+}
+///Cube maps from KTX2 and DDS files
+pub struct CubeMapImporter;
+impl AsyncImporter for CubeMapImporter {
+    type Decoded = (TextureContainer, TextureSettings);
+    type Output = Texture<CubeMapTexture>;
+    fn name(&self) -> &'static str {
+        "cube map"
+    }
+    fn extensions(&self) -> &[&str] {
+        CONTAINER_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<(TextureContainer, TextureSettings), ImportError> {
+        let (container, settings) = decode_container(context)?;
+        if !container.is_cube_map() || container.array {
+            return Err("texture isn't a cube map".into());
+        }
+        Ok((container, settings))
+    }
+    fn finish(
+        &self,
+        (container, settings): (TextureContainer, TextureSettings),
+    ) -> Result<Texture<CubeMapTexture>, ImportError> {
+        let mut texture = Texture::<CubeMapTexture>::new();
+        {
+            let mut handle = texture.inner_mut();
+            handle.set_mip_levels(container.format, &container.levels);
+            let (min_filter, mag_filter) = settings.filters(container.levels.len() > 1);
+            handle.set_min_filter(min_filter);
+            handle.set_mag_filter(mag_filter);
+        }
+        Ok(texture)
+    }
+}
+///2d array textures from KTX2 and DDS files
+pub struct TextureArrayImporter;
+impl AsyncImporter for TextureArrayImporter {
+    type Decoded = (TextureContainer, TextureSettings);
+    type Output = Texture<TextureArray2D>;
+    fn name(&self) -> &'static str {
+        "texture array"
+    }
+    fn extensions(&self) -> &[&str] {
+        CONTAINER_EXTENSIONS
+    }
+    fn decode(&self, context: &ImportContext) -> Result<(TextureContainer, TextureSettings), ImportError> {
+        let (container, settings) = decode_container(context)?;
+        if container.is_cube_map() {
+            return Err("cube map arrays aren't supported".into());
+        }
+        Ok((container, settings))
+    }
+    fn finish(
+        &self,
+        (container, settings): (TextureContainer, TextureSettings),
+    ) -> Result<Texture<TextureArray2D>, ImportError> {
+        let mut texture = Texture::<TextureArray2D>::new();
+        {
+            let mut handle = texture.inner_mut();
+            handle.set_mip_levels(container.format, container.layers as i32, &container.levels);
+            let (min_filter, mag_filter) = settings.filters(container.levels.len() > 1);
+            handle.set_min_filter(min_filter);
+            handle.set_mag_filter(mag_filter);
+        }
+        Ok(texture)
+    }
+}
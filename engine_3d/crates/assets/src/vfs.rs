@@ -0,0 +1,197 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    ext::PathEXT,
+    listing::{self, DirEntry},
+    path_str,
+    ranged_reader::AssetReader,
+    AssetError, Assets, File,
+};
+
+#[derive(Debug)]
+pub enum Layer {
+    ///data file created by saver
+    DataFile(Assets),
+    ///plain directory on disk, files are read as is
+    Directory(PathBuf),
+}
+///File found in one of the layers
+#[derive(Debug, Clone)]
+pub enum VfsFile<'a> {
+    Packed(&'a File),
+    Loose { path: String, source: PathBuf, size: u64 },
+}
+impl VfsFile<'_> {
+    pub fn path(&self) -> &str {
+        match self {
+            VfsFile::Packed(file) => file.path(),
+            VfsFile::Loose { path, .. } => path,
+        }
+    }
+    ///size of file after decompression
+    pub fn size(&self) -> u64 {
+        match self {
+            VfsFile::Packed(file) => file.size(),
+            VfsFile::Loose { size, .. } => *size,
+        }
+    }
+}
+///Several data files and directories mounted on top of each other.
+///Layer mounted last is searched first, so patch data files and loose directories
+///are mounted after the base data file to override its files
+#[derive(Debug, Default)]
+pub struct Vfs {
+    layers: Vec<Layer>,
+}
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn mount_data_file<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, AssetError> {
+        let assets = Assets::from_data_file(path)?;
+        self.layers.push(Layer::DataFile(assets));
+        Ok(self)
+    }
+    pub fn mount_directory<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, AssetError> {
+        if !fs::metadata(path.as_ref())?.is_dir() {
+            return Err(AssetError::FileFindingError);
+        }
+        self.layers.push(Layer::Directory(path.as_ref().into()));
+        Ok(self)
+    }
+    ///layers in the order they were mounted
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+    ///Returns file and index of layer it was found in
+    pub fn find_file_in<P: AsRef<Path>>(&self, path: P) -> Result<(usize, VfsFile<'_>), AssetError> {
+        let path = path.as_ref().to_asset_path().ok_or(AssetError::FileFindingError)?;
+        let mut error = AssetError::FileFindingError;
+        for (i, layer) in self.layers.iter().enumerate().rev() {
+            match layer {
+                Layer::DataFile(assets) => {
+                    if let Ok(file) = assets.find_file(&path) {
+                        return Ok((i, VfsFile::Packed(file)));
+                    }
+                }
+                Layer::Directory(directory) => {
+                    let source = directory.join(&path);
+                    if let Ok(metadata) = fs::metadata(&source) {
+                        //directory with the same name doesn't hide file in lower layers
+                        if !metadata.is_file() {
+                            error = AssetError::NotAFile;
+                            continue;
+                        }
+                        let size = metadata.len();
+                        return Ok((i, VfsFile::Loose { path, source, size }));
+                    }
+                }
+            }
+        }
+        Err(error)
+    }
+    pub fn find_file<P: AsRef<Path>>(&self, path: P) -> Result<VfsFile<'_>, AssetError> {
+        Ok(self.find_file_in(path)?.1)
+    }
+    ///Files and directories directly inside dir in any layer, "" lists the root
+    pub fn list_dir<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<DirEntry>, AssetError> {
+        let paths = self.paths()?;
+        listing::list_dir(paths.iter().map(String::as_str), path_str(dir.as_ref())?)
+    }
+    ///Paths of every file inside dir and its subdirectories, sorted
+    pub fn walk<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<String>, AssetError> {
+        let paths = self.paths()?;
+        let files = listing::walk(paths.iter().map(String::as_str), path_str(dir.as_ref())?)?;
+        Ok(files.into_iter().map(str::to_owned).collect())
+    }
+    ///Paths of every file matching pattern like "models/**/*.obj", sorted
+    pub fn glob(&self, pattern: &str) -> Result<Vec<String>, AssetError> {
+        let paths = self.paths()?;
+        let files = listing::glob(paths.iter().map(String::as_str), pattern);
+        Ok(files.into_iter().map(str::to_owned).collect())
+    }
+    ///Paths of files in all layers, loose directories are read from disk every time
+    fn paths(&self) -> Result<BTreeSet<String>, AssetError> {
+        fn visit(root: &Path, dir: &Path, paths: &mut BTreeSet<String>) -> Result<(), AssetError> {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    visit(root, &path, paths)?;
+                } else if let Some(path) = path.strip_prefix(root).ok().and_then(Path::to_asset_path) {
+                    paths.insert(path);
+                }
+            }
+            Ok(())
+        }
+        let mut paths = BTreeSet::new();
+        for layer in self.layers.iter() {
+            match layer {
+                Layer::DataFile(assets) => {
+                    paths.extend(assets.files().iter().map(|file| file.path().to_owned()))
+                }
+                Layer::Directory(directory) => visit(directory, directory, &mut paths)?,
+            }
+        }
+        Ok(paths)
+    }
+    pub fn open_file<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>, AssetError> {
+        match self.find_file_in(path)? {
+            (i, VfsFile::Packed(file)) => self.data_file(i).open_file(file.path()),
+            (_, VfsFile::Loose { source, .. }) => Ok(fs::read(source)?),
+        }
+    }
+    pub fn open_file_string<P: AsRef<Path>>(&self, path: P) -> Result<String, AssetError> {
+        Ok(String::from_utf8(self.open_file(path)?)?)
+    }
+    ///Opens file for streaming, see Assets::open_reader
+    pub fn open_reader<P: AsRef<Path>>(&self, path: P) -> Result<VfsReader<'_>, AssetError> {
+        match self.find_file_in(path)? {
+            (i, VfsFile::Packed(file)) => {
+                Ok(VfsReader::Packed(self.data_file(i).open_reader(file.path())?))
+            }
+            (_, VfsFile::Loose { source, .. }) => {
+                Ok(VfsReader::Loose(BufReader::new(fs::File::open(source)?)))
+            }
+        }
+    }
+    ///Checks every mounted data file, see Assets::verify
+    pub fn verify(&self) -> Result<(), AssetError> {
+        for layer in self.layers.iter() {
+            if let Layer::DataFile(assets) = layer {
+                assets.verify()?;
+            }
+        }
+        Ok(())
+    }
+    fn data_file(&self, layer: usize) -> &Assets {
+        match &self.layers[layer] {
+            Layer::DataFile(assets) => assets,
+            Layer::Directory(_) => unreachable!("packed file is always found in data file"),
+        }
+    }
+}
+pub enum VfsReader<'a> {
+    Packed(AssetReader<'a>),
+    Loose(BufReader<fs::File>),
+}
+impl Read for VfsReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            VfsReader::Packed(reader) => reader.read(buf),
+            VfsReader::Loose(reader) => reader.read(buf),
+        }
+    }
+}
+impl Seek for VfsReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            VfsReader::Packed(reader) => reader.seek(pos),
+            VfsReader::Loose(reader) => reader.seek(pos),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{ext::PathEXT, pack_cache::SourceInfo, AssetError};
+
+///Watches loose directory for changes by comparing size and modification time of files.
+///Polling is slower than OS notifications but works the same everywhere
+#[derive(Debug)]
+pub struct AssetWatcher {
+    root: PathBuf,
+    interval: Duration,
+    last_poll: Instant,
+    files: HashMap<String, (u64, (u64, u32))>,
+}
+impl AssetWatcher {
+    ///Files that already exist aren't reported as changed
+    pub fn new<P: AsRef<Path>>(root: P) -> Result<Self, AssetError> {
+        let mut watcher = Self {
+            root: root.as_ref().into(),
+            interval: Duration::from_millis(500),
+            last_poll: Instant::now(),
+            files: HashMap::new(),
+        };
+        watcher.files = watcher.scan()?;
+        Ok(watcher)
+    }
+    ///minimal time between two directory scans, 500ms by default
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    ///Returns paths of files that were changed or created since previous poll, sorted.
+    ///Returns nothing if interval hasn't passed yet
+    pub fn poll(&mut self) -> Result<Vec<String>, AssetError> {
+        if self.last_poll.elapsed() < self.interval {
+            return Ok(Vec::new());
+        }
+        self.poll_now()
+    }
+    ///Same as poll, but ignores interval
+    pub fn poll_now(&mut self) -> Result<Vec<String>, AssetError> {
+        self.last_poll = Instant::now();
+        let files = self.scan()?;
+        let mut changed = files
+            .iter()
+            .filter(|(path, stat)| self.files.get(*path) != Some(stat))
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        changed.sort_unstable();
+        self.files = files;
+        Ok(changed)
+    }
+    fn scan(&self) -> Result<HashMap<String, (u64, (u64, u32))>, AssetError> {
+        fn visit(
+            root: &Path,
+            dir: &Path,
+            files: &mut HashMap<String, (u64, (u64, u32))>,
+        ) -> Result<(), AssetError> {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    visit(root, &path, files)?;
+                    continue;
+                }
+                let Some(asset_path) = path.strip_prefix(root).ok().and_then(Path::to_asset_path) else {
+                    continue;
+                };
+                //file could be removed while directory is scanned
+                if let Ok(stat) = SourceInfo::stat(&path) {
+                    files.insert(asset_path, stat);
+                }
+            }
+            Ok(())
+        }
+        let mut files = HashMap::new();
+        visit(&self.root, &self.root, &mut files)?;
+        Ok(files)
+    }
+}
@@ -16,7 +16,9 @@ fn main() {
             "GL_EXT_texture_filter_anisotropic",
             "GL_ARB_draw_buffers_blend",
             "GL_ARB_program_interface_query",
-            "GL_EXT_direct_state_access"
+            "GL_EXT_direct_state_access",
+            "GL_EXT_texture_compression_s3tc",
+            "GL_EXT_texture_sRGB"
         ],
     )
     .write_bindings(gl_generator::GlobalGenerator, &mut file)
@@ -0,0 +1,143 @@
+use std::sync::OnceLock;
+
+use math::{vec3, Mat4, Vec3};
+
+use super::Model;
+use crate::objects::vertex::Vertex;
+
+///Axis aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+impl Aabb {
+    ///None if there are no points
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self { min: first, max: first }, |aabb, point| Self {
+            min: aabb.min.min(point),
+            max: aabb.max.max(point),
+        }))
+    }
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) / 2.0
+    }
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        std::array::from_fn(|i| {
+            vec3(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        })
+    }
+    ///Box around transformed corners, can be larger than box around transformed vertices
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let corners = self.corners().map(|corner| matrix.transform_point3(corner));
+        Self::from_points(corners).unwrap()
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+impl BoundingSphere {
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+    ///Radius is scaled by the largest scale of matrix, so sphere still contains everything after non uniform scale
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let scale = [matrix.x_axis, matrix.y_axis, matrix.z_axis]
+            .map(|axis| axis.truncate().length())
+            .into_iter()
+            .fold(0.0, f32::max);
+        Self { center: matrix.transform_point3(self.center), radius: self.radius * scale }
+    }
+}
+///Box and sphere around vertices of model, sphere is centered at center of box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub aabb: Aabb,
+    pub sphere: BoundingSphere,
+}
+impl Bounds {
+    pub fn from_points(points: impl Iterator<Item = Vec3> + Clone) -> Option<Self> {
+        let aabb = Aabb::from_points(points.clone())?;
+        let center = aabb.center();
+        let radius = points.map(|point| center.distance_squared(point)).fold(0.0, f32::max).sqrt();
+        Some(Self { aabb, sphere: BoundingSphere { center, radius } })
+    }
+}
+impl<V: Vertex> Model<V> {
+    ///Bounds of every vertex, including ones not used by indices. Computed on first call and cached
+    ///until verticies change. None if there are no vertices or they have no position
+    pub fn bounds(&self) -> Option<&Bounds> {
+        self.bounds
+            .get_or_init(|| Bounds::from_points(self.verticies.iter().map_while(Vertex::position)))
+            .as_ref()
+    }
+    pub fn aabb(&self) -> Option<Aabb> {
+        self.bounds().map(|bounds| bounds.aabb)
+    }
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        self.bounds().map(|bounds| bounds.sphere)
+    }
+    ///Model with bounds known beforehand, like ones stored in cooked mesh. They aren't checked,
+    ///so they must be the same as bounds would compute
+    pub fn with_bounds(self, bounds: Option<Bounds>) -> Self {
+        Self { bounds: OnceLock::from(bounds), ..self }
+    }
+    pub(super) fn invalidate_bounds(&mut self) {
+        self.bounds.take();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{model::primitives::cuboid, vertex::ModelVertex};
+    #[test]
+    fn box_and_sphere() {
+        let bounds = *cuboid(vec3(1.0, 2.0, 3.0)).bounds().unwrap();
+        assert_eq!((bounds.aabb.min, bounds.aabb.max), (vec3(-0.5, -1.0, -1.5), vec3(0.5, 1.0, 1.5)));
+        assert_eq!(bounds.sphere.center, Vec3::ZERO);
+        assert!((bounds.sphere.radius - 3.5f32.sqrt()).abs() < 1e-5);
+        assert!(Model::<ModelVertex>::new(Vec::new(), None).bounds().is_none());
+    }
+    #[test]
+    fn cache_is_reset_by_changes() {
+        let mut model = cuboid(Vec3::ONE);
+        assert_eq!(model.aabb().unwrap().min, Vec3::splat(-0.5));
+        model.bake_matrix(&Mat4::from_translation(Vec3::X));
+        assert_eq!(model.aabb().unwrap().min, vec3(0.5, -0.5, -0.5));
+        model.verticies_mut()[0].position = [-1.0, 0.0, 0.0];
+        assert_eq!(model.aabb().unwrap().min, vec3(-1.0, -0.5, -0.5));
+        //given bounds are kept until vertices change
+        let unit = *cuboid(Vec3::ONE).bounds().unwrap();
+        let mut seeded = model.with_bounds(Some(unit));
+        assert_eq!(seeded.bounds(), Some(&unit));
+        seeded.indicies_mut().take();
+        assert_eq!(seeded.bounds(), Some(&unit));
+        seeded.verticies_mut();
+        assert_ne!(seeded.bounds(), Some(&unit));
+    }
+    #[test]
+    fn transformed() {
+        let aabb = Aabb { min: Vec3::ZERO, max: Vec3::ONE };
+        let rotated = aabb.transformed(&Mat4::from_rotation_y(std::f32::consts::FRAC_PI_2));
+        assert!(rotated.min.distance(vec3(0.0, 0.0, -1.0)) < 1e-5 && rotated.max.distance(vec3(1.0, 1.0, 0.0)) < 1e-5);
+        let sphere = BoundingSphere { center: Vec3::X, radius: 1.0 };
+        let scaled = sphere.transformed(&Mat4::from_scale(vec3(1.0, 3.0, 1.0)));
+        assert_eq!((scaled.center, scaled.radius), (Vec3::X, 3.0));
+        assert!(scaled.contains(vec3(1.0, 2.9, 0.0)) && !aabb.contains(Vec3::splat(1.1)));
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use math::{vec3, Mat3, Mat4};
+use transform::Transform;
+
+use super::Model;
+use crate::objects::vertex::Vertex;
+
+///Root of union find set with path halving
+fn find(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+impl<V: Vertex + Clone> Model<V> {
+    ///Adds vertices and triangles of other model, its indices are offset by vertex count of this model.
+    ///If only one of models has indices, the other one gets sequential indices
+    pub fn append(&mut self, other: &Model<V>) {
+        let offset = self.verticies.len() as u32;
+        match (&mut self.indicies, &other.indicies) {
+            (None, None) => (),
+            (indicies, other_indicies) => {
+                let indicies = indicies.get_or_insert_with(|| (0..offset).collect());
+                match other_indicies {
+                    Some(other_indicies) => indicies.extend(other_indicies.iter().map(|i| i + offset)),
+                    None => indicies.extend(offset..offset + other.verticies.len() as u32),
+                }
+            }
+        }
+        self.verticies.extend_from_slice(&other.verticies);
+        self.invalidate_bounds();
+    }
+    ///One model with triangles of all models, see append
+    pub fn merge<'a>(models: impl IntoIterator<Item = &'a Model<V>>) -> Self
+    where
+        V: 'a,
+    {
+        let mut merged = Model::new(Vec::new(), None);
+        for model in models {
+            merged.append(model);
+        }
+        merged
+    }
+    ///Transforms positions by matrix, normals by its inverse transpose and tangents (xyz of attribute named tangent)
+    ///by its rotation and scale. Mirroring matrix flips winding of triangles and sign of bitangent,
+    ///so front faces stay front faces
+    pub fn bake_matrix(&mut self, matrix: &Mat4) {
+        let linear = Mat3::from_mat4(*matrix);
+        let normal_matrix = linear.inverse().transpose();
+        let mirrored = linear.determinant() < 0.0;
+        for vertex in &mut self.verticies {
+            if let Some(position) = vertex.position() {
+                vertex.set_position(matrix.transform_point3(position));
+            }
+            if let Some(normal) = vertex.normal() {
+                vertex.set_normal((normal_matrix * normal).normalize_or_zero());
+            }
+            if let Some([x, y, z, w]) = vertex.attribute("tangent") {
+                let tangent = (linear * vec3(x, y, z)).normalize_or_zero();
+                vertex.set_attribute("tangent", tangent.extend(if mirrored { -w } else { w }).into());
+            }
+        }
+        if mirrored {
+            match &mut self.indicies {
+                Some(indicies) => indicies.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2)),
+                None => self.verticies.chunks_exact_mut(3).for_each(|triangle| triangle.swap(1, 2)),
+            }
+        }
+        self.invalidate_bounds();
+    }
+    ///Bakes transform of entity into vertices, see bake_matrix
+    pub fn bake_transform(&mut self, transform: &Transform) {
+        self.bake_matrix(&transform.get_matrix());
+    }
+    ///Splits model into parts whose triangles are connected through shared vertices.
+    ///Vertices at the same position are connected too, so models where every triangle has its own vertices
+    ///(like parsed obj files) are split by their shape.
+    ///Every island has only vertices it uses, islands and their triangles are in order of triangles of model
+    pub fn islands(&self) -> Vec<Model<V>> {
+        let sequential: Vec<u32>;
+        let indicies = match &self.indicies {
+            Some(indicies) => indicies,
+            None => {
+                sequential = (0..self.verticies.len() as u32).collect();
+                &sequential
+            }
+        };
+        //vertices at the same position are one node, vertices without position are connected only by indices
+        let mut positions = HashMap::new();
+        let nodes: Vec<usize> = self
+            .verticies
+            .iter()
+            .enumerate()
+            .map(|(i, vertex)| match vertex.position() {
+                Some(position) => {
+                    let count = positions.len();
+                    *positions.entry(position.to_array().map(|value| (value + 0.0).to_bits())).or_insert(count)
+                }
+                None => i,
+            })
+            .collect();
+        let mut parents: Vec<usize> = (0..self.verticies.len()).collect();
+        for triangle in indicies.chunks_exact(3) {
+            let root = find(&mut parents, nodes[triangle[0] as usize]);
+            for &i in &triangle[1..] {
+                let other = find(&mut parents, nodes[i as usize]);
+                parents[other] = root;
+            }
+        }
+        let mut islands: Vec<Model<V>> = Vec::new();
+        let mut island_of_root = HashMap::new();
+        //every vertex belongs to one island, so it has one new index
+        let mut remap = vec![u32::MAX; self.verticies.len()];
+        for triangle in indicies.chunks_exact(3) {
+            let root = find(&mut parents, nodes[triangle[0] as usize]);
+            let island = *island_of_root.entry(root).or_insert_with(|| {
+                islands.push(Model::new(Vec::new(), Some(Vec::new())));
+                islands.len() - 1
+            });
+            let island = &mut islands[island];
+            for &i in triangle {
+                let new = &mut remap[i as usize];
+                if *new == u32::MAX {
+                    *new = island.verticies.len() as u32;
+                    island.verticies.push(self.verticies[i as usize].clone());
+                }
+                island.indicies.as_mut().unwrap().push(*new);
+            }
+        }
+        islands
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{
+        model::{from_str, primitives::*},
+        vertex::{ModelVertex, TangentVertex},
+    };
+    use math::Vec3;
+    #[test]
+    fn normals_of_stretched_sphere() {
+        //normals stay perpendicular to ellipsoid
+        let scale = vec3(2.0, 1.0, 0.5);
+        let mut ellipsoid = uv_sphere(1.0, 16, 8);
+        ellipsoid.bake_matrix(&Mat4::from_scale(scale));
+        for vertex in &ellipsoid.verticies {
+            let expected = (Vec3::from(vertex.position) / (scale * scale)).normalize();
+            assert!(Vec3::from(vertex.normal).distance(expected) < 1e-4, "{:?}", vertex);
+        }
+    }
+    #[test]
+    fn mirroring_keeps_front_faces() {
+        let mut mirrored = icosphere(1.0, 1);
+        mirrored.bake_matrix(&Mat4::from_scale(vec3(-1.0, 1.0, 1.0)));
+        for triangle in mirrored.indicies.as_ref().unwrap().chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mirrored.verticies[triangle[i] as usize].position));
+            assert!((b - a).cross(c - a).dot(a + b + c) > 0.0);
+        }
+        let tangent = TangentVertex::new([0.0; 3], [0.0, 1.0, 0.0], [0.0; 2], [1.0, 0.0, 0.0, -1.0]);
+        let mut tangents = Model::new(vec![tangent], None);
+        tangents.bake_matrix(&Mat4::from_scale(vec3(-2.0, 1.0, 1.0)));
+        assert_eq!(tangents.verticies[0].tangent, [-1.0, 0.0, 0.0, 1.0]);
+    }
+    #[test]
+    fn bake_transform() {
+        let mut model = cube(1.0);
+        model.bake_transform(&Transform::from_position(vec3(0.0, 2.0, 0.0)));
+        assert_eq!(model.aabb().unwrap().min, vec3(-0.5, 1.5, -0.5));
+    }
+    #[test]
+    fn merge_offsets_indices() {
+        let cube = cube(1.0);
+        let sphere = icosphere(0.5, 1);
+        let merged = Model::merge([&cube, &sphere]);
+        assert_eq!(merged.verticies.len(), cube.verticies.len() + sphere.verticies.len());
+        assert_eq!(merged.indicies.as_ref().unwrap().len(), 36 + 80 * 3);
+        assert_eq!(merged.indicies.as_ref().unwrap()[36], sphere.indicies.as_ref().unwrap()[0] + 24);
+    }
+    #[test]
+    fn merge_without_indices() {
+        let sphere = icosphere(0.5, 0);
+        let sequential = Model::new(vec![sphere.verticies[0]; 3], None);
+        let merged = Model::merge([&sequential, &sphere]);
+        assert_eq!(merged.indicies.unwrap()[..4], [0, 1, 2, 3 + sphere.indicies.as_ref().unwrap()[0]]);
+        assert!(Model::merge([&sequential, &sequential]).indicies.is_none());
+    }
+    #[test]
+    fn islands_of_merged_models() {
+        let mut sphere = icosphere(0.5, 1);
+        sphere.bake_matrix(&Mat4::from_translation(vec3(5.0, 0.0, 0.0)));
+        let islands = Model::merge([&cube(1.0), &sphere]).islands();
+        assert_eq!(islands.len(), 2);
+        assert_eq!(islands[0].verticies.len(), 24);
+        assert_eq!(islands[1].indicies, sphere.indicies);
+    }
+    #[test]
+    fn islands_connected_by_positions() {
+        //obj triangles don't share vertices
+        let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nv 5 0 0\nv 6 0 0\nv 5 1 0\nf 1 2 3\nf 5 6 7\nf 2 4 3\n";
+        let islands: Vec<Model<ModelVertex>> = from_str(obj).unwrap().islands();
+        assert_eq!(islands.iter().map(|island| island.verticies.len()).collect::<Vec<_>>(), [6, 3]);
+    }
+}
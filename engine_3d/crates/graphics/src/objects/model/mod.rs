@@ -1,9 +1,17 @@
+use std::sync::OnceLock;
+
 use super::{
     buffers::{Buffer, EBO, VAO, VBO},
     vertex::{ModelVertex, Vertex},
 };
 pub mod primitives;
-use math::{Vec2, Vec3};
+pub mod obj;
+pub mod normals;
+pub mod processing;
+pub mod bounds;
+pub mod editing;
+use bounds::Bounds;
+use obj::ObjError;
 /* 
 #[derive(Debug, Clone)]
 pub struct NewModel{
@@ -29,229 +37,43 @@ impl NewModel {
     }
 } */
 //TODO:Material, partial drawing 
+///Vertices are reached through methods, so cached bounds never outlive them
 #[derive(Debug, Clone)]
 pub struct Model<V: Vertex> {
-    pub verticies: Vec<V>,
-    pub indicies: Option<Vec<u32>>,
+    verticies: Vec<V>,
+    indicies: Option<Vec<u32>>,
+    ///computed on first call of bounds, reset when verticies are borrowed mutably
+    bounds: OnceLock<Option<Bounds>>,
 }
 impl<V: Vertex> Model<V> {
     pub fn new(vertexes: Vec<V>, indicies: Option<Vec<u32>>) -> Self {
-        Self { verticies: vertexes, indicies }
+        Self { verticies: vertexes, indicies, bounds: OnceLock::new() }
     }
-    pub fn instantiate(&self) -> InstancedModel {
-        InstancedModel::new(&self)
+    pub fn verticies(&self) -> &[V] {
+        &self.verticies
     }
-    pub fn from_str(source:&str) -> Option<Model<ModelVertex>> {
-        fn calc_normal(a: &[f32; 3], b: &[f32; 3], c: &[f32; 3]) -> [f32; 3] {
-            let a_side = Vec3::new(a[0], a[1], a[2]) - Vec3::new(b[0], b[1], b[2]);
-            let c_side = Vec3::new(b[0], b[1], b[2]) - Vec3::new(c[0], c[1], c[2]);
-            a_side.cross(c_side).normalize().into()
-        }
-        let mut v_pos = Vec::new();
-        let mut v_normal = Vec::new();
-        let mut v_uv = Vec::new();
-        let mut indicies = Vec::new();
-        let mut vertexes = Vec::new();
-        for line in source.lines() {
-            let mut separated = line.split(" ");
-            match separated.next()? {
-                "v" => v_pos.push([
-                    separated.next()?.parse::<f32>().ok()?,
-                    separated.next()?.parse::<f32>().ok()?,
-                    separated.next()?.parse::<f32>().ok()?,
-                ]),
-                "vn" => v_normal.push([
-                    separated.next()?.parse::<f32>().ok()?,
-                    separated.next()?.parse::<f32>().ok()?,
-                    separated.next()?.parse::<f32>().ok()?,
-                ]),
-                "vt" => v_uv.push([
-                    separated.next()?.parse::<f32>().ok()?,
-                    separated.next()?.parse::<f32>().ok()?,
-                ]),
-                "f" => {
-                    let mut positions = [0; 3];
-                    let mut normals = [None; 3];
-                    let mut uvs = [None; 3];
-                    for i in 0..3 {
-                        let mut vertex_indexes = separated.next()?.split("/");
-                        positions[i] = vertex_indexes.next()?.parse::<u32>().ok()? - 1;
-                        if let Some(uv) = vertex_indexes.next() {
-                            if let Some(idx) = uv.parse::<u32>().ok() {
-                                uvs[i] = Some(idx - 1);
-                            }
-                        }
-                        if let Some(normal) = vertex_indexes.next() {
-                            if let Some(idx) = normal.parse::<u32>().ok() {
-                                normals[i] = Some(idx - 1);
-                            }
-                        }
-                    }
-                    match (true, normals[0].is_some(), uvs[0].is_some()) {
-                        (true, true, true) => {
-                            for i in 0..3 {
-                                vertexes.push(ModelVertex::new(
-                                    v_pos[positions[i] as usize],
-                                    v_normal[normals[i].unwrap() as usize],
-                                    v_uv[uvs[i].unwrap() as usize],
-                                ));
-                                indicies.push(indicies.len() as u32);
-                            }
-                        }
-                        (true, true, false) => {
-                            for i in 0..3 {
-                                vertexes.push(ModelVertex::new(
-                                    v_pos[positions[i] as usize],
-                                    v_normal[normals[i].unwrap() as usize],
-                                    [0.0, 0.0],
-                                ));
-                                indicies.push(indicies.len() as u32);
-                            }
-                        }
-                        (true, false, true) => {
-                            let v_normal = calc_normal(
-                                &v_pos[positions[0] as usize],
-                                &v_pos[positions[1] as usize],
-                                &v_pos[positions[2] as usize],
-                            );
-                            for i in 0..3 {
-                                vertexes.push(ModelVertex::new(
-                                    v_pos[positions[i] as usize],
-                                    v_normal,
-                                    v_uv[uvs[i].unwrap() as usize],
-                                ));
-                                indicies.push(indicies.len() as u32);
-                            }
-                        }
-                        (true, false, false) => {
-                            let v_normal = calc_normal(
-                                &v_pos[positions[0] as usize],
-                                &v_pos[positions[1] as usize],
-                                &v_pos[positions[2] as usize],
-                            );
-                            for i in 0..3 {
-                                vertexes.push(ModelVertex::new(
-                                    v_pos[positions[i] as usize],
-                                    v_normal,
-                                    [0.0, 0.0],
-                                ));
-                                indicies.push(indicies.len() as u32);
-                            }
-                        }
-                        _ => (),
-                    }
-                }
-                _ => (),
-            }
-        }
-        return Some(Model::new(vertexes, Some(indicies)))
+    ///Resets cached bounds
+    pub fn verticies_mut(&mut self) -> &mut Vec<V> {
+        self.bounds.take();
+        &mut self.verticies
     }
-}
-
-pub fn from_str(source:&str) -> Option<Model<ModelVertex>> {
-    fn calc_normal(a: &[f32; 3], b: &[f32; 3], c: &[f32; 3]) -> [f32; 3] {
-        let a_side = Vec3::new(a[0], a[1], a[2]) - Vec3::new(b[0], b[1], b[2]);
-        let c_side = Vec3::new(b[0], b[1], b[2]) - Vec3::new(c[0], c[1], c[2]);
-        a_side.cross(c_side).normalize().into()
+    pub fn indicies(&self) -> Option<&[u32]> {
+        self.indicies.as_deref()
     }
-    let mut v_pos = Vec::new();
-    let mut v_normal = Vec::new();
-    let mut v_uv = Vec::new();
-    let mut indicies = Vec::new();
-    let mut vertexes = Vec::new();
-    for line in source.lines() {
-        let mut separated = line.split(" ");
-        match separated.next()? {
-            "v" => v_pos.push([
-                separated.next()?.parse::<f32>().ok()?,
-                separated.next()?.parse::<f32>().ok()?,
-                separated.next()?.parse::<f32>().ok()?,
-            ]),
-            "vn" => v_normal.push([
-                separated.next()?.parse::<f32>().ok()?,
-                separated.next()?.parse::<f32>().ok()?,
-                separated.next()?.parse::<f32>().ok()?,
-            ]),
-            "vt" => v_uv.push([
-                separated.next()?.parse::<f32>().ok()?,
-                separated.next()?.parse::<f32>().ok()?,
-            ]),
-            "f" => {
-                let mut positions = [0; 3];
-                let mut normals = [None; 3];
-                let mut uvs = [None; 3];
-                for i in 0..3 {
-                    let mut vertex_indexes = separated.next()?.split("/");
-                    positions[i] = vertex_indexes.next()?.parse::<u32>().ok()? - 1;
-                    if let Some(uv) = vertex_indexes.next() {
-                        if let Some(idx) = uv.parse::<u32>().ok() {
-                            uvs[i] = Some(idx - 1);
-                        }
-                    }
-                    if let Some(normal) = vertex_indexes.next() {
-                        if let Some(idx) = normal.parse::<u32>().ok() {
-                            normals[i] = Some(idx - 1);
-                        }
-                    }
-                }
-                match (true, normals[0].is_some(), uvs[0].is_some()) {
-                    (true, true, true) => {
-                        for i in 0..3 {
-                            vertexes.push(ModelVertex::new(
-                                v_pos[positions[i] as usize],
-                                v_normal[normals[i].unwrap() as usize],
-                                v_uv[uvs[i].unwrap() as usize],
-                            ));
-                            indicies.push(indicies.len() as u32);
-                        }
-                    }
-                    (true, true, false) => {
-                        for i in 0..3 {
-                            vertexes.push(ModelVertex::new(
-                                v_pos[positions[i] as usize],
-                                v_normal[normals[i].unwrap() as usize],
-                                [0.0, 0.0],
-                            ));
-                            indicies.push(indicies.len() as u32);
-                        }
-                    }
-                    (true, false, true) => {
-                        let v_normal = calc_normal(
-                            &v_pos[positions[0] as usize],
-                            &v_pos[positions[1] as usize],
-                            &v_pos[positions[2] as usize],
-                        );
-                        for i in 0..3 {
-                            vertexes.push(ModelVertex::new(
-                                v_pos[positions[i] as usize],
-                                v_normal,
-                                v_uv[uvs[i].unwrap() as usize],
-                            ));
-                            indicies.push(indicies.len() as u32);
-                        }
-                    }
-                    (true, false, false) => {
-                        let v_normal = calc_normal(
-                            &v_pos[positions[0] as usize],
-                            &v_pos[positions[1] as usize],
-                            &v_pos[positions[2] as usize],
-                        );
-                        for i in 0..3 {
-                            vertexes.push(ModelVertex::new(
-                                v_pos[positions[i] as usize],
-                                v_normal,
-                                [0.0, 0.0],
-                            ));
-                            indicies.push(indicies.len() as u32);
-                        }
-                    }
-                    _ => (),
-                }
-            }
-            _ => (),
-        }
+    ///Bounds include every vertex, so they stay the same whatever indices are
+    pub fn indicies_mut(&mut self) -> &mut Option<Vec<u32>> {
+        &mut self.indicies
+    }
+    pub fn into_parts(self) -> (Vec<V>, Option<Vec<u32>>) {
+        (self.verticies, self.indicies)
     }
-    return Some(Model::new(vertexes, Some(indicies)))
+    pub fn instantiate(&self) -> InstancedModel {
+        InstancedModel::new(&self)
+    }
+}
+///Parses obj model, see parse_obj
+pub fn from_str(source: &str) -> Result<Model<ModelVertex>, ObjError> {
+    source.parse()
 }
 
 #[allow(dead_code)]
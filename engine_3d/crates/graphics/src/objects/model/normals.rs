@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+
+use bevy_mikktspace::{generate_tangents, Geometry};
+use math::Vec3;
+
+use super::Model;
+use crate::objects::vertex::{ModelVertex, TangentVertex};
+
+///How normals of triangles around vertex are summed up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalWeighting {
+    ///larger triangles pull normal more
+    Area,
+    ///triangles with larger angle at vertex pull normal more, result doesn't depend on triangulation
+    Angle,
+}
+///Weighted normal of triangle at every corner
+fn weighted_normals(corners: &[ModelVertex], weighting: NormalWeighting) -> Vec<(Vec3, Vec3)> {
+    corners
+        .chunks_exact(3)
+        .flat_map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(triangle[i].position));
+            let cross = (b - a).cross(c - a);
+            let normal = cross.normalize_or_zero();
+            let weights = match weighting {
+                //length of cross product is double area
+                NormalWeighting::Area => [cross.length(); 3],
+                NormalWeighting::Angle => [(a, b, c), (b, c, a), (c, a, b)].map(|(corner, next, previous)| {
+                    let (first, second) = ((next - corner).normalize_or_zero(), (previous - corner).normalize_or_zero());
+                    first.dot(second).clamp(-1.0, 1.0).acos()
+                }),
+            };
+            weights.map(|weight| (normal, normal * weight))
+        })
+        .collect()
+}
+impl Model<ModelVertex> {
+    ///Replaces normals with weighted average of normals of triangles around vertex.
+    ///Triangles meeting at angle larger than crease_angle (in radians) aren't smoothed together, so edges stay sharp.
+    ///Every triangle gets its own vertices like with flat normals, weld merges them back
+    pub fn smooth_normals(&mut self, crease_angle: f32, weighting: NormalWeighting) {
+        let mut corners = match self.indicies.take() {
+            Some(indices) => indices.iter().map(|&i| self.verticies[i as usize]).collect(),
+            None => std::mem::take(&mut self.verticies),
+        };
+        corners.truncate(corners.len() / 3 * 3);
+        let normals = weighted_normals(&corners, weighting);
+        //corners at the same position
+        let mut positions: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        for (i, corner) in corners.iter().enumerate() {
+            positions.entry(corner.position.map(|value| (value + 0.0).to_bits())).or_default().push(i);
+        }
+        let min_cos = crease_angle.cos();
+        for shared in positions.values() {
+            for &i in shared {
+                let face_normal = normals[i].0;
+                let smooth = shared
+                    .iter()
+                    .filter(|&&j| normals[j].0.dot(face_normal) >= min_cos)
+                    .map(|&j| normals[j].1)
+                    .fold(Vec3::ZERO, |sum, normal| sum + normal)
+                    .normalize_or_zero();
+                corners[i].normal = if smooth == Vec3::ZERO { face_normal } else { smooth }.into();
+            }
+        }
+        self.indicies = Some((0..corners.len() as u32).collect());
+        self.verticies = corners;
+        self.invalidate_bounds();
+    }
+    ///Model with MikkTSpace tangents, so normal maps baked by other tools look the same.
+    ///Normals and texture coordinates must be set, vertices are welded after generation.
+    ///None if tangents can't be generated
+    pub fn with_tangents(&self) -> Option<Model<TangentVertex>> {
+        let corners: Vec<TangentVertex> = match &self.indicies {
+            Some(indices) => indices.iter().map(|&i| tangent_vertex(&self.verticies[i as usize])).collect(),
+            None => self.verticies.iter().map(tangent_vertex).collect(),
+        };
+        let mut geometry = TangentGeometry { corners };
+        geometry.corners.truncate(geometry.corners.len() / 3 * 3);
+        if !geometry.corners.is_empty() && !generate_tangents(&mut geometry) {
+            return None;
+        }
+        let mut model = Model::new(geometry.corners, None);
+        model.weld(0.0);
+        Some(model)
+    }
+}
+fn tangent_vertex(vertex: &ModelVertex) -> TangentVertex {
+    TangentVertex::new(vertex.position, vertex.normal, vertex.texture_coords, [1.0, 0.0, 0.0, 1.0])
+}
+///Triangles with their own vertices, as mikktspace sees them
+struct TangentGeometry {
+    corners: Vec<TangentVertex>,
+}
+impl Geometry for TangentGeometry {
+    fn num_faces(&self) -> usize {
+        self.corners.len() / 3
+    }
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.corners[face * 3 + vert].position
+    }
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.corners[face * 3 + vert].normal
+    }
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.corners[face * 3 + vert].texture_coords
+    }
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.corners[face * 3 + vert].tangent = tangent;
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::model::from_str;
+    const CUBE: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\nvt 0 0\n\
+        f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+    //second quad has mirrored texture coordinates
+    const QUADS: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 2 0 0\nv 2 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nvn 0 0 1\n\
+        f 1/1/1 2/2/1 3/3/1 4/4/1\nf 2/2/1 5/1/1 6/4/1 3/3/1\n";
+    fn is_close<const N: usize>(a: [f32; N], b: [f32; N]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+    ///normal pointing from center of unit cube
+    fn diagonal(vertex: &ModelVertex) -> [f32; 3] {
+        vertex.position.map(|value| (value * 2.0 - 1.0) / 3f32.sqrt())
+    }
+    fn smooth(crease_angle: f32, weighting: NormalWeighting) -> Model<ModelVertex> {
+        let mut model = from_str(CUBE).unwrap();
+        model.smooth_normals(crease_angle.to_radians(), weighting);
+        model
+    }
+    #[test]
+    fn edges_sharper_than_crease_angle_stay_flat() {
+        let flat = from_str(CUBE).unwrap();
+        let sharp = smooth(60.0, NormalWeighting::Angle);
+        assert!(sharp.verticies.iter().zip(&flat.verticies).all(|(a, b)| is_close(a.normal, b.normal)));
+    }
+    #[test]
+    fn angle_weighting_ignores_triangulation() {
+        //every face has right angle at corner of cube, no matter how it is triangulated
+        let model = smooth(100.0, NormalWeighting::Angle);
+        assert!(model.verticies.iter().all(|v| is_close(v.normal, diagonal(v))));
+    }
+    #[test]
+    fn area_weighting() {
+        let model = smooth(100.0, NormalWeighting::Area);
+        assert!(!model.verticies.iter().all(|v| is_close(v.normal, diagonal(v))));
+    }
+    #[test]
+    fn smooth_vertices_can_be_welded() {
+        let mut model = smooth(100.0, NormalWeighting::Angle);
+        model.weld(0.0);
+        assert_eq!(model.verticies.len(), 8);
+    }
+    #[test]
+    fn tangents_follow_texture_coordinates() {
+        let tangents = from_str(QUADS).unwrap().with_tangents().unwrap();
+        assert_eq!(tangents.verticies.len(), 8);
+        let forward = [1.0, 0.0, 0.0, 1.0];
+        let mirrored = [-1.0, 0.0, 0.0, -1.0];
+        for vertex in &tangents.verticies {
+            match vertex.position[0] {
+                x if x < 1.0 => assert!(is_close(vertex.tangent, forward), "{:?}", vertex),
+                x if x > 1.0 => assert!(is_close(vertex.tangent, mirrored), "{:?}", vertex),
+                //vertices on shared edge are split because tangents differ
+                _ => assert!(is_close(vertex.tangent, forward) || is_close(vertex.tangent, mirrored)),
+            }
+        }
+    }
+    #[test]
+    fn tangents_of_empty_model() {
+        assert!(Model::<ModelVertex>::new(Vec::new(), None).with_tangents().unwrap().verticies.is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+use std::{fmt::Display, ops::Range, str::FromStr};
+
+use math::Vec3;
+
+use super::Model;
+use crate::objects::vertex::ModelVertex;
+
+///Error of obj parser with number of line that caused it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjError {
+    ///starts with 1
+    pub line: usize,
+    pub message: String,
+}
+impl Display for ObjError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "obj line {}: {}", self.line, self.message)
+    }
+}
+impl std::error::Error for ObjError {}
+///Triangles after o, g or usemtl statement until next statement of same kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjRange {
+    ///empty for faces before first statement
+    pub name: String,
+    ///indices of triangles, vertices of triangle i are 3 * i..3 * i + 3
+    pub triangles: Range<usize>,
+}
+///Parsed obj file, every triangle has its own 3 vertices in the order of faces
+#[derive(Debug, Clone)]
+pub struct ParsedObj {
+    pub model: Model<ModelVertex>,
+    ///from o statements
+    pub objects: Vec<ObjRange>,
+    ///from g statements, multiple names are kept as they are written
+    pub groups: Vec<ObjRange>,
+    ///from usemtl statements, same material can have several ranges
+    pub materials: Vec<ObjRange>,
+    ///smoothing group of every triangle, 0 when smoothing is off
+    pub smoothing_groups: Vec<u32>,
+    ///files from mtllib statements in order
+    pub material_libraries: Vec<String>,
+}
+impl ParsedObj {
+    pub fn triangle_count(&self) -> usize {
+        self.smoothing_groups.len()
+    }
+}
+impl FromStr for Model<ModelVertex> {
+    type Err = ObjError;
+    fn from_str(source: &str) -> Result<Self, ObjError> {
+        parse_obj(source).map(|obj| obj.model)
+    }
+}
+///Adds range for statement, empty range of previous statement is replaced
+fn start_range(ranges: &mut Vec<ObjRange>, name: &str, triangle: usize) {
+    if ranges.last().is_some_and(|range| range.triangles.is_empty()) {
+        ranges.pop();
+    }
+    ranges.push(ObjRange { name: name.to_owned(), triangles: triangle..triangle });
+}
+fn extend_ranges(ranges: &mut Vec<ObjRange>, triangle: usize) {
+    match ranges.last_mut() {
+        Some(range) => range.triangles.end = triangle + 1,
+        None => ranges.push(ObjRange { name: String::new(), triangles: triangle..triangle + 1 }),
+    }
+}
+///1 based index or negative index relative to the end of already read elements
+fn resolve_index(index: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let index: i64 = index.parse().map_err(|_| format!("{} index \"{}\" isn't a number", kind, index))?;
+    let resolved = match index {
+        1.. => index - 1,
+        0 => return Err(format!("{} index can't be 0", kind)),
+        _ => count as i64 + index,
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!("{} index {} is out of range, there are {} of them", kind, index, count));
+    }
+    Ok(resolved as usize)
+}
+fn parse_floats<const N: usize>(values: &[&str], required: usize) -> Result<[f32; N], String> {
+    if values.len() < required {
+        return Err(format!("expected at least {} numbers, found {}", required, values.len()));
+    }
+    let mut result = [0.0; N];
+    for (value, text) in result.iter_mut().zip(values) {
+        *value = text.parse().map_err(|_| format!("\"{}\" isn't a number", text))?;
+    }
+    Ok(result)
+}
+///Parses Wavefront obj, supports:
+///- any whitespace between values and comments after #
+///- polygons, triangulated as fans so they should be convex
+///- negative indices relative to the last vertex
+///- o, g, s, usemtl and mtllib statements, other statements are ignored.
+///
+///Faces without normals get normal of their triangle
+pub fn parse_obj(source: &str) -> Result<ParsedObj, ObjError> {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut obj = ParsedObj {
+        model: Model::new(Vec::new(), None),
+        objects: Vec::new(),
+        groups: Vec::new(),
+        materials: Vec::new(),
+        smoothing_groups: Vec::new(),
+        material_libraries: Vec::new(),
+    };
+    let mut smoothing_group = 0;
+    let mut face = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError { line: i + 1, message };
+        let line = line.split_once('#').map_or(line, |(line, _)| line);
+        let values = line.split_whitespace().collect::<Vec<_>>();
+        let Some((&statement, values)) = values.split_first() else {
+            continue;
+        };
+        //name statements keep spaces inside of name
+        let name = line.trim().strip_prefix(statement).unwrap_or_default().trim();
+        let triangle = obj.triangle_count();
+        match statement {
+            "v" => positions.push(parse_floats::<3>(values, 3).map_err(error)?),
+            "vn" => normals.push(parse_floats::<3>(values, 3).map_err(error)?),
+            "vt" => uvs.push(parse_floats::<2>(values, 1).map_err(error)?),
+            "o" => start_range(&mut obj.objects, name, triangle),
+            "g" => start_range(&mut obj.groups, name, triangle),
+            "usemtl" => start_range(&mut obj.materials, name, triangle),
+            "mtllib" => obj.material_libraries.push(name.to_owned()),
+            "s" => {
+                smoothing_group = match name {
+                    "off" => 0,
+                    name => name.parse().map_err(|_| error(format!("smoothing group \"{}\" isn't a number", name)))?,
+                }
+            }
+            "f" => {
+                if values.len() < 3 {
+                    return Err(error(format!("face has {} vertices, expected at least 3", values.len())));
+                }
+                face.clear();
+                for vertex in values {
+                    let mut indices = vertex.split('/');
+                    let position = resolve_index(indices.next().unwrap_or_default(), positions.len(), "vertex");
+                    let uv = match indices.next() {
+                        None | Some("") => Ok(None),
+                        Some(index) => resolve_index(index, uvs.len(), "texture coordinate").map(Some),
+                    };
+                    let normal = match indices.next() {
+                        None | Some("") => Ok(None),
+                        Some(index) => resolve_index(index, normals.len(), "normal").map(Some),
+                    };
+                    face.push((position.map_err(error)?, uv.map_err(error)?, normal.map_err(error)?));
+                }
+                for j in 1..face.len() - 1 {
+                    let corners = [face[0], face[j], face[j + 1]];
+                    let [a, b, c] = corners.map(|(position, _, _)| Vec3::from(positions[position]));
+                    let flat_normal = (b - a).cross(c - a).normalize_or_zero().into();
+                    //normals of file are used only if every corner has one
+                    let has_normals = corners.iter().all(|(_, _, normal)| normal.is_some());
+                    for (position, uv, normal) in corners {
+                        obj.model.verticies.push(ModelVertex::new(
+                            positions[position],
+                            match (has_normals, normal) {
+                                (true, Some(normal)) => normals[normal],
+                                _ => flat_normal,
+                            },
+                            uv.map_or([0.0, 0.0], |uv| uvs[uv]),
+                        ));
+                    }
+                    let triangle = obj.triangle_count();
+                    for ranges in [&mut obj.objects, &mut obj.groups, &mut obj.materials] {
+                        extend_ranges(ranges, triangle);
+                    }
+                    obj.smoothing_groups.push(smoothing_group);
+                }
+            }
+            _ => (),
+        }
+    }
+    for ranges in [&mut obj.objects, &mut obj.groups, &mut obj.materials] {
+        if ranges.last().is_some_and(|range| range.triangles.is_empty()) {
+            ranges.pop();
+        }
+    }
+    obj.model.indicies = Some((0..obj.model.verticies.len() as u32).collect());
+    Ok(obj)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const SOURCE: &str = "# quad and pentagon\nmtllib a.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv\t0  1 0 # comment\nv 0.5 2 0\nvt 0 0\nvt 1\nvn 0 0 1\n\n\
+        o first part\ns 1\nf 1/1/1 2/2/1 3/1/1 4/1/1\ng a b\nusemtl red\ns off\nf -5 -4 -3 -1 -2\nusemtl unused\n";
+    fn error(source: &str) -> ObjError {
+        parse_obj(source).err().unwrap()
+    }
+    #[test]
+    fn polygons_are_fan_triangulated() {
+        let obj = parse_obj(SOURCE).unwrap();
+        assert_eq!((obj.triangle_count(), obj.model.verticies.len()), (5, 15));
+        let positions = obj.model.verticies.iter().map(|v| v.position).collect::<Vec<_>>();
+        assert_eq!(positions[3..6], [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+        //negative indices count from the last vertex
+        assert_eq!(positions[12..], [[0.0, 0.0, 0.0], [0.5, 2.0, 0.0], [0.0, 1.0, 0.0]]);
+        assert_eq!(obj.model.indicies, Some((0..15).collect()));
+    }
+    #[test]
+    fn attributes() {
+        let obj = parse_obj(SOURCE).unwrap();
+        //missing v of texture coordinate is 0
+        assert_eq!((obj.model.verticies[1].texture_coords, obj.model.verticies[1].normal), ([1.0, 0.0], [0.0, 0.0, 1.0]));
+        //normal of triangle for faces without normals
+        assert_eq!(obj.model.verticies[14].normal, [0.0, 0.0, 1.0]);
+    }
+    #[test]
+    fn statements() {
+        let obj = parse_obj(SOURCE).unwrap();
+        let range = |name: &str, triangles| ObjRange { name: name.to_owned(), triangles };
+        assert_eq!(obj.objects, [range("first part", 0..5)]);
+        assert_eq!(obj.groups, [range("", 0..2), range("a b", 2..5)]);
+        //usemtl without faces after it has no range
+        assert_eq!(obj.materials, [range("", 0..2), range("red", 2..5)]);
+        assert_eq!(obj.smoothing_groups, [1, 1, 0, 0, 0]);
+        assert_eq!(obj.material_libraries, ["a.mtl"]);
+    }
+    #[test]
+    fn from_str() {
+        let model: Model<ModelVertex> = SOURCE.parse().unwrap();
+        assert_eq!(model.verticies.len(), 15);
+        assert_eq!(super::super::from_str(SOURCE).unwrap().verticies.len(), 15);
+    }
+    #[test]
+    fn errors() {
+        assert_eq!(error("v 0 0 0\n\nf 1 1 0").to_string(), "obj line 3: vertex index can't be 0");
+        assert_eq!(error("v 0 0 0\nf 1 -2 1").line, 2);
+        assert!(error("v 0 0 0\nf 1 1 1/2").message.contains("texture coordinate index 2 is out of range"));
+        assert!(error("v 0 0 0\nf 1 1").message.contains("2 vertices"));
+        assert_eq!(error("v 0 zero 0").message, "\"zero\" isn't a number");
+        assert_eq!(error("vn 0 1").message, "expected at least 3 numbers, found 2");
+        assert_eq!(error("s smooth").line, 1);
+    }
+}
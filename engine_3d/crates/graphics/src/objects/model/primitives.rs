@@ -1,37 +1,341 @@
-use math::{ vec3, Vec3};
+use std::f32::consts::{PI, TAU};
+
+use math::{vec3, Vec3};
 
 use crate::{define_vertex, objects::vertex::{Vertex,IntoGLenum,ModelVertex}};
 use super::Model;
 define_vertex!{SimpleVertex,pos,f32,3}
-//TODO:ADD SOME PRIMITIVES
-pub struct Triangle<V:Vertex>{
-    p1:V,
-    p2:V,
-    p3:V,
-}
-pub fn icosahedron() -> Model<ModelVertex>{
-    let t =(1.0 + 5.0f32.sqrt() ) / 2.0; 
-    fn unit_to_sphere(vec:Vec3) -> Vec3{
-        let len = (vec.x*vec.x + vec.y*vec.y + vec.z*vec.z).sqrt();
-        vec3(vec.x/len, vec.y/len, vec.z/len)
-    }
-    let mut vertices_positions = Vec::new();
-    let indicies = Vec::new();
-    let vertices = Vec::new();
-    vertices_positions.push(unit_to_sphere(vec3(-1.0,t,0.0)));
-    vertices_positions.push(unit_to_sphere(vec3(1.0,t,0.0)));
-    vertices_positions.push(unit_to_sphere(vec3(-1.0,-t,0.0)));
-    vertices_positions.push(unit_to_sphere(vec3(1.0,-t,0.0)));
-
-    vertices_positions.push(unit_to_sphere(vec3(0.0,-1.0,t)));
-    vertices_positions.push(unit_to_sphere(vec3(0.0,1.0,t)));
-    vertices_positions.push(unit_to_sphere(vec3(0.0,-1.0,-t)));
-    vertices_positions.push(unit_to_sphere(vec3(0.0,1.0,-t)));
-    
-    vertices_positions.push(unit_to_sphere(vec3(t,0.0,-1.0)));
-    vertices_positions.push(unit_to_sphere(vec3(t,0.0,1.0)));
-    vertices_positions.push(unit_to_sphere(vec3(-t,0.0,-1.0)));
-    vertices_positions.push(unit_to_sphere(vec3(-t,0.0,1.0)));
+//Every generator is centered at origin with y up, triangles are counter clockwise when looked at from outside.
+//Curved surfaces use u = 0 at -z going around y axis, so texture isn't mirrored
 
-    Model::new(vertices, Some(indicies))
-}
\ No newline at end of file
+///sin and cos of PI * step / steps, exact at both ends so vertices at poles are at the same position
+fn polar(step: u32, steps: u32) -> (f32, f32) {
+    match step {
+        0 => (0.0, 1.0),
+        step if step == steps => (0.0, -1.0),
+        step => (PI * step as f32 / steps as f32).sin_cos(),
+    }
+}
+///Direction around y axis for u coordinate
+fn around(u: f32) -> Vec3 {
+    let (sin, cos) = (TAU * (u - 0.5)).sin_cos();
+    vec3(sin, 0.0, cos)
+}
+///u of vertex at pole, middle of the only triangle that uses it
+fn pole_u(column: u32, columns: u32, top: bool) -> f32 {
+    (column as f32 + if top { -0.5 } else { 0.5 }) / columns as f32
+}
+///Grid of (columns + 1) * (rows + 1) vertices, vertex(column, row) should go around counter clockwise
+///when column grows to the right and row grows up. Triangles with two vertices at the same position are skipped
+fn surface(columns: u32, rows: u32, vertex: impl Fn(u32, u32) -> ModelVertex) -> Model<ModelVertex> {
+    let verticies: Vec<ModelVertex> = (0..=rows)
+        .flat_map(|row| (0..=columns).map(move |column| (column, row)))
+        .map(|(column, row)| vertex(column, row))
+        .collect();
+    let mut indicies = Vec::with_capacity((columns * rows * 6) as usize);
+    let index = |column: u32, row: u32| row * (columns + 1) + column;
+    for row in 0..rows {
+        for column in 0..columns {
+            let [a, b, c, d] = [
+                index(column, row),
+                index(column + 1, row),
+                index(column + 1, row + 1),
+                index(column, row + 1),
+            ];
+            for triangle in [[a, b, c], [a, c, d]] {
+                let [a, b, c] = triangle.map(|i| verticies[i as usize].position);
+                if a != b && b != c && c != a {
+                    indicies.extend(triangle);
+                }
+            }
+        }
+    }
+    let mut model = Model::new(verticies, Some(indicies));
+    //vertices left at poles
+    model.optimize_vertex_fetch();
+    model
+}
+///Flat disc facing up or down at height y, texture is mapped from above or below
+fn disc(radius: f32, y: f32, segments: u32, up: bool) -> Model<ModelVertex> {
+    let normal = if up { Vec3::Y } else { Vec3::NEG_Y };
+    surface(segments, 1, |column, row| {
+        let u = column as f32 / segments as f32;
+        //going the other way around when looked at from above
+        let direction = around(if up { 1.0 - u } else { u }) * row as f32;
+        let z = if up { -direction.z } else { direction.z };
+        ModelVertex::new(
+            (direction * radius + Vec3::Y * y).into(),
+            normal.into(),
+            [0.5 + direction.x * 0.5, 0.5 + z * 0.5],
+        )
+    })
+}
+///20 triangles of sphere with radius 1, vertices at poles and two rings between them
+fn icosahedron_triangles() -> Vec<[Vec3; 3]> {
+    let (ring_y, ring_radius) = (1.0 / 5f32.sqrt(), 2.0 / 5f32.sqrt());
+    let ring = |angle: f32| {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        vec3(sin * ring_radius, 0.0, cos * ring_radius)
+    };
+    let upper: Vec<Vec3> = (0..5).map(|i| ring(72.0 * i as f32) + Vec3::Y * ring_y).collect();
+    let lower: Vec<Vec3> = (0..5).map(|i| ring(36.0 + 72.0 * i as f32) - Vec3::Y * ring_y).collect();
+    (0..5)
+        .flat_map(|i| {
+            let j = (i + 1) % 5;
+            [
+                [Vec3::Y, upper[i], upper[j]],
+                [Vec3::NEG_Y, lower[i], lower[j]],
+                [upper[i], upper[j], lower[i]],
+                [lower[i], lower[j], upper[j]],
+            ]
+        })
+        .map(|[a, b, c]| match (b - a).cross(c - a).dot(a + b + c) < 0.0 {
+            true => [a, c, b],
+            false => [a, b, c],
+        })
+        .collect()
+}
+///Sphere made by splitting every triangle of icosahedron into 4 subdivisions times,
+///has 20 * 4^subdivisions triangles of almost the same size.
+///Texture is mapped like on uv_sphere, vertices are split along the seam
+pub fn icosphere(radius: f32, subdivisions: u32) -> Model<ModelVertex> {
+    let mut triangles = icosahedron_triangles();
+    for _ in 0..subdivisions {
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let [ab, bc, ca] = [(a, b), (b, c), (c, a)].map(|(start, end)| (start + end).normalize());
+                [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+            })
+            .collect();
+    }
+    let mut corners = Vec::with_capacity(triangles.len() * 3);
+    for triangle in triangles {
+        let mut uvs = triangle.map(|p| [p.x.atan2(p.z) / TAU + 0.5, p.y.clamp(-1.0, 1.0).asin() / PI + 0.5]);
+        let poles = triangle.map(|p| p.x == 0.0 && p.z == 0.0);
+        //triangle crossing the seam goes past u = 1 instead of across whole texture
+        let us = (0..3).filter(|&i| !poles[i]).map(|i| uvs[i][0]);
+        if us.clone().fold(0.0f32, f32::max) - us.fold(1.0f32, f32::min) > 0.5 {
+            uvs.iter_mut().filter(|[u, _]| *u < 0.5).for_each(|[u, _]| *u += 1.0);
+        }
+        for i in (0..3).filter(|&i| poles[i]) {
+            uvs[i][0] = (uvs[(i + 1) % 3][0] + uvs[(i + 2) % 3][0]) / 2.0;
+        }
+        corners.extend((0..3).map(|i| ModelVertex::new((triangle[i] * radius).into(), triangle[i].into(), uvs[i])));
+    }
+    let mut model = Model::new(corners, None);
+    model.weld(0.0);
+    model
+}
+///Icosphere with radius 1 without subdivisions
+pub fn icosahedron() -> Model<ModelVertex> {
+    icosphere(1.0, 0)
+}
+///Sphere with segments vertices around y axis and rings rows of triangles from bottom to top
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Model<ModelVertex> {
+    let (segments, rings) = (segments.max(3), rings.max(2));
+    surface(segments, rings, |column, row| {
+        let (ring, y) = polar(row, rings);
+        let u = match row {
+            0 => pole_u(column, segments, false),
+            row if row == rings => pole_u(column, segments, true),
+            _ => column as f32 / segments as f32,
+        };
+        let normal = around(u) * ring - Vec3::Y * y;
+        ModelVertex::new((normal * radius).into(), normal.into(), [u, row as f32 / rings as f32])
+    })
+}
+///Box with size of every side, faces don't share vertices and have whole texture on them
+pub fn cuboid(size: Vec3) -> Model<ModelVertex> {
+    //normal, right and up of every face when looked at from outside
+    let faces = [
+        (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+        (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+        (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+        (Vec3::Z, Vec3::X, Vec3::Y),
+        (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+    ];
+    let faces = faces.map(|(normal, right, up)| {
+        surface(1, 1, |column, row| {
+            let (u, v) = (column as f32, row as f32);
+            let position = (normal * 0.5 + right * (u - 0.5) + up * (v - 0.5)) * size;
+            ModelVertex::new(position.into(), normal.into(), [u, v])
+        })
+    });
+    Model::merge(&faces)
+}
+///Cube with side of size, see cuboid
+pub fn cube(size: f32) -> Model<ModelVertex> {
+    cuboid(Vec3::splat(size))
+}
+///Flat grid in xz plane facing up, split into columns along x and rows along z
+pub fn grid(width: f32, depth: f32, columns: u32, rows: u32) -> Model<ModelVertex> {
+    let (columns, rows) = (columns.max(1), rows.max(1));
+    surface(columns, rows, |column, row| {
+        let (u, v) = (column as f32 / columns as f32, row as f32 / rows as f32);
+        ModelVertex::new([(u - 0.5) * width, 0.0, (0.5 - v) * depth], [0.0, 1.0, 0.0], [u, v])
+    })
+}
+///Grid with 2 triangles
+pub fn plane(width: f32, depth: f32) -> Model<ModelVertex> {
+    grid(width, depth, 1, 1)
+}
+///Cylinder along y axis with caps, side has smooth normals and texture wrapped around it
+pub fn cylinder(radius: f32, height: f32, segments: u32) -> Model<ModelVertex> {
+    let segments = segments.max(3);
+    let side = surface(segments, 1, |column, row| {
+        let u = column as f32 / segments as f32;
+        let normal = around(u);
+        let y = (row as f32 - 0.5) * height;
+        ModelVertex::new((normal * radius + Vec3::Y * y).into(), normal.into(), [u, row as f32])
+    });
+    Model::merge(&[side, disc(radius, height / 2.0, segments, true), disc(radius, -height / 2.0, segments, false)])
+}
+///Cone along y axis with base at -height / 2 and tip at height / 2
+pub fn cone(radius: f32, height: f32, segments: u32) -> Model<ModelVertex> {
+    let segments = segments.max(3);
+    let side = surface(segments, 1, |column, row| {
+        let u = match row {
+            0 => column as f32 / segments as f32,
+            _ => pole_u(column, segments, true),
+        };
+        let direction = around(u);
+        //perpendicular to slope
+        let normal = (direction * height + Vec3::Y * radius).normalize();
+        let position = direction * radius * (1 - row) as f32 + Vec3::Y * (row as f32 - 0.5) * height;
+        ModelVertex::new(position.into(), normal.into(), [u, row as f32])
+    });
+    Model::merge(&[side, disc(radius, -height / 2.0, segments, false)])
+}
+///Cylinder with height between centers of hemispheres at its ends, whole capsule is height + 2 * radius tall.
+///Each hemisphere has rings rows of triangles, v grows with distance along the surface
+pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Model<ModelVertex> {
+    let (segments, rings) = (segments.max(3), rings.max(1));
+    let length = PI * radius + height;
+    surface(segments, rings * 2 + 1, |column, row| {
+        //equator row is repeated for both hemispheres
+        let (step, offset) = match row <= rings {
+            true => (row, -height / 2.0),
+            false => (row - 1, height / 2.0),
+        };
+        let (ring, y) = polar(step, rings * 2);
+        let u = match step {
+            0 => pole_u(column, segments, false),
+            step if step == rings * 2 => pole_u(column, segments, true),
+            _ => column as f32 / segments as f32,
+        };
+        let normal = around(u) * ring - Vec3::Y * y;
+        let distance = PI * radius * step as f32 / (rings * 2) as f32 + if row > rings { height } else { 0.0 };
+        ModelVertex::new(
+            (normal * radius + Vec3::Y * offset).into(),
+            normal.into(),
+            [u, distance / length],
+        )
+    })
+}
+///Torus around y axis, major_radius is distance from center to center of tube
+pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> Model<ModelVertex> {
+    let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+    surface(major_segments, minor_segments, |column, row| {
+        let (u, v) = (column as f32 / major_segments as f32, row as f32 / minor_segments as f32);
+        let direction = around(u);
+        //v starts at the inner side of the tube
+        let (sin, cos) = (TAU * (v - 0.5)).sin_cos();
+        let normal = direction * cos + Vec3::Y * sin;
+        ModelVertex::new((direction * major_radius + normal * minor_radius).into(), normal.into(), [u, v])
+    })
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    ///Checks normals, winding and texture coordinates, returns triangle count.
+    ///Triangles of convex shapes face away from origin, texture of wrapped shapes doesn't go back across the seam
+    fn check(model: &Model<ModelVertex>, convex: bool, wrapped: bool) -> usize {
+        let indices = model.indicies.as_ref().unwrap();
+        assert_eq!(indices.len() % 3, 0);
+        assert!(indices.iter().all(|&i| (i as usize) < model.verticies.len()));
+        for vertex in &model.verticies {
+            assert!((Vec3::from(vertex.normal).length() - 1.0).abs() < 1e-5, "{:?}", vertex);
+            assert!(vertex.texture_coords.iter().all(|&uv| (-1e-5..=1.5).contains(&uv)), "{:?}", vertex);
+        }
+        for triangle in indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| model.verticies[triangle[i] as usize]);
+            let [a, b, c] = corners.map(|v| Vec3::from(v.position));
+            let face = (b - a).cross(c - a);
+            assert!(face.length() > 0.0, "degenerate triangle {:?}", corners);
+            //winding agrees with normals
+            let normals = corners.iter().map(|v| Vec3::from(v.normal)).fold(Vec3::ZERO, |sum, normal| sum + normal);
+            assert!(face.dot(normals) > 0.0, "{:?}", corners);
+            assert!(!convex || face.dot(a + b + c) > 0.0, "{:?}", corners);
+            let us = corners.map(|v| v.texture_coords[0]);
+            assert!(!wrapped || us.iter().all(|u| (u - us[0]).abs() < 0.5), "{:?}", corners);
+        }
+        indices.len() / 3
+    }
+    fn is_close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+    #[test]
+    fn icosphere_subdivisions() {
+        for subdivisions in 0..3 {
+            let sphere = icosphere(2.0, subdivisions);
+            assert_eq!(check(&sphere, true, true), 20 * 4usize.pow(subdivisions));
+            assert!(sphere.verticies.iter().all(|v| is_close(Vec3::from(v.position).length(), 2.0)));
+        }
+        assert_eq!(check(&icosahedron(), true, true), 20);
+    }
+    #[test]
+    fn uv_sphere_rings() {
+        let sphere = uv_sphere(1.0, 16, 8);
+        //rings next to poles have one triangle per segment
+        assert_eq!(check(&sphere, true, true), 16 * 2 * 8 - 16 * 2);
+        assert!(sphere.verticies.iter().all(|v| is_close(Vec3::from(v.position).length(), 1.0)));
+    }
+    #[test]
+    fn cuboid_faces() {
+        let cuboid = cuboid(vec3(1.0, 2.0, 3.0));
+        assert_eq!(check(&cuboid, true, false), 12);
+        assert_eq!(cuboid.verticies.len(), 24);
+        for vertex in &cuboid.verticies {
+            let half_size = [0.5, 1.0, 1.5];
+            assert!((0..3).all(|i| is_close(vertex.position[i].abs(), half_size[i])
+                || vertex.normal[i] == 0.0 && vertex.position[i].abs() <= half_size[i]), "{:?}", vertex);
+        }
+        assert_eq!(check(&cube(1.0), true, false), 12);
+    }
+    #[test]
+    fn grid_and_plane() {
+        let grid = grid(4.0, 2.0, 4, 2);
+        assert_eq!(check(&grid, false, false), 16);
+        assert_eq!(grid.verticies.len(), 15);
+        assert!(grid.verticies.iter().all(|v| v.normal == [0.0, 1.0, 0.0]));
+        assert_eq!(check(&plane(1.0, 1.0), false, false), 2);
+    }
+    #[test]
+    fn cylinder_and_cone() {
+        //side and two caps
+        assert_eq!(check(&cylinder(1.0, 2.0, 12), true, false), 12 * 2 + 12 * 2);
+        assert_eq!(check(&cone(1.0, 2.0, 12), true, false), 12 + 12);
+    }
+    #[test]
+    fn capsule_surface() {
+        let capsule = capsule(0.5, 1.0, 12, 4);
+        check(&capsule, true, true);
+        //every vertex is radius away from segment between centers of hemispheres
+        for vertex in &capsule.verticies {
+            let [x, y, z] = vertex.position;
+            assert!(is_close(x.hypot(z).hypot(y.abs() - y.abs().min(0.5)), 0.5), "{:?}", vertex);
+        }
+    }
+    #[test]
+    fn torus_surface() {
+        let torus = torus(1.0, 0.25, 24, 8);
+        assert_eq!(check(&torus, false, true), 24 * 8 * 2);
+        assert_eq!(torus.verticies.len(), 25 * 9);
+        for vertex in &torus.verticies {
+            let [x, y, z] = vertex.position;
+            assert!(is_close((x.hypot(z) - 1.0).hypot(y), 0.25), "{:?}", vertex);
+        }
+    }
+}
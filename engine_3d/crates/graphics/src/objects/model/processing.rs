@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use super::Model;
+use crate::objects::vertex::Vertex;
+
+///Size of post-transform vertex cache assumed by optimize_vertex_cache, fits most GPUs
+pub const VERTEX_CACHE_SIZE: usize = 16;
+///Grid cell of vertex, only first three components are hashed (position for most vertices)
+fn cell(components: &[f32], epsilon: f32) -> [i64; 3] {
+    std::array::from_fn(|i| {
+        let value = components.get(i).copied().unwrap_or_default();
+        match epsilon > 0.0 {
+            true => (value / epsilon).floor() as i64,
+            //-0.0 and 0.0 are the same vertex
+            false => (value + 0.0).to_bits() as i64,
+        }
+    })
+}
+fn is_close(a: &[f32], b: &[f32], epsilon: f32) -> bool {
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() <= epsilon)
+}
+impl<V: Vertex + Clone> Model<V> {
+    fn indices_or_sequential(&mut self) -> Vec<u32> {
+        self.indicies
+            .take()
+            .unwrap_or_else(|| (0..self.verticies.len() as u32).collect())
+    }
+    ///Merges vertices whose every component differs by at most epsilon and rebuilds index buffer,
+    ///first vertex of merged ones is kept. Model without indices gets them
+    pub fn weld(&mut self, epsilon: f32) {
+        let indices = self.indices_or_sequential();
+        let mut remap = Vec::with_capacity(self.verticies.len());
+        let mut welded: Vec<V> = Vec::new();
+        //components of welded vertices one after another
+        let mut welded_components = Vec::new();
+        let mut cells: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+        let mut components = Vec::new();
+        let offsets: &[i64] = if epsilon > 0.0 { &[-1, 0, 1] } else { &[0] };
+        for vertex in self.verticies.drain(..) {
+            components.clear();
+            vertex.components(&mut components);
+            let stride = components.len();
+            let [x, y, z] = cell(&components, epsilon);
+            //vertices closer than epsilon can be in neighbouring cells
+            let found = offsets
+                .iter()
+                .flat_map(|dx| offsets.iter().flat_map(move |dy| offsets.iter().map(move |dz| [x + dx, y + dy, z + dz])))
+                .filter_map(|cell| cells.get(&cell))
+                .flatten()
+                .find(|&&i| is_close(&welded_components[i as usize * stride..][..stride], &components, epsilon));
+            match found {
+                Some(&i) => remap.push(i),
+                None => {
+                    let i = welded.len() as u32;
+                    cells.entry([x, y, z]).or_default().push(i);
+                    welded_components.extend_from_slice(&components);
+                    welded.push(vertex);
+                    remap.push(i);
+                }
+            }
+        }
+        self.verticies = welded;
+        self.indicies = Some(indices.iter().map(|&i| remap[i as usize]).collect());
+        self.invalidate_bounds();
+    }
+    ///Reorders triangles so their vertices are reused while they are still in post-transform cache,
+    ///uses Tipsify from "Fast Triangle Reordering for Vertex Locality and Reduced Overdraw"
+    pub fn optimize_vertex_cache(&mut self, cache_size: usize) {
+        let indices = self.indices_or_sequential();
+        let vertex_count = self.verticies.len();
+        let triangle_count = indices.len() / 3;
+        //triangles of every vertex, triangles of vertex v are adjacency[offsets[v]..offsets[v + 1]]
+        let mut live = vec![0usize; vertex_count];
+        for &i in &indices[..triangle_count * 3] {
+            live[i as usize] += 1;
+        }
+        let mut offsets = vec![0; vertex_count + 1];
+        for v in 0..vertex_count {
+            offsets[v + 1] = offsets[v] + live[v];
+        }
+        let mut filled = offsets.clone();
+        let mut adjacency = vec![0; triangle_count * 3];
+        for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+            for &v in corners {
+                adjacency[filled[v as usize]] = triangle;
+                filled[v as usize] += 1;
+            }
+        }
+        let mut cache_time = vec![0; vertex_count];
+        let mut time = cache_size + 1;
+        let mut emitted = vec![false; triangle_count];
+        let mut dead_end = Vec::new();
+        let mut output = Vec::with_capacity(indices.len());
+        let mut cursor = 0;
+        let mut fanning = (0..vertex_count).find(|&v| live[v] > 0);
+        while let Some(f) = fanning {
+            let mut candidates = Vec::new();
+            for &triangle in &adjacency[offsets[f]..offsets[f + 1]] {
+                if emitted[triangle] {
+                    continue;
+                }
+                emitted[triangle] = true;
+                for &v in &indices[triangle * 3..triangle * 3 + 3] {
+                    let v = v as usize;
+                    output.push(v as u32);
+                    dead_end.push(v);
+                    candidates.push(v);
+                    live[v] -= 1;
+                    if time - cache_time[v] > cache_size {
+                        cache_time[v] = time;
+                        time += 1;
+                    }
+                }
+            }
+            //candidate that stays in cache until all its triangles are emitted, the oldest one is preferred
+            let mut best = None;
+            let mut best_priority = 0;
+            for &v in &candidates {
+                if live[v] == 0 {
+                    continue;
+                }
+                let age = time - cache_time[v];
+                let priority = if age + 2 * live[v] <= cache_size { age } else { 0 };
+                if best.is_none() || priority > best_priority {
+                    best = Some(v);
+                    best_priority = priority;
+                }
+            }
+            fanning = best.or_else(|| {
+                while let Some(v) = dead_end.pop() {
+                    if live[v] > 0 {
+                        return Some(v);
+                    }
+                }
+                while cursor < vertex_count {
+                    if live[cursor] > 0 {
+                        return Some(cursor);
+                    }
+                    cursor += 1;
+                }
+                None
+            });
+        }
+        //incomplete triangle at the end is kept as it was
+        output.extend_from_slice(&indices[triangle_count * 3..]);
+        self.indicies = Some(output);
+    }
+    ///Reorders vertices by their first use in index buffer so they are fetched sequentially,
+    ///vertices not used by any triangle are removed
+    pub fn optimize_vertex_fetch(&mut self) {
+        let Some(indices) = self.indicies.as_mut() else {
+            return;
+        };
+        let mut remap = vec![u32::MAX; self.verticies.len()];
+        let mut verticies = Vec::with_capacity(self.verticies.len());
+        for index in indices.iter_mut() {
+            let new = &mut remap[*index as usize];
+            if *new == u32::MAX {
+                *new = verticies.len() as u32;
+                verticies.push(self.verticies[*index as usize].clone());
+            }
+            *index = *new;
+        }
+        self.verticies = verticies;
+        self.invalidate_bounds();
+    }
+    ///Weld, then optimize_vertex_cache and optimize_vertex_fetch
+    pub fn optimize(&mut self, weld_epsilon: f32) {
+        self.weld(weld_epsilon);
+        self.optimize_vertex_cache(VERTEX_CACHE_SIZE);
+        self.optimize_vertex_fetch();
+    }
+    ///Average count of vertices transformed per triangle with FIFO cache of cache_size vertices,
+    ///1 for a single triangle strip, 3 if no vertex is reused
+    pub fn cache_miss_ratio(&self, cache_size: usize) -> f32 {
+        let sequential: Vec<u32>;
+        let indices = match &self.indicies {
+            Some(indices) => indices,
+            None => {
+                sequential = (0..self.verticies.len() as u32).collect();
+                &sequential
+            }
+        };
+        let mut cache = std::collections::VecDeque::with_capacity(cache_size + 1);
+        let mut misses = 0;
+        for index in indices {
+            if !cache.contains(index) {
+                misses += 1;
+                cache.push_back(*index);
+                if cache.len() > cache_size {
+                    cache.pop_front();
+                }
+            }
+        }
+        misses as f32 / (indices.len() / 3).max(1) as f32
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::{model::from_str, vertex::ModelVertex};
+    ///Positions of every triangle, sorted so models with different vertex order can be compared
+    fn triangles(model: &Model<ModelVertex>) -> Vec<[[u32; 3]; 3]> {
+        let indices = model.indicies.as_ref().unwrap();
+        let mut triangles = indices
+            .chunks_exact(3)
+            .map(|triangle| std::array::from_fn(|i| model.verticies[triangle[i] as usize].position.map(f32::to_bits)))
+            .collect::<Vec<_>>();
+        triangles.sort();
+        triangles
+    }
+    ///8x8 grid of quads where every triangle has its own vertices, columns are written in an order bad for vertex cache
+    fn grid() -> Model<ModelVertex> {
+        let mut obj = String::new();
+        for y in 0..9 {
+            for x in 0..9 {
+                obj += &format!("v {} {} 0\n", x, y);
+            }
+        }
+        for x in 0..8 {
+            for y in 0..8 {
+                let i = y * 9 + x + 1;
+                obj += &format!("f {} {} {} {}\n", i, i + 1, i + 10, i + 9);
+            }
+        }
+        from_str(&obj).unwrap()
+    }
+    fn vertex(position: [f32; 3]) -> ModelVertex {
+        ModelVertex::new(position, [0.0, 1.0, 0.0], [0.0, 0.0])
+    }
+    #[test]
+    fn weld_keeps_triangles() {
+        let parsed = grid();
+        assert_eq!(parsed.verticies.len(), 8 * 8 * 6);
+        let mut model = parsed.clone();
+        model.weld(0.0);
+        assert_eq!(model.verticies.len(), 81);
+        assert_eq!(triangles(&model), triangles(&parsed));
+    }
+    #[test]
+    fn weld_exact() {
+        let vertices = vec![vertex([0.99999, 0.0, 0.0]), vertex([1.00001, 0.0, 0.0]), vertex([-0.0, 0.0, 0.0]), vertex([0.0, 0.0, 0.0])];
+        let mut model = Model::new(vertices, None);
+        model.weld(0.0);
+        //-0.0 and 0.0 are the same
+        assert_eq!((model.verticies.len(), model.indicies.as_deref()), (3, Some(&[0, 1, 2, 2][..])));
+    }
+    #[test]
+    fn weld_with_epsilon() {
+        //vertices on both sides of grid cell are merged, the first one is kept
+        let vertices = vec![vertex([0.99999, 0.0, 0.0]), vertex([1.00001, 0.0, 0.0]), vertex([1.1, 0.0, 0.0])];
+        let mut model = Model::new(vertices, None);
+        model.weld(0.001);
+        assert_eq!(model.indicies.as_deref(), Some(&[0, 0, 1][..]));
+        assert_eq!(model.verticies[0].position, [0.99999, 0.0, 0.0]);
+    }
+    #[test]
+    fn vertex_cache() {
+        let mut model = grid();
+        model.weld(0.0);
+        let welded_ratio = model.cache_miss_ratio(VERTEX_CACHE_SIZE);
+        model.optimize_vertex_cache(VERTEX_CACHE_SIZE);
+        let ratio = model.cache_miss_ratio(VERTEX_CACHE_SIZE);
+        assert!(ratio < welded_ratio, "{} {}", ratio, welded_ratio);
+        assert_eq!(triangles(&model), triangles(&grid()));
+    }
+    #[test]
+    fn vertex_fetch() {
+        let mut model = grid();
+        model.optimize(0.0);
+        let mut first_uses = Vec::new();
+        for &i in model.indicies.as_ref().unwrap() {
+            if !first_uses.contains(&i) {
+                first_uses.push(i);
+            }
+        }
+        assert_eq!(first_uses, (0..81).collect::<Vec<_>>());
+        assert_eq!(triangles(&model), triangles(&grid()));
+        //unused vertices are removed
+        let mut model = Model::new(vec![vertex([0.0; 3]), vertex([1.0; 3])], Some(vec![1, 1, 1]));
+        model.optimize_vertex_fetch();
+        assert_eq!((model.verticies.len(), model.indicies.as_deref()), (1, Some(&[0, 0, 0][..])));
+    }
+}
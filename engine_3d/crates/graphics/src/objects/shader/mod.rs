@@ -1,6 +1,8 @@
 use std::{collections::HashMap, ffi::CString, str::FromStr, sync::Arc};
 use math::*;
 
+pub mod preprocessor;
+
 
 use super::{
     buffers::{ Buffer, ShaderStorage},
@@ -77,6 +79,23 @@ impl Shader {
 }
 impl Shader {
     pub fn new<T: IntoIterator<Item = SubShader>>(subshaders: T) -> Self {
+        let (shader, error) = Self::link(subshaders);
+        if let Some(log) = error {
+            log::error!("shader link error:{}", log);
+        }
+        shader
+    }
+    ///Same as new, but link error is returned instead of being logged
+    pub fn try_new<T: IntoIterator<Item = SubShader>>(subshaders: T) -> Result<Self, String> {
+        match Self::link(subshaders) {
+            (shader, None) => Ok(shader),
+            (shader, Some(log)) => {
+                unsafe { gl::DeleteProgram(shader.id) };
+                Err(log)
+            }
+        }
+    }
+    fn link<T: IntoIterator<Item = SubShader>>(subshaders: T) -> (Self, Option<String>) {
         unsafe {
             let id = gl::CreateProgram();
             let mut iter = subshaders.into_iter();
@@ -86,6 +105,7 @@ impl Shader {
             gl::LinkProgram(id);
             let mut status = 0;
             gl::GetProgramiv(id, gl::LINK_STATUS, &mut status);
+            let mut error = None;
             if status == 0 {
                 let mut len = 0;
                 gl::GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut len);
@@ -94,7 +114,7 @@ impl Shader {
                 let log = std::ffi::CStr::from_bytes_with_nul_unchecked(&buff)
                     .to_str()
                     .unwrap();
-                log::error!("shader link error:{}", log);
+                error = Some(log.to_owned());
             }
             for subshader in iter {
                 gl::DetachShader(id, subshader.id);
@@ -133,10 +153,13 @@ impl Shader {
                 //println!("SUCCESS {} {}",name,loc);
                 uniforms.insert(name.to_string(), loc as i32);
             }
-            Self {
-                id,
-                uniforms:Arc::new(uniforms),
-            }
+            (
+                Self {
+                    id,
+                    uniforms: Arc::new(uniforms),
+                },
+                error,
+            )
         }
     }
     pub fn bind(&self) {
@@ -175,13 +198,33 @@ pub struct SubShader {
 }
 impl SubShader {
     pub fn new(source: &str, shader_type: ShaderType) -> Self {
+        let (subshader, error) = Self::compile(source, shader_type);
+        if let Some(log) = error {
+            log::error!("Failed to compile shader: {}", log);
+        }
+        subshader
+    }
+    ///Same as new, but compilation error is returned instead of being logged
+    pub fn try_new(source: &str, shader_type: ShaderType) -> Result<Self, String> {
+        match Self::compile(source, shader_type) {
+            (subshader, None) => Ok(subshader),
+            (subshader, Some(log)) => {
+                unsafe { gl::DeleteShader(subshader.id) };
+                Err(log)
+            }
+        }
+    }
+    fn compile(source: &str, shader_type: ShaderType) -> (Self, Option<String>) {
         unsafe {
             let id = gl::CreateShader(shader_type.into());
-            let cstring = std::ffi::CString::new(source).unwrap();
+            let Ok(cstring) = std::ffi::CString::new(source) else {
+                return (Self { id }, Some("shader source contains nul byte".to_owned()));
+            };
             gl::ShaderSource(id, 1, &cstring.as_ptr(), std::ptr::null());
             gl::CompileShader(id);
             let mut success = 0;
             gl::GetShaderiv(id, gl::COMPILE_STATUS, &mut success);
+            let mut error = None;
             if success == 0 {
                 let mut len = 0;
                 gl::GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut len);
@@ -196,9 +239,9 @@ impl SubShader {
                     .unwrap()
                     .to_str()
                     .unwrap();
-                log::error!("Failed to compile shader: {}", log);
+                error = Some(log.to_owned());
             }
-            Self { id }
+            (Self { id }, error)
         }
     }
 }
@@ -0,0 +1,207 @@
+use std::collections::HashSet;
+
+use super::{ShaderType, SubShader};
+
+///Line of included file that ended up in preprocessed source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineOrigin {
+    ///index in ShaderSource.files
+    pub file: usize,
+    ///starts with 1, as in compiler errors
+    pub line: u32,
+}
+///Shader source with resolved includes and injected defines
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShaderSource {
+    pub code: String,
+    ///every file that was read, first is the shader itself
+    pub files: Vec<String>,
+    ///origin of every line of code, None for injected defines
+    pub lines: Vec<Option<LineOrigin>>,
+}
+impl ShaderSource {
+    ///file and line that produced line of preprocessed code
+    pub fn origin(&self, line: u32) -> Option<(&str, u32)> {
+        let origin = (*self.lines.get((line as usize).checked_sub(1)?)?)?;
+        Some((&self.files[origin.file], origin.line))
+    }
+    ///Replaces line numbers of compiler log with files and lines they came from.
+    ///Understands "0:12(5)", "0(12)" and "0:12:" styles of common drivers
+    pub fn map_log(&self, log: &str) -> String {
+        log.lines()
+            .map(|line| match find_line_number(line) {
+                Some((range, number)) => match self.origin(number) {
+                    Some((file, number)) => {
+                        format!("{}{}:{}{}", &line[..range.start], file, number, &line[range.end..])
+                    }
+                    None => line.to_owned(),
+                },
+                None => line.to_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    ///Compiles source, errors are logged with lines of original files
+    pub fn compile(&self, shader_type: ShaderType) -> SubShader {
+        let (subshader, error) = SubShader::compile(&self.code, shader_type);
+        if let Some(log) = error {
+            log::error!("Failed to compile shader {}: {}", self.files[0], self.map_log(&log));
+        }
+        subshader
+    }
+    ///Same as compile, but error is returned instead of being logged
+    pub fn try_compile(&self, shader_type: ShaderType) -> Result<SubShader, String> {
+        SubShader::try_new(&self.code, shader_type).map_err(|log| self.map_log(&log))
+    }
+}
+///Finds "0:12", "0(12)" source string and line at start of log line or after "ERROR: "
+fn find_line_number(line: &str) -> Option<(std::ops::Range<usize>, u32)> {
+    let start = line.len() - line.trim_start_matches(|c: char| c.is_ascii_alphabetic() || c == ':' || c == ' ').len();
+    let rest = line[start..].strip_prefix('0')?;
+    let rest = rest.strip_prefix(':').or_else(|| rest.strip_prefix('('))?;
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let number = rest[..digits].parse().ok()?;
+    let mut end = start + 2 + digits;
+    if line[start..].starts_with("0(") {
+        end += line[end..].starts_with(')') as usize;
+    }
+    Some((start..end, number))
+}
+///Path of file included by file at from, "/" at start makes it relative to root instead of including file
+pub fn include_path(from: &str, include: &str) -> Option<String> {
+    let directory = match include.starts_with('/') {
+        true => "",
+        false => from.rsplit_once('/').map_or("", |(directory, _)| directory),
+    };
+    let mut components = Vec::new();
+    for component in directory.split('/').chain(include.split(['/', '\\'])) {
+        match component {
+            "" | "." => (),
+            ".." => {
+                components.pop()?;
+            }
+            component => components.push(component),
+        }
+    }
+    Some(components.join("/"))
+}
+///Resolves `#include "path"` directives and injects `#define`s after `#version`.
+///Every file is included once per shader, so included files don't need guards
+pub struct Preprocessor<'a> {
+    read: Box<dyn Fn(&str) -> Result<String, String> + 'a>,
+    defines: Vec<(String, String)>,
+}
+impl<'a> Preprocessor<'a> {
+    ///read returns content of file by path relative to root, see include_path
+    pub fn new(read: impl Fn(&str) -> Result<String, String> + 'a) -> Self {
+        Self {
+            read: Box::new(read),
+            defines: Vec::new(),
+        }
+    }
+    pub fn define(mut self, name: &str, value: impl ToString) -> Self {
+        self.defines.push((name.to_owned(), value.to_string()));
+        self
+    }
+    pub fn defines<K: AsRef<str>, V: ToString>(mut self, defines: impl IntoIterator<Item = (K, V)>) -> Self {
+        for (name, value) in defines {
+            self = self.define(name.as_ref(), value);
+        }
+        self
+    }
+    ///Reads and preprocesses shader at path
+    pub fn process(&self, path: &str) -> Result<ShaderSource, String> {
+        let source = (self.read)(path).map_err(|error| format!("can't read {}: {}", path, error))?;
+        self.process_source(path, &source)
+    }
+    ///Preprocesses shader that is already read, path is used for includes and error messages
+    pub fn process_source(&self, path: &str, source: &str) -> Result<ShaderSource, String> {
+        let mut output = ShaderSource::default();
+        let mut included = HashSet::new();
+        included.insert(path.to_owned());
+        let mut lines = source.lines().enumerate().peekable();
+        //#version must stay the first line
+        if let Some((_, line)) = lines.next_if(|(_, line)| line.trim_start().starts_with("#version")) {
+            push_line(&mut output, line, Some(LineOrigin { file: 0, line: 1 }));
+        }
+        for (name, value) in &self.defines {
+            push_line(&mut output, &format!("#define {} {}", name, value), None);
+        }
+        output.files.push(path.to_owned());
+        self.append(&mut output, &mut included, 0, lines)?;
+        Ok(output)
+    }
+    fn append<'s>(
+        &self,
+        output: &mut ShaderSource,
+        included: &mut HashSet<String>,
+        file: usize,
+        lines: impl Iterator<Item = (usize, &'s str)>,
+    ) -> Result<(), String> {
+        for (i, line) in lines {
+            let origin = LineOrigin { file, line: i as u32 + 1 };
+            let Some(directive) = line.trim_start().strip_prefix("#include") else {
+                push_line(output, line, Some(origin));
+                continue;
+            };
+            let location = format!("{}:{}", output.files[file], origin.line);
+            let include = directive
+                .trim()
+                .strip_prefix('"')
+                .and_then(|rest| rest.strip_suffix('"'))
+                .ok_or_else(|| format!("{}: expected #include \"path\"", location))?;
+            let path = include_path(&output.files[file], include)
+                .ok_or_else(|| format!("{}: \"{}\" is outside of root", location, include))?;
+            if !included.insert(path.clone()) {
+                continue;
+            }
+            let source = (self.read)(&path)
+                .map_err(|error| format!("{}: can't include \"{}\": {}", location, include, error))?;
+            output.files.push(path);
+            self.append(output, included, output.files.len() - 1, source.lines().enumerate())?;
+        }
+        Ok(())
+    }
+}
+fn push_line(output: &mut ShaderSource, line: &str, origin: Option<LineOrigin>) {
+    output.code.push_str(line);
+    output.code.push('\n');
+    output.lines.push(origin);
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn read(path: &str) -> Result<String, String> {
+        match path {
+            "shaders/main.frag" => Ok("#version 430\n#include \"lib/light.glsl\"\n#include \"/common.glsl\"\nvoid main(){}"),
+            "shaders/lib/light.glsl" => Ok("#include \"../../common.glsl\"\nfloat light(){ return PI; }"),
+            "common.glsl" => Ok("const float PI = 3.14;"),
+            "shaders/escape.frag" => Ok("#include \"../../secret.glsl\""),
+            "shaders/broken.frag" => Ok("\n#include <light.glsl>"),
+            _ => Err("not found".to_owned()),
+        }
+        .map(str::to_owned)
+    }
+    #[test]
+    fn includes_once() {
+        let source = Preprocessor::new(read).define("KERNEL", 5).process("shaders/main.frag").unwrap();
+        assert_eq!(source.code, "#version 430\n#define KERNEL 5\nconst float PI = 3.14;\nfloat light(){ return PI; }\nvoid main(){}\n");
+        assert_eq!(source.files, ["shaders/main.frag", "shaders/lib/light.glsl", "common.glsl"]);
+        assert_eq!(source.origin(1), Some(("shaders/main.frag", 1)));
+        assert_eq!(source.origin(2), None);
+        assert_eq!(source.origin(3), Some(("common.glsl", 1)));
+        assert_eq!(source.origin(4), Some(("shaders/lib/light.glsl", 2)));
+        assert_eq!(source.origin(5), Some(("shaders/main.frag", 4)));
+        assert_eq!(source.map_log("0:4(10): error: `PI' undeclared"), "shaders/lib/light.glsl:2(10): error: `PI' undeclared");
+    }
+    #[test]
+    fn errors() {
+        let preprocessor = Preprocessor::new(read);
+        assert_eq!(preprocessor.process("shaders/escape.frag").unwrap_err(), "shaders/escape.frag:1: \"../../secret.glsl\" is outside of root");
+        assert_eq!(preprocessor.process("shaders/broken.frag").unwrap_err(), "shaders/broken.frag:2: expected #include \"path\"");
+        assert_eq!(preprocessor.process("missing.frag").unwrap_err(), "can't read missing.frag: not found");
+        let error = preprocessor.process_source("a.frag", "#include \"b.glsl\"").unwrap_err();
+        assert_eq!(error, "a.frag:1: can't include \"b.glsl\": not found");
+        assert_eq!(include_path("a/b/c.glsl", "..\\d.glsl").as_deref(), Some("a/d.glsl"));
+    }
+}
@@ -29,6 +29,7 @@ pub struct Texture2DBuilder {
     texture_format: TextureFormat,
     internal_format: TextureFormat,
     texture_type: TextureDataType,
+    mip_levels: Option<Vec<MipLevel>>,
 }
 impl Texture2DBuilder {
     pub fn new() -> Self {
@@ -41,6 +42,7 @@ impl Texture2DBuilder {
             internal_format: TextureFormat::RGBA,
             texture_size: (0, 0),
             texture_type: TextureDataType::UnsignedByte,
+            mip_levels: None,
         }
     }
     pub fn texture_format(mut self, texture_format: TextureFormat) -> Self {
@@ -91,10 +93,22 @@ impl Texture2DBuilder {
         self.image = Some(image);
         self
     }
+    ///precomputed mip chain in internal_format, first level is the largest
+    pub fn mip_levels(mut self, mip_levels: Vec<MipLevel>) -> Self {
+        self.mip_levels = Some(mip_levels);
+        self
+    }
     pub fn build(mut self) -> Result<Texture2D, BuildError> {
-        if self.texture_size == (0, 0) && self.image == None {
+        if self.texture_size == (0, 0) && self.image == None && self.mip_levels.is_none() {
             return Err(BuildError {});
         }
+        if let Some(mip_levels) = self.mip_levels {
+            if mip_levels.is_empty() {
+                return Err(BuildError {});
+            }
+            self.texture.from_mip_levels(self.internal_format, &mip_levels);
+            return Ok(self.texture);
+        }
         match self.image {
             Some(img) => self.texture.from_image(self.internal_format, img),
             None => self.texture.finalize(
@@ -109,6 +123,93 @@ impl Texture2DBuilder {
         Ok(self.texture)
     }
 }
+///Single level of mip chain, images of every layer and cube face are stored one after another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MipLevel {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+///Highest level sampled from uploaded mip chain. None if only base level of larger image is given,
+///then max level stays at default so generated mipmaps are used
+pub fn max_mip_level(mip_levels: &[MipLevel]) -> Option<i32> {
+    match mip_levels {
+        [base] if base.width.max(base.height) > 1 => None,
+        _ => Some(mip_levels.len() as i32 - 1),
+    }
+}
+///Uploads images of mip levels to target, depth is count of layers for array textures.
+///Texture must be bound
+pub(crate) fn upload_mip_levels(
+    target: u32,
+    internal_format: TextureFormat,
+    mip_levels: &[MipLevel],
+    image_target: impl Fn(usize) -> u32,
+    images: usize,
+    depth: Option<i32>,
+) {
+    unsafe {
+        //rows of containers aren't padded
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        for (level, mip_level) in mip_levels.iter().enumerate() {
+            let image_size = mip_level.data.len() / images;
+            for image in 0..images {
+                let data = &mip_level.data[image * image_size..(image + 1) * image_size];
+                let (width, height) = (mip_level.width as i32, mip_level.height as i32);
+                let format = internal_format.into_glenum();
+                match (internal_format.pixel_transfer(), depth) {
+                    (Some((pixel_format, data_type)), None) => gl::TexImage2D(
+                        image_target(image),
+                        level as i32,
+                        format as i32,
+                        width,
+                        height,
+                        0,
+                        pixel_format.into_glenum(),
+                        data_type.into_glenum(),
+                        data.as_ptr() as *const _,
+                    ),
+                    (Some((pixel_format, data_type)), Some(depth)) => gl::TexImage3D(
+                        image_target(image),
+                        level as i32,
+                        format as i32,
+                        width,
+                        height,
+                        depth,
+                        0,
+                        pixel_format.into_glenum(),
+                        data_type.into_glenum(),
+                        data.as_ptr() as *const _,
+                    ),
+                    (None, None) => gl::CompressedTexImage2D(
+                        image_target(image),
+                        level as i32,
+                        format,
+                        width,
+                        height,
+                        0,
+                        data.len() as i32,
+                        data.as_ptr() as *const _,
+                    ),
+                    (None, Some(depth)) => gl::CompressedTexImage3D(
+                        image_target(image),
+                        level as i32,
+                        format,
+                        width,
+                        height,
+                        depth,
+                        0,
+                        data.len() as i32,
+                        data.as_ptr() as *const _,
+                    ),
+                }
+            }
+        }
+        //1000 is default of OpenGL
+        gl::TexParameteri(target, gl::TEXTURE_MAX_LEVEL, max_mip_level(mip_levels).unwrap_or(1000));
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+    }
+}
 #[derive(Debug, Clone, Copy)]
 pub struct BuildError {}
 impl Display for BuildError {
@@ -197,9 +298,11 @@ impl Texture2D {
             );
         }
     }
+    ///Generates every level from base level, also ones limited by uploaded mip chain
     pub fn gen_mipmaps(&self) {
         self.bind();
         unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 1000);
             gl::GenerateMipmap(gl::TEXTURE_2D);
         }
     }
@@ -319,6 +422,17 @@ impl Texture2D {
             );
         }
     }
+    ///Uploads precomputed mip chain, compressed formats are uploaded as is
+    pub fn from_mip_levels(&mut self, internal_format: TextureFormat, mip_levels: &[MipLevel]) {
+        let Some(base) = mip_levels.first() else {
+            return;
+        };
+        self.internal_format = internal_format;
+        self.width = base.width as i32;
+        self.height = base.height as i32;
+        self.bind();
+        upload_mip_levels(gl::TEXTURE_2D, internal_format, mip_levels, |_| gl::TEXTURE_2D, 1, None);
+    }
     pub fn finalize(
         &mut self,
         internal_format: TextureFormat,
@@ -440,6 +554,8 @@ pub enum Filter {
     NearestLinearMipMap,
     ///
     NearestMipMap,
+    /// Trilinear filter. Interpolates between pixels and between mipmaps
+    LinearMipMap,
 }
 impl Filter {
     pub fn to_param(&self) -> u32 {
@@ -448,6 +564,7 @@ impl Filter {
             Filter::Nearest => gl::NEAREST,
             Filter::NearestMipMap => gl::NEAREST_MIPMAP_NEAREST,
             Filter::NearestLinearMipMap => gl::NEAREST_MIPMAP_LINEAR,
+            Filter::LinearMipMap => gl::LINEAR_MIPMAP_LINEAR,
         }
     }
 }
@@ -493,7 +610,7 @@ impl TextureDataType {
     }
 }
 ///Defines format of image in which it will be stored
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TextureFormat {
     //unsigned integer
     RGBA,
@@ -528,6 +645,30 @@ pub enum TextureFormat {
     R11G11B10F,
     RGB9E5,
     RG16F,
+    RGBA32F,
+    R8,
+    RG8,
+    //formats of pixel data
+    Red,
+    RG,
+    //block compressed, every 4x4 block takes 8 (BC1, BC4) or 16 bytes
+    ///DXT1
+    Bc1,
+    Bc1Srgb,
+    ///DXT3
+    Bc2,
+    Bc2Srgb,
+    ///DXT5
+    Bc3,
+    Bc3Srgb,
+    ///single channel
+    Bc4,
+    ///two channels, used for normal maps
+    Bc5,
+    ///hdr colors without alpha
+    Bc6HUFloat,
+    Bc7,
+    Bc7Srgb,
 }
 impl TextureFormat {
     pub fn into_glenum(&self) -> u32 {
@@ -558,6 +699,22 @@ impl TextureFormat {
             TextureFormat::RGBA8SNorm => gl::RGBA8_SNORM,
             TextureFormat::SRGB => gl::SRGB,
             TextureFormat::Depth32FStencil8 => gl::DEPTH32F_STENCIL8,
+            TextureFormat::RGBA32F => gl::RGBA32F,
+            TextureFormat::R8 => gl::R8,
+            TextureFormat::RG8 => gl::RG8,
+            TextureFormat::Red => gl::RED,
+            TextureFormat::RG => gl::RG,
+            TextureFormat::Bc1 => gl::COMPRESSED_RGBA_S3TC_DXT1_EXT,
+            TextureFormat::Bc1Srgb => gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+            TextureFormat::Bc2 => gl::COMPRESSED_RGBA_S3TC_DXT3_EXT,
+            TextureFormat::Bc2Srgb => gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+            TextureFormat::Bc3 => gl::COMPRESSED_RGBA_S3TC_DXT5_EXT,
+            TextureFormat::Bc3Srgb => gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+            TextureFormat::Bc4 => gl::COMPRESSED_RED_RGTC1,
+            TextureFormat::Bc5 => gl::COMPRESSED_RG_RGTC2,
+            TextureFormat::Bc6HUFloat => gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT,
+            TextureFormat::Bc7 => gl::COMPRESSED_RGBA_BPTC_UNORM,
+            TextureFormat::Bc7Srgb => gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
         }
     }
     pub fn from_glenum(glenum: u32) -> Option<Self> {
@@ -588,9 +745,72 @@ impl TextureFormat {
             gl::RGBA8_SNORM => TextureFormat::RGBA8SNorm,
             gl::SRGB => TextureFormat::SRGB,
             gl::DEPTH32F_STENCIL8 => TextureFormat::Depth32FStencil8,
+            gl::RGBA32F => TextureFormat::RGBA32F,
+            gl::R8 => TextureFormat::R8,
+            gl::RG8 => TextureFormat::RG8,
+            gl::RED => TextureFormat::Red,
+            gl::RG => TextureFormat::RG,
+            gl::COMPRESSED_RGBA_S3TC_DXT1_EXT => TextureFormat::Bc1,
+            gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT => TextureFormat::Bc1Srgb,
+            gl::COMPRESSED_RGBA_S3TC_DXT3_EXT => TextureFormat::Bc2,
+            gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT => TextureFormat::Bc2Srgb,
+            gl::COMPRESSED_RGBA_S3TC_DXT5_EXT => TextureFormat::Bc3,
+            gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT => TextureFormat::Bc3Srgb,
+            gl::COMPRESSED_RED_RGTC1 => TextureFormat::Bc4,
+            gl::COMPRESSED_RG_RGTC2 => TextureFormat::Bc5,
+            gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT => TextureFormat::Bc6HUFloat,
+            gl::COMPRESSED_RGBA_BPTC_UNORM => TextureFormat::Bc7,
+            gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM => TextureFormat::Bc7Srgb,
+            _ => return None,
+        })
+    }
+    ///bytes in 4x4 block of block compressed format, None for other formats
+    pub fn block_bytes(&self) -> Option<usize> {
+        match self {
+            TextureFormat::Bc1 | TextureFormat::Bc1Srgb | TextureFormat::Bc4 => Some(8),
+            TextureFormat::Bc2
+            | TextureFormat::Bc2Srgb
+            | TextureFormat::Bc3
+            | TextureFormat::Bc3Srgb
+            | TextureFormat::Bc5
+            | TextureFormat::Bc6HUFloat
+            | TextureFormat::Bc7
+            | TextureFormat::Bc7Srgb => Some(16),
+            _ => None,
+        }
+    }
+    pub fn is_compressed(&self) -> bool {
+        self.block_bytes().is_some()
+    }
+    ///Format and type of pixel data uploaded into uncompressed internal format,
+    ///None for compressed and depth formats
+    pub fn pixel_transfer(&self) -> Option<(TextureFormat, TextureDataType)> {
+        Some(match self {
+            TextureFormat::RGBA | TextureFormat::RGBA8 | TextureFormat::SrgbA => {
+                (TextureFormat::RGBA, TextureDataType::UnsignedByte)
+            }
+            TextureFormat::R8 => (TextureFormat::Red, TextureDataType::UnsignedByte),
+            TextureFormat::RG8 => (TextureFormat::RG, TextureDataType::UnsignedByte),
+            TextureFormat::RGBA16F => (TextureFormat::RGBA, TextureDataType::HalfFloat),
+            TextureFormat::RGBA32F => (TextureFormat::RGBA, TextureDataType::Float),
             _ => return None,
         })
     }
+    ///Size in bytes of single image, None if format has no known size or size doesn't fit into usize
+    pub fn image_size(&self, width: u32, height: u32) -> Option<usize> {
+        let (width, height) = (width as usize, height as usize);
+        if let Some(block_bytes) = self.block_bytes() {
+            return width.div_ceil(4).max(1).checked_mul(height.div_ceil(4).max(1))?.checked_mul(block_bytes);
+        }
+        let pixel_bytes = match self.pixel_transfer()? {
+            (TextureFormat::Red, _) => 1,
+            (TextureFormat::RG, _) => 2,
+            (_, TextureDataType::HalfFloat) => 8,
+            (_, TextureDataType::Float) => 16,
+            _ => 4,
+        };
+        width.checked_mul(height)?.checked_mul(pixel_bytes)
+    }
     ///gives compatible texture format for given internal format
     pub fn to_texture_format<T: TextureTypeTrait>(&self) -> TextureFormat {
         let mut format = 0;
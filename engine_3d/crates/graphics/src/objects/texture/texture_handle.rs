@@ -10,7 +10,7 @@ use image::{DynamicImage, EncodableLayout, GenericImageView};
 use super::{
     texture_trait::{Texture1DTrait, Texture2DTrait, Texture3DTrait, TextureTrait},
     texture_type::*,
-    Filter, TextureDataType, TextureFormat, TextureWrap,
+    upload_mip_levels, Filter, MipLevel, TextureDataType, TextureFormat, TextureWrap,
 };
 /* pub struct Texture<T: TextureTypeTrait> {
     handle: AtomicRefCell<TextureHandle<T>>,
@@ -217,6 +217,18 @@ impl TextureHandle<CubeMapTexture> {
             }
         }
     }
+    ///Uploads precomputed mip chain, every level has 6 faces in order Right, Left, Top, Bottom, Front, Back
+    pub fn set_mip_levels(&mut self, internal_format: TextureFormat, mip_levels: &[MipLevel]) {
+        let Some(base) = mip_levels.first() else {
+            return;
+        };
+        self.bind();
+        self.internal_format = internal_format;
+        self.width = base.width as i32;
+        self.height = base.height as i32;
+        let face = |i: usize| gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32;
+        upload_mip_levels(gl::TEXTURE_CUBE_MAP, internal_format, mip_levels, face, 6, None);
+    }
     /// The correct order for textures is: Right, Left, Top, Bottom, Front, Back
     pub fn set_images(&self, internal_format: TextureFormat, images: [DynamicImage; 6]) {
         self.bind();
@@ -244,6 +256,22 @@ impl TextureHandle<CubeMapTexture> {
     }
 }
 
+impl TextureHandle<TextureArray2D> {
+    ///Uploads precomputed mip chain, every level has images of all layers one after another
+    pub fn set_mip_levels(&mut self, internal_format: TextureFormat, layers: i32, mip_levels: &[MipLevel]) {
+        let Some(base) = mip_levels.first() else {
+            return;
+        };
+        self.bind();
+        self.internal_format = internal_format;
+        self.width = base.width as i32;
+        self.height = base.height as i32;
+        self.depth = layers;
+        let target = gl::TEXTURE_2D_ARRAY;
+        upload_mip_levels(target, internal_format, mip_levels, |_| target, 1, Some(layers));
+    }
+}
+
 impl<T> Texture1DTrait for TextureHandle<T>
 where
     T: D1 + TextureTypeTrait,
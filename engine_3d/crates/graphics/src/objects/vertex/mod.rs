@@ -1,9 +1,34 @@
-use math::Mat4;
+use math::{vec3, Mat4, Vec3};
 use gl;
 
 use super::buffers::{Buffer, Data};
 pub trait Vertex: Sized {
     fn declaration();
+    ///appends every component of every attribute in declaration order, used by mesh processing
+    fn components(&self, components: &mut Vec<f32>);
+    ///first 4 components of attribute with this name as f32, missing components are 0.
+    ///None if vertex has no such attribute
+    fn attribute(&self, name: &str) -> Option<[f32; 4]>;
+    ///sets as many components as attribute has, does nothing if vertex has no such attribute
+    fn set_attribute(&mut self, name: &str, value: [f32; 4]);
+    ///attribute named position or pos
+    fn position(&self) -> Option<Vec3> {
+        self.attribute("position")
+            .or_else(|| self.attribute("pos"))
+            .map(|[x, y, z, _]| vec3(x, y, z))
+    }
+    fn set_position(&mut self, position: Vec3) {
+        for name in ["position", "pos"] {
+            self.set_attribute(name, position.extend(0.0).into());
+        }
+    }
+    ///attribute named normal
+    fn normal(&self) -> Option<Vec3> {
+        self.attribute("normal").map(|[x, y, z, _]| vec3(x, y, z))
+    }
+    fn set_normal(&mut self, normal: Vec3) {
+        self.set_attribute("normal", normal.extend(0.0).into());
+    }
 }
 #[allow(unused)]
 pub trait IntoGLenum {
@@ -87,6 +112,22 @@ macro_rules! define_vertex {
                     )*
                 }
             }
+            fn components(&self, components: &mut Vec<f32>){
+                $(components.extend(self.$var_name.iter().map(|value| *value as f32));)*
+            }
+            fn attribute(&self, name: &str) -> Option<[f32; 4]>{
+                $(if stringify!($var_name) == name {
+                    return Some(std::array::from_fn(|i| self.$var_name.get(i).map_or(0.0, |value| *value as f32)));
+                })*
+                None
+            }
+            fn set_attribute(&mut self, name: &str, value: [f32; 4]){
+                $(if stringify!($var_name) == name {
+                    for (component, value) in self.$var_name.iter_mut().zip(value) {
+                        *component = value as $type;
+                    }
+                })*
+            }
         }
         #[allow(dead_code,unused_assignments)]
         impl $name{
@@ -119,8 +160,42 @@ define_vertex!(
     f32,
     2
 );
+//ModelVertex with tangent for normal mapping, w of tangent is sign of bitangent
+define_vertex!(
+    TangentVertex,
+    position,
+    f32,
+    3,
+    normal,
+    f32,
+    3,
+    texture_coords,
+    f32,
+    2,
+    tangent,
+    f32,
+    4
+);
 #[test]
 pub fn test() {
     ModelVertex::debug_decl();
     println!("{}", std::mem::offset_of!(ModelVertex, texture_coords));
 }
+#[test]
+pub fn attributes() {
+    let mut vertex = TangentVertex::new([1.0, 2.0, 3.0], [0.0, 1.0, 0.0], [0.5, 0.25], [1.0, 0.0, 0.0, -1.0]);
+    assert_eq!(vertex.position(), Some(vec3(1.0, 2.0, 3.0)));
+    assert_eq!(vertex.attribute("tangent"), Some([1.0, 0.0, 0.0, -1.0]));
+    //missing components are 0
+    assert_eq!(vertex.attribute("texture_coords"), Some([0.5, 0.25, 0.0, 0.0]));
+    assert_eq!(vertex.attribute("color"), None);
+    vertex.set_position(vec3(4.0, 5.0, 6.0));
+    vertex.set_normal(Vec3::X);
+    vertex.set_attribute("texture_coords", [1.0, 2.0, 3.0, 4.0]);
+    assert_eq!((vertex.position, vertex.normal, vertex.texture_coords), ([4.0, 5.0, 6.0], [1.0, 0.0, 0.0], [1.0, 2.0]));
+    //position can be called pos
+    let mut simple = crate::objects::model::primitives::SimpleVertex::new([1.0, 2.0, 3.0]);
+    assert_eq!((simple.position(), simple.normal()), (Some(vec3(1.0, 2.0, 3.0)), None));
+    simple.set_position(Vec3::ONE);
+    assert_eq!(simple.pos, [1.0; 3]);
+}
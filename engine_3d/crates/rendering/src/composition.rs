@@ -1,9 +1,12 @@
 use specs::{Read, ReadStorage, System, WriteStorage};
 
-use crate::camera::{Camera, MainCamera};
+use crate::{
+    camera::{Camera, MainCamera},
+    shader_sources::embedded_subshader,
+};
 use graphics::objects::{
     buffers::{Framebuffer, FramebufferAttachment},
-    shader::{Shader, ShaderType, SubShader},
+    shader::{Shader, ShaderType},
     texture::Filter,
 };
 use graphics::resize_event::ResizeEvent;
@@ -16,7 +19,7 @@ pub struct CompositionSystem {
 impl CompositionSystem {
     pub fn new() -> Self {
         let shader = Shader::new([
-            SubShader::new(include_str!("shaders/copy.glsl"), ShaderType::Fragment),
+            embedded_subshader("shaders/copy.glsl", ShaderType::Fragment),
             *FULLSCREENPASS_VERTEX_SHADER,
         ]);
         Self { shader }
@@ -5,4 +5,5 @@ pub mod render_path;
 pub mod render_system;
 pub mod camera;
 pub mod light;
-pub mod material;
\ No newline at end of file
+pub mod material;
+pub mod shader_sources;
\ No newline at end of file
@@ -8,6 +8,7 @@ use crate::{
     material::Material,
     mesh_renderer::MeshRenderer,
     render_path::RenderPath,
+    shader_sources::ReloadableShader,
 };
 use graphics::{
     draw_options::{
@@ -20,16 +21,15 @@ use graphics::{
     impl_data_type,
     objects::{
         buffers::{Buffer, ClearFlags, Data, Framebuffer, FramebufferAttachment, ShaderStorage},
-        model::{InstancedModel, Model, from_str, primitives::SimpleVertex},
-        shader::{Shader, ShaderType, SubShader},
+        model::{InstancedModel, Model, primitives::{icosphere, SimpleVertex}},
+        shader::ShaderType,
         texture::{Filter, Texture2DBuilder, TextureDataType, TextureFormat},
-        vertex::ModelVertex,
         viewport::Viewport,
     },
 };
 pub struct ForwardPath {
     out_framebuffer: Framebuffer,
-    shader: Shader,
+    shader: ReloadableShader,
     depth_prepass: bool,
     light_props: Buffer<ShaderStorage>,
 }
@@ -50,15 +50,9 @@ impl ForwardPath {
             .filter(Filter::Nearest);
         let _ =
             out_framebuffer.create_attachment(FramebufferAttachment::Depth, depth_texture.clone());
-        let shader = Shader::new([
-            SubShader::new(
-                include_str!("./shaders/forward_vert.glsl"),
-                ShaderType::Vertex,
-            ),
-            SubShader::new(
-                include_str!("./shaders/forward_frag.glsl"),
-                ShaderType::Fragment,
-            ),
+        let shader = ReloadableShader::embedded(&[
+            ("shaders/forward_vert.glsl", ShaderType::Vertex),
+            ("shaders/forward_frag.glsl", ShaderType::Fragment),
         ]);
         let light_props = Buffer::create();
         Self {
@@ -110,7 +104,7 @@ impl RenderPath for ForwardPath {
             });
         }
         self.light_props.set_data(&lights);
-        let mut shader_h = &self.shader;
+        let mut shader_h = self.shader.shader();
         shader_h.bind();
         shader_h.set_shader_storage_block("lights", &self.light_props, 1);
         //depth pass
@@ -127,13 +121,14 @@ impl RenderPath for ForwardPath {
             depth::set_write(false);
             color_mask::set_write(true, true, true, true);
         }
-        let mut shader = &self.shader;
+        let mut shader = self.shader.shader();
         for (mesh_renderer, transform, material) in (&mut *models, transforms, materials).join() {
             //TOOD:finish
             shader_h.set_matrix4("projection", &projection.get_projection());
             shader.set_matrix4("view", &view_mat);
             shader_h.set_matrix4("transformation", &transform.get_matrix());
             shader.set_texture2d("main_texture", &material.main_texture, 1);
+            shader.set_texture2d("specular_map", &material.specular_map, 2);
             shader.set_vec3("color", &material.color);
             shader.set_f32("specular", material.specular);
             shader.set_vec3("camera_position", &camera_transform.position);
@@ -150,18 +145,21 @@ impl RenderPath for ForwardPath {
     fn framebuffer(&self) -> &Framebuffer {
         &self.out_framebuffer
     }
+    fn shaders_mut(&mut self) -> Vec<&mut ReloadableShader> {
+        vec![&mut self.shader]
+    }
 }
 pub struct DeferredPath {
     out_framebuffer: Framebuffer,
     g_buffer: Framebuffer,
-    geometry_pass: Shader,
+    geometry_pass: ReloadableShader,
 
-    point_light_pass: Shader,
+    point_light_pass: ReloadableShader,
     point_light_props: Buffer<ShaderStorage>,
     point_light_volume: InstancedModel,
 
-    sun_light_pass: Shader,
-    ambient_light_pass: Shader,
+    sun_light_pass: ReloadableShader,
+    ambient_light_pass: ReloadableShader,
     ambient_color: Vec3,
 }
 impl DeferredPath {
@@ -210,49 +208,31 @@ impl DeferredPath {
         );
         g_buffer.add_attachment(FramebufferAttachment::DepthStencil, depth);
 
-        let point_light_pass = Shader::new([
-            SubShader::new(
-                include_str!("./shaders/deferred_shading_vert.glsl"),
-                ShaderType::Vertex,
-            ),
-            SubShader::new(
-                include_str!("./shaders/deferred_shading_point_frag.glsl"),
-                ShaderType::Fragment,
-            ),
+        let point_light_pass = ReloadableShader::embedded(&[
+            ("shaders/deferred_shading_vert.glsl", ShaderType::Vertex),
+            ("shaders/deferred_shading_point_frag.glsl", ShaderType::Fragment),
         ]);
-        let sun_light_pass = Shader::new([
-            *FULLSCREENPASS_VERTEX_SHADER,
-            SubShader::new(
-                include_str!("./shaders/deferred_shading_sun_frag.glsl"),
-                ShaderType::Fragment,
-            ),
+        //opt_vert.glsl is the same as FULLSCREENPASS_VERTEX_SHADER
+        let sun_light_pass = ReloadableShader::embedded(&[
+            ("shaders/opt_vert.glsl", ShaderType::Vertex),
+            ("shaders/deferred_shading_sun_frag.glsl", ShaderType::Fragment),
         ]);
-        let ambient_light_pass = Shader::new([
-            *FULLSCREENPASS_VERTEX_SHADER,
-            SubShader::new(
-                include_str!("./shaders/deferred_shading_ambient_frag.glsl"),
-                ShaderType::Fragment,
-            ),
+        let ambient_light_pass = ReloadableShader::embedded(&[
+            ("shaders/opt_vert.glsl", ShaderType::Vertex),
+            ("shaders/deferred_shading_ambient_frag.glsl", ShaderType::Fragment),
         ]);
-        let geometry_pass = Shader::new([
-            SubShader::new(
-                include_str!("./shaders/gbuffer_vert.glsl"),
-                ShaderType::Vertex,
-            ),
-            SubShader::new(
-                include_str!("./shaders/gbuffer_frag.glsl"),
-                ShaderType::Fragment,
-            ),
+        let geometry_pass = ReloadableShader::embedded(&[
+            ("shaders/gbuffer_vert.glsl", ShaderType::Vertex),
+            ("shaders/gbuffer_frag.glsl", ShaderType::Fragment),
         ]);
 
-        let point_light_volume: Model<ModelVertex> =
-            from_str(include_str!("./icosahedron.obj")).unwrap();
+        let point_light_volume = icosphere(1.0, 2);
         let mut verticies = Vec::new();
-        for vert in point_light_volume.verticies.iter() {
+        for vert in point_light_volume.verticies().iter() {
             verticies.push(SimpleVertex::new(vert.position));
         }
         let point_light_volume =
-            Model::new(verticies, point_light_volume.indicies.clone()).instantiate();
+            Model::new(verticies, point_light_volume.indicies().map(<[u32]>::to_vec)).instantiate();
         let point_light_props = Buffer::<ShaderStorage>::create();
         Self {
             out_framebuffer,
@@ -291,13 +271,14 @@ impl RenderPath for DeferredPath {
         face_culling::enable();
         face_culling::set_cullface(CullFace::Front);
         face_culling::set_frontface_order(FrontFaceOrder::Clockwise);
-        let mut geometry_pass = &self.geometry_pass;
+        let mut geometry_pass = self.geometry_pass.shader();
         geometry_pass.bind();
         for (mesh_renderer, transform, material) in (&mut *models, transforms, materials).join() {
             geometry_pass.set_matrix4("projection", &projection.get_projection());
             geometry_pass.set_matrix4("view", &view_mat);
             geometry_pass.set_matrix4("transformation", &transform.get_matrix());
             geometry_pass.set_texture2d("main_texture", &material.main_texture, 1);
+            geometry_pass.set_texture2d("specular_map", &material.specular_map, 2);
             geometry_pass.set_vec3("color", &material.color);
             geometry_pass.set_f32("specular", material.specular);
             geometry_pass.set_f32("shininess", material.shininess);
@@ -326,7 +307,7 @@ impl RenderPath for DeferredPath {
             .g_buffer
             .attachment_texture(FramebufferAttachment::Color(1))
             .unwrap();
-        let mut point_light_pass = &self.point_light_pass;
+        let mut point_light_pass = self.point_light_pass.shader();
         point_light_pass.bind();
         point_light_pass.set_texture2d("position", &position, 0);
         point_light_pass.set_texture2d("normal", &normal, 1);
@@ -433,7 +414,7 @@ impl RenderPath for DeferredPath {
         stencil::disable();
         depth::disable();
         if let Some(direction) = sun.direction() {
-            let mut sun_light_pass = &self.sun_light_pass;
+            let mut sun_light_pass = self.sun_light_pass.shader();
             sun_light_pass.bind();
             sun_light_pass.set_texture2d("position", &position, 0);
             sun_light_pass.set_texture2d("normal", &normal, 1);
@@ -447,7 +428,7 @@ impl RenderPath for DeferredPath {
             );
             EMPTY.draw();
         }
-        let mut ambient_light_pass = &self.ambient_light_pass;
+        let mut ambient_light_pass = self.ambient_light_pass.shader();
         ambient_light_pass.bind();
         ambient_light_pass.set_vec3("ambient", &self.ambient_color);
         ambient_light_pass.set_texture2d("color_spec", &color_spec, 2);
@@ -464,10 +445,18 @@ impl RenderPath for DeferredPath {
     fn framebuffer(&self) -> &Framebuffer {
         &self.out_framebuffer
     }
+    fn shaders_mut(&mut self) -> Vec<&mut ReloadableShader> {
+        vec![
+            &mut self.geometry_pass,
+            &mut self.point_light_pass,
+            &mut self.sun_light_pass,
+            &mut self.ambient_light_pass,
+        ]
+    }
 }
 use graphics::objects::buffers::DataType;
 
-use graphics::utils::{EMPTY, FULLSCREENPASS_VERTEX_SHADER};
+use graphics::utils::EMPTY;
 impl_data_type!(
     LightProps,
     transf,
@@ -7,6 +7,8 @@ use graphics::objects::texture::Texture2D;
 pub struct Material {
     pub color: Vec3,
     pub main_texture:Texture2D,
+    ///red channel multiplies specular
+    pub specular_map:Texture2D,
     pub specular: f32,
     pub shininess:f32,
     pub transparent:bool,
@@ -16,6 +18,7 @@ impl Default for Material {
         Self {
             color: Vec3::new(1.0, 1.0, 1.0), //White
             main_texture:Texture2D::white(),
+            specular_map:Texture2D::white(),
             specular: 0.5,
             shininess:32.0,
             transparent:false,
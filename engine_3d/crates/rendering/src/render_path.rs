@@ -3,7 +3,7 @@ use math::Mat4;
 use specs::{Read, ReadStorage, WriteStorage};
 use transform::Transform;
 
-use crate::{camera::{projection::Projection, CameraTransform, ViewFrustum}, light::{Light, Sun}, material::Material, mesh_renderer::MeshRenderer};
+use crate::{camera::{projection::Projection, CameraTransform, ViewFrustum}, light::{Light, Sun}, material::Material, mesh_renderer::MeshRenderer, shader_sources::ReloadableShader};
 
 
 pub trait RenderPath {
@@ -21,4 +21,8 @@ pub trait RenderPath {
     );
     fn resize(&mut self, viewport: Viewport);
     fn framebuffer(&self) -> &Framebuffer;
+    ///Shaders hot reloading can replace, none by default
+    fn shaders_mut(&mut self) -> Vec<&mut ReloadableShader> {
+        Vec::new()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+use graphics::objects::shader::{
+    preprocessor::{Preprocessor, ShaderSource},
+    Shader, ShaderType, SubShader,
+};
+
+macro_rules! embed {
+    ($($path:literal),* $(,)?) => {
+        &[$(($path, include_str!($path))),*]
+    };
+}
+///Shaders of rendering crate by their path relative to src, so assets can include them
+pub static EMBEDDED_SHADERS: &[(&str, &str)] = embed!(
+    "shaders/common/gbuffer.glsl",
+    "shaders/copy.glsl",
+    "shaders/deferred_shading_ambient_frag.glsl",
+    "shaders/deferred_shading_point_frag.glsl",
+    "shaders/deferred_shading_sun_frag.glsl",
+    "shaders/deferred_shading_vert.glsl",
+    "shaders/depth_pass_vert.glsl",
+    "shaders/empty_frag.glsl",
+    "shaders/forward_frag.glsl",
+    "shaders/forward_vert.glsl",
+    "shaders/gbuffer_frag.glsl",
+    "shaders/gbuffer_vert.glsl",
+    "shaders/opt_vert.glsl",
+    "shaders/screen_vert.glsl",
+);
+pub fn embedded_shader(path: &str) -> Option<&'static str> {
+    EMBEDDED_SHADERS
+        .iter()
+        .find(|(embedded, _)| *embedded == path)
+        .map(|(_, source)| *source)
+}
+///Preprocessor that includes only embedded shaders
+pub fn embedded_preprocessor() -> Preprocessor<'static> {
+    Preprocessor::new(|path| {
+        embedded_shader(path)
+            .map(str::to_owned)
+            .ok_or_else(|| "no such embedded shader".to_owned())
+    })
+}
+///Preprocesses and compiles embedded shader, compile errors are logged like in SubShader::new
+pub(crate) fn embedded_subshader(path: &str, shader_type: ShaderType) -> SubShader {
+    //includes of embedded shaders are known at compile time
+    embedded_preprocessor().process(path).unwrap().compile(shader_type)
+}
+///Shader program linked from embedded shaders. Remembers every file its stages were made of,
+///so it can be built again when one of them changes, see RenderPath::shaders_mut
+#[derive(Debug, Clone)]
+pub struct ReloadableShader {
+    shader: Shader,
+    stages: Vec<(String, ShaderType)>,
+    ///stages and every file they include
+    files: Vec<String>,
+}
+impl ReloadableShader {
+    ///Compile and link errors are logged, like in embedded_subshader
+    pub fn embedded(stages: &[(&str, ShaderType)]) -> Self {
+        let sources = stages
+            .iter()
+            .map(|(path, _)| embedded_preprocessor().process(path).unwrap())
+            .collect::<Vec<_>>();
+        let shader = Shader::new(sources.iter().zip(stages).map(|(source, (_, shader_type))| source.compile(*shader_type)));
+        Self {
+            shader,
+            stages: stages.iter().map(|(path, shader_type)| (path.to_string(), *shader_type)).collect(),
+            files: sources.into_iter().flat_map(|source| source.files).collect(),
+        }
+    }
+    pub fn shader(&self) -> &Shader {
+        &self.shader
+    }
+    ///true if file at path is one of stages or is included by them
+    pub fn uses(&self, path: &str) -> bool {
+        self.files.iter().any(|file| file == path)
+    }
+    ///Builds shader again from sources returned by preprocess, previous shader is kept if anything fails
+    pub fn reload(&mut self, mut preprocess: impl FnMut(&str) -> Result<ShaderSource, String>) -> Result<(), String> {
+        let mut subshaders = Vec::new();
+        let mut files = Vec::new();
+        for (path, shader_type) in &self.stages {
+            let source = preprocess(path)?;
+            subshaders.push(source.try_compile(*shader_type)?);
+            files.extend(source.files);
+        }
+        self.shader = Shader::try_new(subshaders)?;
+        self.files = files;
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+//helpers for reading g-buffer in deferred shading passes
+vec3 decode_normal(vec2 enc) {
+    const float kPI = 3.1415926536;
+    vec2 ang = enc * 2 - 1;
+    vec2 scth = vec2(sin(ang.x * kPI), cos(ang.x * kPI));
+    vec2 scphi = vec2(sqrt(1.0 - ang.y * ang.y), ang.y);
+    return vec3(scth.y * scphi.x, scth.x * scphi.x, scphi.y);
+}
+//inv_vp is inverse of projection * view
+vec3 depth_to_world_pos(float depth, vec2 uv, mat4 inv_vp) {
+    float z = depth * 2.0 - 1.0;
+
+    vec4 clipSpacePosition = vec4(uv * 2.0 - 1.0, z, 1.0);
+    vec4 worldSpacePosition = inv_vp * clipSpacePosition;
+    worldSpacePosition /= worldSpacePosition.w;
+
+    return worldSpacePosition.xyz;
+}
@@ -25,40 +25,14 @@ LightProp light_props[];
 
 out vec4 frag_color;
 
-vec3 decode_normal(vec2 enc) {
-    const float kPI = 3.1415926536;
-    vec2 ang = enc * 2 - 1;
-    vec2 scth = vec2(sin(ang.x * kPI), cos(ang.x * kPI));
-    vec2 scphi = vec2(sqrt(1.0 - ang.y * ang.y), ang.y);
-    return vec3(scth.y * scphi.x, scth.x * scphi.x, scphi.y);
-
-        /* vec2 fenc = enc*4.0-2.0;
-        float f = dot(fenc,fenc);
-        float g = sqrt(1.0-f/4.0);
-        vec3 n;
-        n.xy = fenc*g;
-        n.z = 1.0-f/2.0;
-        return n; */
-}
-vec3 depth_to_world_pos(float depth, vec2 uv) {
-    float z = depth * 2.0 - 1.0;
-
-    vec4 clipSpacePosition = vec4(uv * 2.0 - 1.0, z, 1.0);
-    vec4 worldSpacePosition = inv_proj * clipSpacePosition;
-
-            // Perspective division
-    worldSpacePosition /= worldSpacePosition.w;
-    worldSpacePosition = inv_view * worldSpacePosition;
-
-    return worldSpacePosition.xyz;
-}
+#include "common/gbuffer.glsl"
 void main() {
     vec2 uv = (texture_coordinates.xy) / texture_coordinates.w * 0.5 + 0.5;
             //float proj_a = clip_far / (clip_far - clip_near);
             //float proj_b = (-clip_far * clip_near) / (clip_far - clip_near);
             //float depth = texture(position,uv).r * 2.0 - 1.0;
 
-    vec3 frag_position = depth_to_world_pos(texture(position, uv).r, uv);//texture(position,uv).rgb;
+    vec3 frag_position = depth_to_world_pos(texture(position, uv).r, uv, inv_view * inv_proj);//texture(position,uv).rgb;
             //vec3 frag_normal = texture(normal,uv).rgb;
 
             //read from textures
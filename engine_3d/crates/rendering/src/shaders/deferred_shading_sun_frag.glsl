@@ -13,6 +13,8 @@ uniform vec3 light_color;
 uniform vec3 light_direction;
 out vec4 frag_color;
 
+#include "common/gbuffer.glsl"
+
 float calc_diffuse(in vec3 light_dir,in vec3 normal){
     return max(dot(normal,light_dir),0.0);
 }
@@ -21,19 +23,10 @@ float calc_specular(in vec3 view_dir,in vec3 light_dir,in vec3 normal,in float s
     vec3 half_dir = normalize(light_dir + view_dir);
     return specular * pow(clamp(dot(normal,half_dir),0.0,1.0),shininess);
 }
-vec3 depth_to_world_pos(float depth,vec2 uv){
-    float z = depth * 2.0 - 1.0;
-
-    vec4 clipSpacePosition = vec4(uv * 2.0 -1.0, z, 1.0);
-    vec4 worldSpacePosition = inv_vp * clipSpacePosition;
-    worldSpacePosition /= worldSpacePosition.w;
-
-    return worldSpacePosition.xyz;
-}
 void main(){
     vec2 uv = texture_coordinates;//(texture_coordinates.xy)/texture_coordinates.w*0.5 + 0.5;
 
-    vec3 frag_position = depth_to_world_pos(texture(position,uv).r,uv);
+    vec3 frag_position = depth_to_world_pos(texture(position,uv).r,uv,inv_vp);
 
     //read from textures
     vec3 normal_shininess = texture(normal,uv).rgb;
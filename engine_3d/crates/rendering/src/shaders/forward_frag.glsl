@@ -2,6 +2,7 @@
 
 uniform vec3 color;
 uniform sampler2D main_texture;
+uniform sampler2D specular_map;
 uniform vec3 camera_position;
 uniform float ambient;
 uniform float specular;
@@ -36,6 +37,7 @@ float calc_specular(in vec3 view_dir,in vec3 light_dir,in vec3 normal,in float s
 void main() {
     vec3 norm = normalize(normal);
     vec3 object_color = texture(main_texture,texture_coordinates).rgb * color;
+    float object_specular = specular * texture(specular_map,texture_coordinates).r;
 
     vec3 ambient_result = ambient * vec3(1.0);
     vec3 result = ambient_result;     
@@ -49,7 +51,7 @@ void main() {
         vec3 diffuse_result = vec3(calc_diffuse(light_direction,norm)) ;
         
         vec3 view_direction = normalize(camera_position - position);
-        vec3 specular_result = vec3(calc_specular(view_direction,light_direction,norm,specular,shininess));
+        vec3 specular_result = vec3(calc_specular(view_direction,light_direction,norm,object_specular,shininess));
 
         result += ( diffuse_result + specular_result ) * (light_props[i].light_color.rgb*attenuation)* object_color;   
     }
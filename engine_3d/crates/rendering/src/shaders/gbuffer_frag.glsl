@@ -4,6 +4,7 @@ in vec2 v_uv;
 
 uniform vec3 color;
 uniform sampler2D main_texture;
+uniform sampler2D specular_map;
 uniform float specular;
 uniform float shininess;
 
@@ -16,5 +17,5 @@ void main(){
     //normal = normalize(v_normal)*0.5+0.5;
     normal = vec4(v_normal,shininess/256.0-0.5);
     //normal = vec4(encode_normal(v_normal),shininess/1024.0,0.0);
-    ColorSpec = vec4(color*texture(main_texture,v_uv).rgb,specular);
+    ColorSpec = vec4(color*texture(main_texture,v_uv).rgb,specular*texture(specular_map,v_uv).r);
 }
\ No newline at end of file
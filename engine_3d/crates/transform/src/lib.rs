@@ -162,6 +162,12 @@ impl Transform {
             ..Default::default()
         }
     }
+    ///Decomposes matrix, shear of non-uniformly scaled hierarchies is lost
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, position) = matrix.to_scale_rotation_translation();
+        let (x, y, z) = rotation.to_euler(EulerRot::XYZ);
+        Self::new_nonuniform_scale(position, Vec3::new(x.to_degrees(), y.to_degrees(), z.to_degrees()), scale)
+    }
     #[inline]
     pub fn rotation(&self) -> Vec3 {
         self.rotation
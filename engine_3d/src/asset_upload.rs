@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use assets::AssetServer;
+use specs::*;
+
+///Finishes assets loaded with AssetServer::load_async: uploads textures, instantiates models.
+///Must be thread local, GL objects are created with GL context
+pub struct AssetUploadSystem {
+    budget: Duration,
+}
+impl AssetUploadSystem {
+    ///budget is time per frame spent on uploads, at least one asset is uploaded every frame
+    pub fn new(budget: Duration) -> Self {
+        Self { budget }
+    }
+}
+///4ms per frame
+impl Default for AssetUploadSystem {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(4))
+    }
+}
+impl<'a> System<'a> for AssetUploadSystem {
+    type SystemData = WriteExpect<'a, AssetServer>;
+
+    fn run(&mut self, mut server: Self::SystemData) {
+        server.finish_loads(self.budget);
+    }
+}
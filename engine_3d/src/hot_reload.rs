@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use assets::{meta_path, normalize_path, shader_importer::preprocess_shader, AssetServer, AssetWatcher};
+use graphics::objects::{
+    model::{InstancedModel, Model},
+    shader::{Shader, ShaderType},
+    texture::Texture2D,
+    vertex::ModelVertex,
+};
+use rendering::{camera::Camera, material::Material, mesh_renderer::MeshRenderer};
+use specs::*;
+
+///Paths assets of entity were loaded from, only entities with it are hot reloaded
+#[derive(Debug, Clone, Default)]
+pub struct AssetSources {
+    ///replaces Material.main_texture
+    pub main_texture: Option<String>,
+    ///replaces MeshRenderer.model
+    pub model: Option<String>,
+    ///vertex and fragment shader, replace MeshRenderer.shader
+    pub shader: Option<(String, String)>,
+}
+impl AssetSources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn main_texture(mut self, path: &str) -> Self {
+        self.main_texture = Some(path.to_owned());
+        self
+    }
+    pub fn model(mut self, path: &str) -> Self {
+        self.model = Some(path.to_owned());
+        self
+    }
+    pub fn shader(mut self, vertex: &str, fragment: &str) -> Self {
+        self.shader = Some((vertex.to_owned(), fragment.to_owned()));
+        self
+    }
+}
+impl Component for AssetSources {
+    type Storage = DenseVecStorage<Self>;
+}
+///Reimports files changed in watched directory and swaps them into components.
+///Shaders of camera render paths are reloaded too, shaders are reloaded when any file they include changes.
+///Must be thread local, textures, models and shaders are created with GL context.
+///Files that fail to load are logged and components keep previous version
+pub struct HotReloadSystem {
+    watcher: AssetWatcher,
+    ///files each shader of AssetSources is made of, includes too
+    shader_files: HashMap<(String, String), Vec<String>>,
+}
+impl HotReloadSystem {
+    ///watcher should watch directory mounted into AssetServer's Vfs,
+    ///otherwise changed files are read from other layers
+    pub fn new(watcher: AssetWatcher) -> Self {
+        Self {
+            watcher,
+            shader_files: HashMap::new(),
+        }
+    }
+}
+///Every changed file is loaded once and shared by all entities using it
+#[derive(Default)]
+struct Reloaded {
+    textures: HashMap<String, Option<Texture2D>>,
+    models: HashMap<String, Option<InstancedModel>>,
+    shaders: HashMap<(String, String), Option<Shader>>,
+    shader_files: HashMap<(String, String), Vec<String>>,
+}
+impl Reloaded {
+    fn texture(&mut self, server: &mut AssetServer, path: &str) -> Option<Texture2D> {
+        self.textures
+            .entry(path.to_owned())
+            .or_insert_with(|| match server.reload::<Texture2D, _>(path) {
+                Ok(texture) => Some((*texture).clone()),
+                Err(error) => {
+                    log::error!("failed to reload texture {}: {}", path, error);
+                    None
+                }
+            })
+            .clone()
+    }
+    fn model(&mut self, server: &mut AssetServer, path: &str) -> Option<InstancedModel> {
+        self.models
+            .entry(path.to_owned())
+            .or_insert_with(|| match server.reload::<Model<ModelVertex>, _>(path) {
+                Ok(model) => Some(model.instantiate()),
+                Err(error) => {
+                    log::error!("failed to reload model {}: {}", path, error);
+                    None
+                }
+            })
+            .clone()
+    }
+    fn shader(&mut self, server: &AssetServer, sources: &(String, String)) -> Option<Shader> {
+        let shader_files = &mut self.shader_files;
+        self.shaders
+            .entry(sources.clone())
+            .or_insert_with(|| {
+                let mut files = Vec::new();
+                let mut compile = |path: &str, shader_type| {
+                    let source = preprocess_shader(server.source(), path)?;
+                    files.extend_from_slice(&source.files);
+                    source.try_compile(shader_type)
+                };
+                let shader = compile(&sources.0, ShaderType::Vertex).and_then(|vertex| {
+                    let fragment = compile(&sources.1, ShaderType::Fragment)?;
+                    Shader::try_new([vertex, fragment])
+                });
+                //on failure previous files are kept, so fixing any of them triggers reload again
+                if shader.is_ok() {
+                    shader_files.insert(sources.clone(), files);
+                }
+                shader
+                    .inspect_err(|error| {
+                        log::error!(
+                            "failed to reload shader {} + {}: {}",
+                            sources.0,
+                            sources.1,
+                            error
+                        )
+                    })
+                    .ok()
+            })
+            .clone()
+    }
+}
+///Files shader is made of, only shaders themselves if they fail to preprocess
+fn shader_files(server: &AssetServer, sources: &(String, String)) -> Vec<String> {
+    [&sources.0, &sources.1]
+        .into_iter()
+        .flat_map(|path| match preprocess_shader(server.source(), path) {
+            Ok(source) => source.files,
+            Err(_) => normalize_path(path).into_iter().collect(),
+        })
+        .collect()
+}
+///true if path written by user points to changed asset or its import settings changed
+fn is_changed(path: &str, changed: &[String]) -> bool {
+    normalize_path(path).is_some_and(|path| changed.contains(&path) || changed.contains(&meta_path(&path)))
+}
+impl<'a> System<'a> for HotReloadSystem {
+    type SystemData = (
+        WriteExpect<'a, AssetServer>,
+        ReadStorage<'a, AssetSources>,
+        WriteStorage<'a, Material>,
+        WriteStorage<'a, MeshRenderer>,
+        WriteStorage<'a, Camera>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut server, sources, mut materials, mut mesh_renderers, mut cameras) = data;
+        let changed = match self.watcher.poll() {
+            Ok(changed) => changed,
+            Err(error) => {
+                log::error!("failed to scan {}: {}", self.watcher.root().display(), error);
+                return;
+            }
+        };
+        if changed.is_empty() {
+            return;
+        }
+        let mut reloaded = Reloaded::default();
+        for (sources, material, mesh_renderer) in
+            (&sources, (&mut materials).maybe(), (&mut mesh_renderers).maybe()).join()
+        {
+            let texture = sources.main_texture.as_deref().filter(|path| is_changed(path, &changed));
+            if let (Some(material), Some(path)) = (material, texture) {
+                if let Some(texture) = reloaded.texture(&mut server, path) {
+                    material.main_texture = texture;
+                }
+            }
+            let Some(mesh_renderer) = mesh_renderer else {
+                continue;
+            };
+            if let Some(path) = sources.model.as_deref().filter(|path| is_changed(path, &changed)) {
+                if let Some(model) = reloaded.model(&mut server, path) {
+                    mesh_renderer.model = model;
+                }
+            }
+            let shader_sources = sources.shader.as_ref().filter(|shader_sources| {
+                self.shader_files
+                    .entry((*shader_sources).clone())
+                    .or_insert_with(|| shader_files(&server, shader_sources))
+                    .iter()
+                    .any(|path| is_changed(path, &changed))
+            });
+            if let Some(shader_sources) = shader_sources {
+                if let Some(shader) = reloaded.shader(&server, shader_sources) {
+                    mesh_renderer.shader = Some(shader);
+                }
+            }
+        }
+        self.shader_files.extend(reloaded.shader_files);
+        for camera in (&mut cameras).join() {
+            for shader in camera.render_path.shaders_mut() {
+                if !changed.iter().any(|path| shader.uses(path)) {
+                    continue;
+                }
+                if let Err(error) = shader.reload(|path| preprocess_shader(server.source(), path)) {
+                    log::error!("failed to reload render path shader: {}", error);
+                }
+            }
+        }
+    }
+}
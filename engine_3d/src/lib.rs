@@ -1,4 +1,7 @@
 mod game_engine;
+pub mod asset_upload;
+pub mod hot_reload;
+pub mod scene;
 use animation::animation::morph::Morphable;
 pub use game_engine::*;
 use graphics::{objects::vertex::ModelVertex, resize_event::ResizeEvent};
@@ -26,6 +29,8 @@ pub fn init(world:&mut World){
     world.register::<Camera>();
     world.register::<Transform>();
     world.register::<Morphable<ModelVertex>>();
+    world.register::<hot_reload::AssetSources>();
+    world.register::<scene::SceneNode>();
     world.insert(ResizeEvent::default());
     world.insert(Sun::default());
     world.insert(MainCamera::default());
@@ -0,0 +1,67 @@
+use assets::gltf_importer::GltfScene;
+use math::Mat4;
+use rendering::mesh_renderer::MeshRenderer;
+use specs::*;
+use transform::Transform;
+
+///Node of spawned scene, transforms are already multiplied by parents
+#[derive(Debug, Clone)]
+pub struct SceneNode {
+    pub name: Option<String>,
+    ///index of node in scene
+    pub node: usize,
+    ///entity of parent node, for primitives it's entity of their node
+    pub parent: Option<Entity>,
+}
+impl Component for SceneNode {
+    type Storage = DenseVecStorage<Self>;
+}
+///Creates entity for every node of default scene and entity with MeshRenderer and Material
+///for every primitive of node's mesh. Must be called on the thread with GL context.
+///Returns entities of nodes by their index, None for nodes outside of default scene
+pub fn spawn_gltf(world: &mut World, scene: &GltfScene) -> Vec<Option<Entity>> {
+    let mut entities = vec![None; scene.nodes.len()];
+    let mut stack = scene
+        .roots
+        .iter()
+        .map(|&root| (root, Mat4::IDENTITY, None))
+        .collect::<Vec<_>>();
+    while let Some((index, parent_matrix, parent)) = stack.pop() {
+        //cycles are invalid glTF, every node is spawned once
+        let Some(node) = scene.nodes.get(index).filter(|_| entities[index].is_none()) else {
+            continue;
+        };
+        let matrix = parent_matrix * node.matrix();
+        let transform = Transform::from_matrix(matrix);
+        let entity = world
+            .create_entity()
+            .with(transform)
+            .with(SceneNode {
+                name: node.name.clone(),
+                node: index,
+                parent,
+            })
+            .build();
+        entities[index] = Some(entity);
+        for primitive in node.mesh.and_then(|mesh| scene.meshes.get(mesh)).into_iter().flat_map(|mesh| &mesh.primitives) {
+            let material = primitive
+                .material
+                .and_then(|material| scene.materials.get(material))
+                .cloned()
+                .unwrap_or_default();
+            world
+                .create_entity()
+                .with(transform)
+                .with(MeshRenderer::new(primitive.model.instantiate(), None))
+                .with(material)
+                .with(SceneNode {
+                    name: node.name.clone(),
+                    node: index,
+                    parent: Some(entity),
+                })
+                .build();
+        }
+        stack.extend(node.children.iter().map(|&child| (child, matrix, Some(entity))));
+    }
+    entities
+}
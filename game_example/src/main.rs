@@ -1,6 +1,6 @@
 
 pub mod free_cam;
-use engine_3d::{assets::{image_importer::ImageImporter, model_importer::Modelmporter, Assets}, graphics::objects::texture::{Filter, Texture2DBuilder}, math::{vec3, Vec3}, post_processing::{PostProcessing, PostProcessingContainer, PostProcessingSystem, SimpleColorProcessing}, rendering::{camera::{projection::{Perspective, Projection}, Camera, CameraTransform, MainCamera, OnResizeEvent}, composition::CompositionSystem, light::{Light, LightProperties}, lit_shading::DeferredPath, material::Material, mesh_renderer::MeshRenderer, render_system::RenderSystem}, specs::{Builder, DispatcherBuilder, World, WorldExt}, transform::Transform, window::WindowConfig, Application};
+use engine_3d::{asset_upload::AssetUploadSystem, assets::{AssetServer, AssetWatcher, Vfs}, hot_reload::{AssetSources, HotReloadSystem}, graphics::objects::{model::Model, texture::Texture2D, vertex::ModelVertex}, math::{vec3, Vec3}, post_processing::{PostProcessing, PostProcessingContainer, PostProcessingSystem, SimpleColorProcessing}, rendering::{camera::{projection::{Perspective, Projection}, Camera, CameraTransform, MainCamera, OnResizeEvent}, composition::CompositionSystem, light::{Light, LightProperties}, lit_shading::DeferredPath, material::Material, mesh_renderer::MeshRenderer, render_system::RenderSystem}, specs::{Builder, DispatcherBuilder, World, WorldExt}, transform::Transform, window::WindowConfig, Application};
 use free_cam::FreeCameraSystem;
 use rand::{Rng, SeedableRng};
 
@@ -20,7 +20,19 @@ fn main() {
         engine_3d::image::load_from_memory(include_bytes!("../ico.jpg")).expect("error with ico"),
     );
     let app = Application::new(win_conf);
-    let mut assets = Assets::from_data_file("assets.data").unwrap();
+    let mut vfs = Vfs::new();
+    vfs.mount_data_file("assets.data").unwrap();
+    //in debug builds files from assets folder override data file and are reloaded when changed
+    //without assets folder, e.g. when started from another directory, only data file is used
+    let watcher = if cfg!(debug_assertions) {
+        vfs.mount_directory("assets")
+            .and_then(|_| AssetWatcher::new("assets"))
+            .inspect_err(|error| log::warn!("assets folder isn't used, loading from data file only: {}", error))
+            .ok()
+    } else {
+        None
+    };
+    let mut assets = AssetServer::new(vfs);
     let mut world = World::new();
     engine_3d::init(&mut world);
     /* let model = assets
@@ -73,21 +85,24 @@ fn main() {
         .build();
     */
 
-    let checked_texture = Texture2DBuilder::new()
-        .image(assets.import_image("models/checked_texture.png").unwrap())
-        .filter(Filter::NearestLinearMipMap)
-        .build()
+    //loaded once and shared by every material that uses it
+    let checked_texture = assets
+        .load::<Texture2D, _>("models/checked_texture.png")
         .unwrap();
-    checked_texture.gen_mipmaps();
     //Plane
     let plane = assets
-        .import_model("models/plane.obj")
+        .load::<Model<ModelVertex>, _>("models/plane.obj")
         .unwrap()
         .instantiate();
 
     world
         .create_entity()
         .with(MeshRenderer::new(plane, None))
+        .with(
+            AssetSources::new()
+                .model("models/plane.obj")
+                .main_texture("models/checked_texture.png"),
+        )
         .with(Transform::new(
             vec3(0.0, 0.0, 0.0),
             Vec3::new(0.0, 0.0, 0.0),
@@ -96,18 +111,23 @@ fn main() {
         .with(Material {
             shininess: 1024.0,
             specular: 0.3,
-            main_texture: checked_texture.clone(),
+            main_texture: (*checked_texture).clone(),
             ..Default::default()
         })
         .build();
 
     let obstacles = assets
-        .import_model("models/obstacles.obj")
+        .load::<Model<ModelVertex>, _>("models/obstacles.obj")
         .unwrap()
         .instantiate();
     world
         .create_entity()
         .with(MeshRenderer::new(obstacles, None))
+        .with(
+            AssetSources::new()
+                .model("models/obstacles.obj")
+                .main_texture("models/checked_texture.png"),
+        )
         .with(Transform::new(
             vec3(0.0, 0.0, 0.0),
             Vec3::new(0.0, 90.0, 0.0),
@@ -115,7 +135,7 @@ fn main() {
         ))
         .with(Material {
             shininess: 16.0,
-            main_texture: checked_texture.clone(),
+            main_texture: (*checked_texture).clone(),
             ..Default::default()
         })
         .build();
@@ -142,6 +162,7 @@ fn main() {
         .with(postprocessing_container.clone())
         .build();
     world.fetch_mut::<MainCamera>().set(main_camera);
+    world.insert(assets);
 
     // world.insert(Sun::new(vec3(-1.0, -1.0, 0.0), vec3(0.9, 0.84, 0.19)));
     let n = 100;
@@ -181,7 +202,12 @@ fn main() {
         .with(Transform::from_position(vec3(1.0, 2.0, 30.0)))
         .build();
 
-    let dispatcher = DispatcherBuilder::new()
+    let mut dispatcher = DispatcherBuilder::new();
+    dispatcher.add_thread_local(AssetUploadSystem::default());
+    if let Some(watcher) = watcher {
+        dispatcher.add_thread_local(HotReloadSystem::new(watcher));
+    }
+    let dispatcher = dispatcher
         .with(
             FreeCameraSystem {
                 sensetivity: 20.0,